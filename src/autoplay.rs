@@ -0,0 +1,69 @@
+use rand::Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use ride_the_bus::PlayingCard;
+use ride_the_bus::decision::DiscreteDecision;
+use ride_the_bus::decision::event::{DeckSpec, RandomEvent};
+use ride_the_bus::decision::solver::{DiscreteDecisionTree, LeafPath};
+
+/// Plays a hand from `tree` to the end, taking the optimal choice at every decision
+/// and drawing the event that resolves it from its probabilities
+///
+/// Cashing out doesn't depend on the event, so it's left out of the path
+pub fn play_optimally<'a, E: RandomEvent>(
+    tree: &'a DiscreteDecisionTree<E>,
+    rng: &mut impl Rng,
+) -> LeafPath<'a, E> {
+    let mut path = LeafPath {
+        steps: Vec::new(),
+        probability: 1.0,
+        payout: tree.pot(),
+    };
+    let mut tree = tree;
+    while let Some(optimal) = tree.optimal() {
+        let mut roll = rng.random::<f64>();
+        let mut drawn = None;
+        for (probability, outcome) in optimal.iter_probabilities() {
+            drawn = Some((probability, outcome));
+            if roll < probability {
+                break;
+            }
+            roll -= probability;
+        }
+        // the probabilities may sum to slightly less than 1, so fall back to the last event
+        let Some((probability, outcome)) = drawn else {
+            break;
+        };
+        let event = outcome.event.filter(|_| !optimal.choice.is_cashout());
+        path.steps.push((optimal, event));
+        path.probability *= probability;
+        path.payout = outcome.value;
+        match outcome.next_decision() {
+            Some(next) => tree = next,
+            None => break,
+        }
+    }
+    path
+}
+
+/// Plays a hand of the game by its rules, taking the optimal choices of `tree` (solved
+/// from `first_decision`) and dealing from a shuffled `deck`
+///
+/// Unlike [`play_optimally`], the hand is scored from the cards dealt rather than the
+/// values in the tree (see [`DiscreteDecisionTree::play`]), so it can check them
+pub fn play_from_deck(
+    tree: &DiscreteDecisionTree,
+    first_decision: DiscreteDecision,
+    deck: &DeckSpec,
+    rng: &mut impl Rng,
+) -> Option<f64> {
+    let mut cards = deck.cards();
+    // a reshuffled deck can deal any card every time
+    if deck.reshuffles() {
+        return tree.play(first_decision, |_: &[PlayingCard]| {
+            cards.choose(rng).copied()
+        });
+    }
+    cards.shuffle(rng);
+    let mut cards = cards.into_iter();
+    tree.play(first_decision, |_: &[PlayingCard]| cards.next())
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// What changed the balance of the bankroll
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// The balance was set with the `bankroll` command
+    Set,
+    /// A hand won the pot
+    Win,
+    /// A hand lost the pot, though some of it may have been refunded
+    Lose,
+    /// A hand was cashed out
+    Cashout,
+}
+
+/// A change to the balance, as kept in the ledger (one line of JSON per entry)
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    /// When the balance changed, in milliseconds since the unix epoch
+    pub time: u64,
+    pub kind: Kind,
+    /// How much the balance changed by
+    pub amount: f64,
+    /// The balance after the change
+    pub balance: f64,
+}
+
+/// The ledger of the bankroll, kept in the data directory across runs
+///
+/// Hands only change the balance once it's been set, so there's no ledger until then
+pub struct Ledger {
+    path: PathBuf,
+    pub entries: Vec<Entry>,
+}
+impl Ledger {
+    /// Loads the ledger from the data directory (see [`data_dir`]), which has no entries
+    /// if it hasn't been started
+    pub fn load() -> io::Result<Self> {
+        let path = data_dir()
+            .ok_or_else(|| io::Error::other("no data directory (HOME isn't set)"))?
+            .join("bankroll.jsonl");
+        let entries = match File::open(&path) {
+            Ok(file) => BufReader::new(file)
+                .lines()
+                .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+                .map(|line| serde_json::from_str(&line?).map_err(io::Error::other))
+                .collect::<io::Result<_>>()?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self { path, entries })
+    }
+    /// The current balance, or `None` if it hasn't been set
+    pub fn balance(&self) -> Option<f64> {
+        self.entries.last().map(|entry| entry.balance)
+    }
+    /// Sets the balance, e.g. to start the ledger or after a deposit
+    pub fn set(&mut self, balance: f64) -> io::Result<()> {
+        let amount = balance - self.balance().unwrap_or(0.0);
+        self.push(Kind::Set, amount, balance)
+    }
+    /// Changes the balance by the `amount` won (or lost, if negative) on a hand, if
+    /// the balance has been set
+    ///
+    /// Returns the entry added, if any
+    pub fn record(&mut self, kind: Kind, amount: f64) -> io::Result<Option<&Entry>> {
+        let Some(balance) = self.balance() else {
+            return Ok(None);
+        };
+        self.push(kind, amount, balance + amount)?;
+        Ok(self.entries.last())
+    }
+
+    fn push(&mut self, kind: Kind, amount: f64, balance: f64) -> io::Result<()> {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        let entry = Entry {
+            time,
+            kind,
+            amount,
+            balance,
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(&entry).map_err(io::Error::other)?;
+        writeln!(file, "{}", line)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+/// The directory data is kept in across runs
+///
+/// This is `$XDG_DATA_HOME/ride-the-bus` (or `~/.local/share/ride-the-bus`) on Linux,
+/// `~/Library/Application Support/ride-the-bus` on macOS, and `%APPDATA%\ride-the-bus`
+/// on Windows
+pub fn data_dir() -> Option<PathBuf> {
+    let non_empty = |var: &str| env::var_os(var).filter(|value| !value.is_empty());
+    let base = if cfg!(windows) {
+        PathBuf::from(non_empty("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(non_empty("HOME")?).join("Library/Application Support")
+    } else {
+        match non_empty("XDG_DATA_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(non_empty("HOME")?).join(".local/share"),
+        }
+    };
+    Some(base.join("ride-the-bus"))
+}
@@ -0,0 +1,154 @@
+use crate::PlayingCard;
+use crate::config::GameConfig;
+use crate::decision::solver::{DiscreteDecisionTree, SolveOptions};
+use crate::game_file::GameFile;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
+use tracing::{debug, info};
+
+/// The version of the game's rules and payouts, which invalidates caches made
+/// with other versions
+///
+/// Bump this whenever the choices, their payouts, or the solver change
+const RULES_VERSION: u32 = 13;
+
+/// Written before the tree, to check whether the cache can be used
+#[derive(Serialize, Deserialize, PartialEq)]
+struct Header {
+    rules_version: u32,
+    options: SolveOptions,
+    rules: GameConfig,
+    /// The game file written as TOML, since its picks can't be read back without a
+    /// self-describing format
+    game: Option<String>,
+    removed: Vec<PlayingCard>,
+}
+
+/// Loads the tree cached at `path`, if it was solved with the same rules version,
+/// `options`, `rules`, `game`, and `removed` cards
+///
+/// Returns `Ok(None)` if there is no cache or it's out of date
+pub fn load(
+    path: &Path,
+    options: SolveOptions,
+    rules: GameConfig,
+    game: Option<&GameFile>,
+    removed: &[PlayingCard],
+) -> io::Result<Option<DiscreteDecisionTree>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            debug!(path = %path.display(), "no cache");
+            return Ok(None);
+        }
+        Err(err) => return Err(err),
+    };
+    let mut reader = BufReader::new(file);
+    let expected = Header {
+        rules_version: RULES_VERSION,
+        options,
+        rules,
+        game: game_key(game)?,
+        removed: removed.to_vec(),
+    };
+    // a header that can't be read is from an older format, so it's out of date too
+    match bincode::serde::decode_from_std_read::<Header, _, _>(&mut reader, config()) {
+        Ok(header) if header == expected => {}
+        _ => {
+            info!(path = %path.display(), "cache is out of date");
+            return Ok(None);
+        }
+    }
+    debug!(path = %path.display(), "loading cache");
+    bincode::serde::decode_from_std_read(&mut reader, config())
+        .map(Some)
+        .map_err(io::Error::other)
+}
+
+/// Caches the tree to `path`, solved with `options`, `rules`, `game`, and `removed` cards
+pub fn save(
+    path: &Path,
+    tree: &DiscreteDecisionTree,
+    options: SolveOptions,
+    rules: GameConfig,
+    game: Option<&GameFile>,
+    removed: &[PlayingCard],
+) -> io::Result<()> {
+    debug!(path = %path.display(), "saving cache");
+    let mut writer = BufWriter::new(File::create(path)?);
+    let header = Header {
+        rules_version: RULES_VERSION,
+        options,
+        rules,
+        game: game_key(game)?,
+        removed: removed.to_vec(),
+    };
+    bincode::serde::encode_into_std_write(&header, &mut writer, config())
+        .map_err(io::Error::other)?;
+    bincode::serde::encode_into_std_write(tree, &mut writer, config()).map_err(io::Error::other)?;
+    writer.flush()
+}
+
+fn game_key(game: Option<&GameFile>) -> io::Result<Option<String>> {
+    game.map(|game| toml::to_string(game).map_err(io::Error::other))
+        .transpose()
+}
+
+fn config() -> bincode::config::Configuration {
+    bincode::config::standard()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DeckPreset;
+
+    #[test]
+    fn solves_cached_trees_again_at_the_table() {
+        // a piquet deck without 7 through 10, so it solves quickly
+        let mut rules = GameConfig::default();
+        rules.deck.preset = DeckPreset::Piquet;
+        rules.deck.stripped_ranks = (7..=10).fold(0, |ranks, rank| ranks | 1 << rank);
+        let options = SolveOptions::default();
+        let first_decision = || crate::first_decision(rules, None);
+        let tree = DiscreteDecisionTree::solve_with(first_decision(), &rules.deck(), options);
+
+        let path = std::env::temp_dir().join(format!("ride-the-bus-{}.cache", std::process::id()));
+        save(&path, &tree, options, rules, None, &[]).unwrap();
+        let cached = load(&path, options, rules, None, &[]).unwrap().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // red was picked on the jack of hearts, then the king of spades was dealt to
+        // another seat at the table
+        let jack = "JH".parse().ok();
+        let step = [("red", jack)];
+        let next = cached.descend("red", jack).unwrap();
+        let deck = rules.deck().without_card("KS".parse().ok().unwrap());
+        let resolved =
+            DiscreteDecisionTree::solve_at(first_decision(), &step, &deck, cached.options())
+                .unwrap();
+        let names = |tree: &DiscreteDecisionTree| {
+            tree.iter()
+                .map(|choice| format!("{:?}", choice.choice))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&resolved), names(next));
+        assert_eq!(resolved.pot(), next.pot());
+
+        // without cards at the table, it's solved the same as before it was cached
+        let same = DiscreteDecisionTree::solve_at(
+            first_decision(),
+            &step,
+            &rules.deck(),
+            cached.options(),
+        )
+        .unwrap();
+        for (choice, cached) in same.iter().zip(next.iter()) {
+            assert!((choice.expected_value - cached.expected_value).abs() < 1e-9);
+        }
+    }
+}
@@ -1,11 +1,19 @@
 use std::str::FromStr;
 
-/// Simple representation of a standard playing card using values 0-52
+/// Simple representation of a standard playing card using values 0-52, or a
+/// joker using 52
 ///
 /// First (least significant) 4 bits are used to determine suit
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct PlayingCard(u8);
 impl PlayingCard {
+    /// A joker, which has no real rank or suit (see [`PlayingCard::is_joker`])
+    pub const JOKER: Self = Self(52);
+
+    /// Whether the card is a joker, whose rank, suit, and color are meaningless
+    pub fn is_joker(self) -> bool {
+        self == Self::JOKER
+    }
     /// * hearts == 0
     /// * diamonds == 1
     /// * spades == 2
@@ -27,6 +35,48 @@ impl PlayingCard {
         // remove suit bits
         (self.0 >> 2) + 2
     }
+    /// The rank (see [`PlayingCard::rank`]), but with the ace ranked 1 instead of 14,
+    /// for games that play ace low
+    pub fn rank_ace_low(self) -> u8 {
+        match self.rank() {
+            14 => 1,
+            rank => rank,
+        }
+    }
+
+    /// The rank as shown on the card face (`2`-`10`, `J`, `Q`, `K`, `A`), or `JK`
+    /// for a joker
+    pub fn rank_label(self) -> &'static str {
+        match self.is_joker() {
+            true => "JK",
+            false => RANK_LABELS[(self.rank() - 2) as usize],
+        }
+    }
+    /// The suit as a single letter (`H`, `D`, `S`, `C`), or nothing for a joker
+    pub fn suit_label(self) -> &'static str {
+        match self.is_joker() {
+            true => "",
+            false => SUIT_LABELS[self.suit() as usize],
+        }
+    }
+    /// The name of the rank (e.g. `queen`), or `joker` for a joker
+    pub fn rank_name(self) -> &'static str {
+        match self.is_joker() {
+            true => "joker",
+            false => RANK_NAMES[(self.rank() - 2) as usize],
+        }
+    }
+    /// The name of the suit (e.g. `diamonds`), or nothing for a joker
+    pub fn suit_name(self) -> &'static str {
+        match self.is_joker() {
+            true => "",
+            false => SUIT_NAMES[self.suit() as usize],
+        }
+    }
+    /// The index of the card in [`PlayingCard::deck_iter`] (52 for a joker)
+    pub(crate) fn index(self) -> usize {
+        self.0 as usize
+    }
 
     /// An iterator over an entire deck of playing cards
     pub fn deck_iter() -> impl Iterator<Item = Self> {
@@ -34,19 +84,46 @@ impl PlayingCard {
     }
 }
 
-const RANK_LABELS: &[&str] = &[
+/// The labels of the ranks, from two to ace, as cards are written (e.g. `10H`)
+pub const RANK_LABELS: &[&str] = &[
     "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A",
 ];
 const SUIT_LABELS: &[&str] = &["H", "D", "S", "C"];
+/// The names of the ranks, from two to ace
+pub const RANK_NAMES: &[&str] = &[
+    "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "jack", "queen",
+    "king", "ace",
+];
+/// The names of the suits, in the order of [`PlayingCard::suit`]
+pub const SUIT_NAMES: &[&str] = &["hearts", "diamonds", "spades", "clubs"];
 
 impl std::fmt::Display for PlayingCard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}{}",
-            RANK_LABELS[(self.rank() - 2) as usize],
-            SUIT_LABELS[self.suit() as usize]
-        )
+        write!(f, "{}{}", self.rank_label(), self.suit_label())
+    }
+}
+
+// cards are serialized as they're displayed (e.g. `10H`) for human readable formats,
+// otherwise as their value since there are millions of them in a solved tree
+impl serde::Serialize for PlayingCard {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match serializer.is_human_readable() {
+            true => serializer.collect_str(self),
+            false => serializer.serialize_u8(self.0),
+        }
+    }
+}
+impl<'de> serde::Deserialize<'de> for PlayingCard {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return match u8::deserialize(deserializer)? {
+                value @ 0..=52 => Ok(Self(value)),
+                value => Err(serde::de::Error::custom(format!("invalid card {}", value))),
+            };
+        }
+        let s = <&str>::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| serde::de::Error::custom(format!("invalid card '{}'", s)))
     }
 }
 
@@ -55,10 +132,88 @@ impl FromStr for PlayingCard {
     type Err = InvalidCardError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.to_uppercase();
-        // kinda shit but it works and is fast enough
-        Self::deck_iter()
-            .find(|c| format!("{}", c) == s)
-            .ok_or(InvalidCardError)
+        if s.eq_ignore_ascii_case("JK") {
+            return Ok(Self::JOKER);
+        }
+        // the suit is always the last character, and the rank is everything before it
+        let (rank, suit) = match s.as_bytes() {
+            [rank @ .., suit] => (rank, suit),
+            [] => return Err(InvalidCardError),
+        };
+        let suit = match suit.to_ascii_uppercase() {
+            b'H' => 0,
+            b'D' => 1,
+            b'S' => 2,
+            b'C' => 3,
+            _ => return Err(InvalidCardError),
+        };
+        let rank = match rank {
+            [digit @ b'2'..=b'9'] => digit - b'0',
+            b"10" => 10,
+            [face] => match face.to_ascii_uppercase() {
+                b'J' => 11,
+                b'Q' => 12,
+                b'K' => 13,
+                b'A' => 14,
+                _ => return Err(InvalidCardError),
+            },
+            _ => return Err(InvalidCardError),
+        };
+        Ok(Self(((rank - 2) << 2) | suit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_card() {
+        for card in PlayingCard::deck_iter() {
+            assert_eq!(card.to_string().parse().ok(), Some(card));
+            assert_eq!(card.to_string().to_lowercase().parse().ok(), Some(card));
+        }
+    }
+
+    #[test]
+    fn parses_rank_and_suit() {
+        let card = "10h".parse::<PlayingCard>().ok().unwrap();
+        assert_eq!((card.rank(), card.suit()), (10, 0));
+        let card = "Qc".parse::<PlayingCard>().ok().unwrap();
+        assert_eq!((card.rank(), card.suit()), (12, 3));
+    }
+
+    #[test]
+    fn ranks_aces_low() {
+        let ace = "AS".parse::<PlayingCard>().ok().unwrap();
+        assert_eq!((ace.rank(), ace.rank_ace_low()), (14, 1));
+        let king = "KS".parse::<PlayingCard>().ok().unwrap();
+        assert_eq!(king.rank_ace_low(), 13);
+    }
+
+    #[test]
+    fn round_trips_jokers() {
+        assert_eq!(PlayingCard::JOKER.to_string(), "JK");
+        assert_eq!("jk".parse().ok(), Some(PlayingCard::JOKER));
+        assert!(PlayingCard::deck_iter().all(|card| !card.is_joker()));
+    }
+
+    #[test]
+    fn names_ranks_and_suits() {
+        let card = "QD".parse::<PlayingCard>().ok().unwrap();
+        assert_eq!((card.rank_name(), card.suit_name()), ("queen", "diamonds"));
+        let card = "2C".parse::<PlayingCard>().ok().unwrap();
+        assert_eq!((card.rank_name(), card.suit_name()), ("two", "clubs"));
+        let joker = PlayingCard::JOKER;
+        assert_eq!((joker.rank_name(), joker.suit_name()), ("joker", ""));
+    }
+
+    #[test]
+    fn rejects_invalid_cards() {
+        for s in [
+            "", "H", "1H", "11H", "0H", "010H", "2X", "ZH", " 2H", "2H ", "10", "♥2",
+        ] {
+            assert!(s.parse::<PlayingCard>().is_err(), "parsed {s:?}");
+        }
     }
 }
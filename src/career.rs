@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A hand finished in any run, as kept in the career file (one line of JSON per hand)
+#[derive(Serialize, Deserialize)]
+pub struct Hand {
+    /// When the hand finished, in milliseconds since the unix epoch
+    pub time: u64,
+    pub bet: f64,
+    /// The final pot, as a multiple of the bet
+    pub pot: f64,
+    /// The EV of the hand when it started under optimal play, as a multiple of the bet
+    pub expected: f64,
+}
+impl Hand {
+    /// A hand finishing now
+    pub fn new(bet: f64, pot: f64, expected: f64) -> Self {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        Self {
+            time,
+            bet,
+            pot,
+            expected,
+        }
+    }
+    /// Whether less than the bet came back
+    pub fn lost(&self) -> bool {
+        self.pot < 1.0
+    }
+}
+
+/// Adds a finished hand to the end of the career file at `path`, creating it if needed
+pub fn append(path: &Path, hand: &Hand) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let line = serde_json::to_string(hand).map_err(io::Error::other)?;
+    writeln!(file, "{}", line)
+}
+/// Loads every hand in the career file at `path`, in the order they finished
+///
+/// A career file that doesn't exist yet has no hands
+pub fn load(path: &Path) -> io::Result<Vec<Hand>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    BufReader::new(file)
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| serde_json::from_str(&line?).map_err(io::Error::other))
+        .collect()
+}
+
+/// The totals of every hand in a career
+#[derive(Default)]
+pub struct Career {
+    pub hands: usize,
+    /// The total bet over every hand
+    pub staked: f64,
+    /// The total paid out
+    pub returned: f64,
+    /// The total expected to be paid out under optimal play
+    pub expected: f64,
+    /// The hand that won the most above its bet
+    pub biggest_win: Option<(f64, f64)>,
+    /// The most hands lost in a row
+    pub longest_losing_streak: usize,
+}
+impl Career {
+    pub fn of(hands: &[Hand]) -> Self {
+        let mut career = Self::default();
+        let mut streak = 0;
+        for hand in hands {
+            career.hands += 1;
+            career.staked += hand.bet;
+            career.returned += hand.pot * hand.bet;
+            career.expected += hand.expected * hand.bet;
+            let won = (hand.pot - 1.0) * hand.bet;
+            if won > 0.0 && career.biggest_win.is_none_or(|(_, biggest)| won > biggest) {
+                career.biggest_win = Some((hand.pot, won));
+            }
+            streak = match hand.lost() {
+                true => streak + 1,
+                false => 0,
+            };
+            career.longest_losing_streak = career.longest_losing_streak.max(streak);
+        }
+        career
+    }
+    /// The return on everything bet, e.g. `0.1` for 10% more than was bet coming back
+    pub fn realized_return(&self) -> f64 {
+        self.returned / self.staked - 1.0
+    }
+    /// The return expected on everything bet under optimal play
+    pub fn expected_return(&self) -> f64 {
+        self.expected / self.staked - 1.0
+    }
+}
@@ -0,0 +1,119 @@
+use crate::decision::solver::DiscreteDecisionTree;
+use crate::export;
+use plotters::{coord::Shift, prelude::*};
+use std::{error::Error, path::Path};
+
+const SIZE: (u32, u32) = (800, 480);
+
+/// Writes every chart for the tree as SVG files into the directory `dir`
+///
+/// Returns the paths of the charts written
+pub fn write_charts(
+    tree: &DiscreteDecisionTree,
+    dir: &Path,
+) -> Result<Vec<std::path::PathBuf>, Box<dyn Error>> {
+    std::fs::create_dir_all(dir)?;
+    let ev_path = dir.join("ev_by_stage.svg");
+    draw_ev_by_stage(tree, SVGBackend::new(&ev_path, SIZE).into_drawing_area())?;
+    let payouts_path = dir.join("payout_distribution.svg");
+    draw_payout_distribution(
+        tree,
+        SVGBackend::new(&payouts_path, SIZE).into_drawing_area(),
+    )?;
+    Ok(vec![ev_path, payouts_path])
+}
+
+/// Renders every chart for the tree as SVG documents, in the same order as
+/// [`write_charts`]
+pub fn render_charts(tree: &DiscreteDecisionTree) -> Result<Vec<String>, Box<dyn Error>> {
+    let (mut ev, mut payouts) = (String::new(), String::new());
+    draw_ev_by_stage(
+        tree,
+        SVGBackend::with_string(&mut ev, SIZE).into_drawing_area(),
+    )?;
+    draw_payout_distribution(
+        tree,
+        SVGBackend::with_string(&mut payouts, SIZE).into_drawing_area(),
+    )?;
+    Ok(vec![ev, payouts])
+}
+
+/// Bar chart of the average optimal EV at each stage that can be reached
+fn draw_ev_by_stage(
+    tree: &DiscreteDecisionTree,
+    root: DrawingArea<SVGBackend, Shift>,
+) -> Result<(), Box<dyn Error>> {
+    let stages = export::stage_values(tree);
+    let max_ev = stages.iter().map(|s| s.expected_value).fold(1.0, f64::max);
+
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Expected Value by Stage (optimal play)", ("sans-serif", 24))
+        .margin(16)
+        .x_label_area_size(40)
+        .y_label_area_size(56)
+        .build_cartesian_2d((1..stages.len()).into_segmented(), 0.0..max_ev * 1.1)?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_desc("Stage")
+        .y_desc("Expected Value (x bet)")
+        .draw()?;
+    chart.draw_series(
+        Histogram::vertical(&chart)
+            .style(BLUE.mix(0.6).filled())
+            .margin(24)
+            .data(
+                stages
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| (i + 1, s.expected_value)),
+            ),
+    )?;
+    root.present()?;
+    Ok(())
+}
+
+/// Bar chart of the probability of each final payout when playing optimally
+fn draw_payout_distribution(
+    tree: &DiscreteDecisionTree,
+    root: DrawingArea<SVGBackend, Shift>,
+) -> Result<(), Box<dyn Error>> {
+    let distribution = tree
+        .optimal()
+        .map(|c| c.payout_distribution())
+        .unwrap_or_default();
+    let labels = distribution
+        .iter()
+        .map(|(pot, _)| format!("{:.02}x", pot))
+        .collect::<Vec<_>>();
+
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Payout Distribution (optimal play)", ("sans-serif", 24))
+        .margin(16)
+        .x_label_area_size(40)
+        .y_label_area_size(56)
+        .build_cartesian_2d(
+            (0..distribution.len().saturating_sub(1)).into_segmented(),
+            0.0..1.0,
+        )?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_desc("Final Pot")
+        .y_desc("Probability")
+        .x_label_formatter(&|x| match x {
+            SegmentValue::CenterOf(i) => labels.get(*i).cloned().unwrap_or_default(),
+            _ => String::new(),
+        })
+        .draw()?;
+    chart.draw_series(
+        Histogram::vertical(&chart)
+            .style(GREEN.mix(0.6).filled())
+            .margin(24)
+            .data(distribution.iter().enumerate().map(|(i, &(_, p))| (i, p))),
+    )?;
+    root.present()?;
+    Ok(())
+}
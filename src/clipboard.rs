@@ -0,0 +1,67 @@
+use std::io;
+#[cfg(not(target_os = "wasi"))]
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Programs that copy their stdin to the system clipboard, tried in order
+#[cfg(not(target_os = "wasi"))]
+const COPY_PROGRAMS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["clip.exe"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Copies the text to the system clipboard
+///
+/// This uses whichever clipboard program is installed (`pbcopy` on macOS,
+/// `clip.exe` on Windows, and `wl-copy`, `xclip`, or `xsel` on Linux)
+#[cfg(not(target_os = "wasi"))]
+pub fn copy(text: &str) -> io::Result<()> {
+    for program in COPY_PROGRAMS {
+        let child = Command::new(program[0])
+            .args(&program[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        // try the next program if this one isn't installed
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+
+        child
+            .stdin
+            .take()
+            .expect("piped stdin")
+            .write_all(text.as_bytes())?;
+        let status = child.wait()?;
+        return match status.success() {
+            true => Ok(()),
+            false => Err(io::Error::other(format!(
+                "{} exited with {}",
+                program[0], status
+            ))),
+        };
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no clipboard program found (install wl-copy, xclip, or xsel)",
+    ))
+}
+
+/// Copies the text to the system clipboard
+///
+/// WASI modules can't start other programs, so there's no clipboard to copy to
+#[cfg(target_os = "wasi")]
+pub fn copy(_: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the clipboard isn't available when running as a WASI module",
+    ))
+}
@@ -0,0 +1,173 @@
+use crate::PlayingCard;
+use crate::decision::solver::{ChoiceEval, DiscreteDecisionTree};
+use crate::export;
+
+/// Headline numbers for playing a solved tree's strategy from its first decision
+pub struct Metrics {
+    pub expected_value: f64,
+    pub bust_probability: f64,
+    /// The largest pot that can be won in the game, following any strategy
+    pub max_payout: f64,
+    /// The probability of winning the [`Metrics::max_payout`]
+    pub max_payout_probability: f64,
+    /// The probability of cashing out at each stage (index 0 is the first decision)
+    pub cashout_probabilities: Vec<f64>,
+}
+impl Metrics {
+    /// Computes the metrics for following the optimal choices of the tree
+    pub fn of(tree: &DiscreteDecisionTree) -> Self {
+        let Some(optimal) = tree.optimal() else {
+            return Self {
+                expected_value: 0.0,
+                bust_probability: 0.0,
+                max_payout: 0.0,
+                max_payout_probability: 0.0,
+                cashout_probabilities: Vec::new(),
+            };
+        };
+        let max_payout = max_payout(tree);
+        let max_payout_probability = optimal
+            .payout_distribution()
+            .into_iter()
+            .filter(|(pot, _)| (pot - max_payout).abs() < 1e-6)
+            .fold(0.0, |sum, (_, probability)| sum + probability);
+        let mut cashout_probabilities = Vec::new();
+        add_cashouts(tree, 0, 1.0, &mut cashout_probabilities);
+
+        Self {
+            expected_value: optimal.expected_value,
+            bust_probability: optimal.bust_probability,
+            max_payout,
+            max_payout_probability,
+            cashout_probabilities,
+        }
+    }
+}
+fn max_payout(tree: &DiscreteDecisionTree) -> f64 {
+    tree.iter()
+        .flat_map(|c| c.iter())
+        .map(|o| o.next_decision().map(max_payout).unwrap_or(o.value))
+        .fold(0.0, f64::max)
+}
+fn add_cashouts(tree: &DiscreteDecisionTree, depth: usize, probability: f64, out: &mut Vec<f64>) {
+    let Some(optimal) = tree.optimal() else {
+        return;
+    };
+    if out.len() <= depth {
+        out.resize(depth + 1, 0.0);
+    }
+    if optimal.choice.is_cashout() {
+        out[depth] += probability;
+        return;
+    }
+
+    // choices that don't end the stage (e.g. don't reveal a card) lead to another
+    // decision in the same stage
+    let depth = depth + usize::from(optimal.choice.ends_stage());
+    for (outcome_probability, outcome) in optimal.iter_probabilities() {
+        if let Some(next) = outcome.next_decision() {
+            add_cashouts(next, depth, probability * outcome_probability, out);
+        }
+    }
+}
+
+/// The final pots of following a policy, see [`policy_payouts`]
+pub struct PolicyPayouts {
+    /// The probability of each final pot, sorted by the pot
+    pub distribution: Vec<(f64, f64)>,
+    /// The probability of losing the pot (or stopping with nothing won), even if
+    /// some of it was refunded or returned by a side bet
+    pub bust_probability: f64,
+}
+
+/// Finds the probability of each final pot when following a policy from the first
+/// decision of the tree
+///
+/// The policy picks a choice for a decision given its stage (from 1) and the cards
+/// seen so far (backwards, like [`crate::Choice::score`]), or `None` to stop (as if
+/// the hand was abandoned with nothing won)
+pub fn policy_payouts<P>(tree: &DiscreteDecisionTree, policy: &P) -> PolicyPayouts
+where
+    P: for<'a> Fn(&'a DiscreteDecisionTree, usize, &[PlayingCard]) -> Option<&'a ChoiceEval>,
+{
+    let mut payouts = PolicyPayouts {
+        distribution: Vec::new(),
+        bust_probability: 0.0,
+    };
+    add_policy_payouts(tree, &mut Vec::new(), 1.0, policy, &mut payouts);
+    payouts
+        .distribution
+        .sort_by(|(p1, _), (p2, _)| f64::total_cmp(p1, p2));
+    payouts
+}
+fn add_policy_payouts<P>(
+    tree: &DiscreteDecisionTree,
+    history: &mut Vec<PlayingCard>,
+    probability: f64,
+    policy: &P,
+    payouts: &mut PolicyPayouts,
+) where
+    P: for<'a> Fn(&'a DiscreteDecisionTree, usize, &[PlayingCard]) -> Option<&'a ChoiceEval>,
+{
+    let Some(choice) = policy(tree, history.len() + 1, history) else {
+        add_payout(0.0, probability, &mut payouts.distribution);
+        payouts.bust_probability += probability;
+        return;
+    };
+    for (outcome_probability, outcome) in choice.iter_probabilities() {
+        let probability = probability * outcome_probability;
+        match (outcome.next_decision(), outcome.event) {
+            (Some(next), Some(event)) => {
+                history.insert(0, event);
+                add_policy_payouts(next, history, probability, policy, payouts);
+                history.remove(0);
+            }
+            // decisions reached without revealing a card are still the same stage
+            (Some(next), None) => add_policy_payouts(next, history, probability, policy, payouts),
+            (None, _) => {
+                add_payout(outcome.value, probability, &mut payouts.distribution);
+                if outcome.is_lost() {
+                    payouts.bust_probability += probability;
+                }
+            }
+        }
+    }
+}
+fn add_payout(pot: f64, probability: f64, distribution: &mut Vec<(f64, f64)>) {
+    match distribution
+        .iter_mut()
+        .find(|(p, _)| (p - pot).abs() < 1e-6)
+    {
+        Some((_, p)) => *p += probability,
+        None => distribution.push((pot, probability)),
+    }
+}
+
+/// A stage and state where two trees recommend different choices
+pub struct Difference {
+    pub stage: usize,
+    pub state: String,
+    pub choices: (String, String),
+}
+
+/// Finds every stage and state (see [`export::stage_strategy`]) where the
+/// optimal choices of the two trees differ
+pub fn differences(
+    tree1: &DiscreteDecisionTree,
+    tree2: &DiscreteDecisionTree,
+    state_of: impl Fn(&[PlayingCard]) -> String,
+) -> Vec<Difference> {
+    let strategy1 = export::stage_strategy(tree1, &state_of);
+    let mut strategy2 = export::stage_strategy(tree2, &state_of);
+    strategy1
+        .into_iter()
+        .filter_map(|(key, row1)| {
+            let row2 = strategy2.remove(&key)?;
+            (row1.choice != row2.choice).then_some(Difference {
+                stage: key.0,
+                state: key.1,
+                choices: (row1.choice, row2.choice),
+            })
+        })
+        .collect()
+}
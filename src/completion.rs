@@ -0,0 +1,51 @@
+use crate::{COMMAND_NAMES, PlayingCard};
+use rustyline::{
+    Context, Helper,
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+};
+
+/// Completes the words of a command line with tab: the command names for the first
+/// word, and the names of the current choices for the rest, along with card codes
+/// anywhere (since a card on its own is a command)
+#[derive(Default)]
+pub struct CommandCompleter {
+    /// The names of the choices of the current decision, e.g. `Red` and `Black`
+    pub choices: Vec<String>,
+}
+impl Completer for CommandCompleter {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos].rfind(' ').map_or(0, |i| i + 1);
+        let word = line[start..pos].to_lowercase();
+        let names = match start {
+            0 => COMMAND_NAMES.iter().map(|&name| name.to_owned()).collect(),
+            _ => self.choices.clone(),
+        };
+        let candidates = names
+            .into_iter()
+            .chain(PlayingCard::deck_iter().map(|card| card.to_string()))
+            .filter(|candidate| candidate.to_lowercase().starts_with(&word))
+            // a completed word is followed by a space, ready for the next one
+            .map(|candidate| Pair {
+                replacement: format!("{} ", candidate),
+                display: candidate,
+            })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+impl Hinter for CommandCompleter {
+    type Hint = String;
+}
+impl Highlighter for CommandCompleter {}
+impl Validator for CommandCompleter {}
+impl Helper for CommandCompleter {}
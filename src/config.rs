@@ -0,0 +1,505 @@
+use ride_the_bus::PlayingCard;
+use ride_the_bus::decision::event::DeckSpec;
+use ride_the_bus::decision::solver::{DoubleDown, Mulligan};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fs, io, path::Path, str::FromStr};
+
+/// The rules of the game that change between versions of Schedule I and house variants
+///
+/// Loaded from a TOML file (see [`load`]), where anything left out keeps its default
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub payouts: Payouts,
+    pub ties: Ties,
+    pub aces: Aces,
+    /// Whether the deck is reshuffled before every card, so cards are dealt with
+    /// replacement instead of never being dealt twice
+    pub reshuffle: bool,
+    pub deck: DeckConfig,
+    /// Whether the pot can be doubled down on, and at which stages
+    pub double_down: Option<DoubleDownConfig>,
+    /// Whether the pick of a stage can be insured, and for what
+    pub insurance: Option<InsuranceConfig>,
+    /// Whether a revealed card can be discarded and drawn again, and at which stages
+    pub mulligan: Option<MulliganConfig>,
+}
+impl GameConfig {
+    /// The deck the cards are dealt from
+    pub fn deck(&self) -> DeckSpec {
+        let mut deck = DeckSpec::default()
+            .with_jokers(self.deck.jokers)
+            .reshuffled(self.reshuffle);
+        let stripped_ranks = self.deck.preset.stripped_ranks() | self.deck.stripped_ranks;
+        for rank in (2..=14).filter(|rank| stripped_ranks & (1 << rank) != 0) {
+            deck = deck.without_rank(rank);
+        }
+        for (suit, &copies) in self.deck.suits.iter().enumerate() {
+            deck = deck.with_suit_copies(suit as u8, copies);
+        }
+        deck
+    }
+    /// The double down rule the game is solved with, if the pot can be doubled down on
+    pub fn double_down(&self) -> Option<DoubleDown> {
+        self.double_down.map(|double_down| DoubleDown {
+            stages: double_down.stages,
+            multiplier: double_down.multiplier,
+        })
+    }
+    /// The mulligan rule the game is solved with, if revealed cards can be discarded
+    pub fn mulligan(&self) -> Option<Mulligan> {
+        self.mulligan.map(|mulligan| Mulligan {
+            stages: mulligan.stages,
+            fee: mulligan.fee,
+        })
+    }
+}
+
+/// The pot, as a multiple of the bet, after winning each stage
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Payouts {
+    /// Red/Black
+    pub color: f64,
+    /// Higher/Lower
+    pub latitude: f64,
+    /// Inside/Outside
+    pub contained: f64,
+    /// The suit
+    pub suit: f64,
+}
+impl Default for Payouts {
+    fn default() -> Self {
+        Self {
+            color: 2.0,
+            latitude: 3.0,
+            contained: 4.0,
+            suit: 10.0,
+        }
+    }
+}
+impl Payouts {
+    /// Parses the payouts given as `{color}:{latitude}:{contained}:{suit}` (e.g. `2:3:4:10`)
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut split = spec.split(':').map(|payout| payout.parse().ok());
+        let payouts = Self {
+            color: split.next()??,
+            latitude: split.next()??,
+            contained: split.next()??,
+            suit: split.next()??,
+        };
+        (split.next().is_none() && payouts.is_valid()).then_some(payouts)
+    }
+    /// Whether every payout is positive, since each stage's multiplier is relative to the last
+    pub fn is_valid(&self) -> bool {
+        [self.color, self.latitude, self.contained, self.suit]
+            .iter()
+            .all(|&payout| payout > 0.0)
+    }
+}
+
+/// How ties are ruled in the stages that compare ranks
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Ties {
+    /// Higher/Lower, where the card has the same rank as the last one
+    pub latitude: TieRule,
+    /// Inside/Outside, where the card has the same rank as one of the bounds
+    pub contained: TieRule,
+}
+impl Ties {
+    /// Parses the tie rules given as `{latitude}:{contained}` (e.g. `push:loss`)
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (latitude, contained) = spec.split_once(':')?;
+        Some(Self {
+            latitude: latitude.parse().ok()?,
+            contained: contained.parse().ok()?,
+        })
+    }
+}
+
+/// What a pick scores when the card ties it
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TieRule {
+    /// Ties count as Higher for Higher/Lower, and as Inside for Inside/Outside
+    #[default]
+    Standard,
+    /// Ties lose the pot, whichever was picked
+    Loss,
+    /// Ties keep the pot and move on to the next stage
+    Push,
+    /// Ties win, whichever was picked
+    Win,
+}
+impl TieRule {
+    /// The score of a tie, given the `payout` (the score for winning) and whether
+    /// the pick wins ties under the standard rule
+    pub fn score(self, payout: f64, wins_standard: bool) -> f64 {
+        match self {
+            Self::Standard if wins_standard => payout,
+            Self::Standard | Self::Loss => 0.0,
+            Self::Push => 1.0,
+            Self::Win => payout,
+        }
+    }
+}
+impl FromStr for TieRule {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Self::Standard),
+            "loss" => Ok(Self::Loss),
+            "push" => Ok(Self::Push),
+            "win" => Ok(Self::Win),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How aces rank against the other cards when comparing ranks
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aces {
+    /// Aces rank above kings
+    #[default]
+    High,
+    /// Aces rank below twos
+    Low,
+    /// Aces rank either way, whichever wins the pick
+    Both,
+}
+impl Aces {
+    /// The ways cards are ranked, where a pick wins if it wins with any of them
+    pub fn rankings(self) -> &'static [fn(PlayingCard) -> u8] {
+        match self {
+            Self::High => &[PlayingCard::rank],
+            Self::Low => &[PlayingCard::rank_ace_low],
+            Self::Both => &[PlayingCard::rank, PlayingCard::rank_ace_low],
+        }
+    }
+}
+impl FromStr for Aces {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "high" => Ok(Self::High),
+            "low" => Ok(Self::Low),
+            "both" => Ok(Self::Both),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The cards in the deck, a standard 52 card deck by default
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeckConfig {
+    /// The deck to start from, before any other ranks are stripped
+    pub preset: DeckPreset,
+    /// The number of jokers shuffled in
+    pub jokers: u8,
+    /// What a joker does to the pick it's dealt for, or is compared against
+    pub joker: JokerRule,
+    /// The ranks (2-14) taken out of the deck, as a bit for each rank
+    #[serde(with = "rank_set")]
+    pub stripped_ranks: u16,
+    /// The copies of each suit (hearts, diamonds, spades, clubs)
+    pub suits: [u8; 4],
+}
+impl Default for DeckConfig {
+    fn default() -> Self {
+        Self {
+            preset: DeckPreset::default(),
+            jokers: 0,
+            joker: JokerRule::default(),
+            stripped_ranks: 0,
+            suits: [1; 4],
+        }
+    }
+}
+
+/// A house rule where the pot can be multiplied before a stage, in exchange for
+/// playing the rest of the game out without cashing out
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DoubleDownConfig {
+    /// What the pot is multiplied by, 2 by default
+    #[serde(default = "doubled")]
+    pub multiplier: f64,
+    /// The stages (from 1) it's offered at, as a bit for each stage
+    #[serde(with = "stage_set")]
+    pub stages: u16,
+}
+fn doubled() -> f64 {
+    2.0
+}
+impl DoubleDownConfig {
+    /// Parses the rule given as `{stages}:{multiplier}`, where the stages are
+    /// separated by commas (e.g. `3,4:2`)
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (stages, multiplier) = spec.split_once(':')?;
+        let stages = parse_stages(stages)?;
+        let multiplier = multiplier.parse().ok().filter(|&m: &f64| m > 0.0)?;
+        Some(Self { multiplier, stages })
+    }
+    /// The last stage it's offered at
+    pub fn last_stage(&self) -> usize {
+        15 - self.stages.leading_zeros() as usize
+    }
+}
+
+/// A house rule where, before a stage, the player may pay a fee to insure the pick,
+/// so that losing it refunds part of the bet
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InsuranceConfig {
+    /// The stage it's offered at, 2 (after Red/Black) by default
+    #[serde(default = "after_color")]
+    pub stage: usize,
+    /// The fee, as a multiple of the bet
+    pub fee: f64,
+    /// What's refunded if the pick loses, as a multiple of the bet, half the bet by default
+    #[serde(default = "half_bet")]
+    pub refund: f64,
+}
+fn after_color() -> usize {
+    2
+}
+fn half_bet() -> f64 {
+    0.5
+}
+impl InsuranceConfig {
+    /// Parses the rule given as `{fee}:{refund}`, optionally after the stage as
+    /// `{stage}:{fee}:{refund}` (e.g. `0.25:0.5` or `3:0.5:1`)
+    pub fn parse(spec: &str) -> Option<Self> {
+        let parts = spec.split(':').collect::<Vec<_>>();
+        let (stage, fee, refund) = match *parts.as_slice() {
+            [fee, refund] => (after_color(), fee, refund),
+            [stage, fee, refund] => (stage.parse().ok()?, fee, refund),
+            _ => return None,
+        };
+        let insurance = Self {
+            stage,
+            fee: fee.parse().ok()?,
+            refund: refund.parse().ok()?,
+        };
+        insurance.is_valid().then_some(insurance)
+    }
+    /// Whether the stage is one, and the fee and refund aren't negative
+    pub fn is_valid(&self) -> bool {
+        self.stage >= 1 && self.fee >= 0.0 && self.refund >= 0.0
+    }
+}
+
+/// A house rule where, once per game, the card revealed at a stage can be discarded
+/// and drawn again for a fee
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MulliganConfig {
+    /// The fee, as a fraction of the pot
+    pub fee: f64,
+    /// The stages (from 1) it's offered at, as a bit for each stage
+    #[serde(with = "stage_set")]
+    pub stages: u16,
+}
+impl MulliganConfig {
+    /// Parses the rule given as `{stages}:{fee}`, where the stages are separated by
+    /// commas (e.g. `4:0.25`)
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (stages, fee) = spec.split_once(':')?;
+        let mulligan = Self {
+            fee: fee.parse().ok()?,
+            stages: parse_stages(stages)?,
+        };
+        mulligan.is_valid().then_some(mulligan)
+    }
+    /// Whether the fee is a fraction of the pot, and it's offered at any stage
+    pub fn is_valid(&self) -> bool {
+        (0.0..1.0).contains(&self.fee) && self.stages != 0
+    }
+    /// The last stage it's offered at
+    pub fn last_stage(&self) -> usize {
+        15 - self.stages.leading_zeros() as usize
+    }
+}
+/// Parses stages (from 1) separated by commas, as a bit for each stage
+fn parse_stages(spec: &str) -> Option<u16> {
+    spec.split(',').try_fold(0u16, |stages, stage| {
+        let stage = stage
+            .parse()
+            .ok()
+            .filter(|stage: &u16| (1..16).contains(stage))?;
+        Some(stages | (1 << stage))
+    })
+}
+
+/// The decks played with in different places, which leave out the low ranks
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeckPreset {
+    /// The standard 52 card deck
+    #[default]
+    Standard,
+    /// The 32 card piquet deck, 7 through ace
+    Piquet,
+    /// The 36 card short deck, 6 through ace
+    Short,
+}
+impl DeckPreset {
+    /// The ranks left out of the deck, as a bit for each rank
+    pub fn stripped_ranks(self) -> u16 {
+        let lowest = match self {
+            Self::Standard => 2,
+            Self::Piquet => 7,
+            Self::Short => 6,
+        };
+        (2..lowest).fold(0, |ranks, rank| ranks | (1 << rank))
+    }
+}
+impl FromStr for DeckPreset {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Self::Standard),
+            "piquet" => Ok(Self::Piquet),
+            "short" => Ok(Self::Short),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What a pick scores when a joker is dealt for it, or is one of the cards it's
+/// compared against, since jokers have no rank or suit
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JokerRule {
+    /// Jokers lose the pot, whichever was picked
+    #[default]
+    Loss,
+    /// Jokers win, whichever was picked
+    Win,
+}
+impl JokerRule {
+    /// The score of a pick settled by a joker, given the `payout` (the score for winning)
+    pub fn score(self, payout: f64) -> f64 {
+        match self {
+            Self::Loss => 0.0,
+            Self::Win => payout,
+        }
+    }
+}
+
+// ranks are written as a list of ranks (2-14), and kept as a bit for each rank
+mod rank_set {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(ranks: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        // collected first, since bincode needs the length up front
+        let ranks = (2..=14u8)
+            .filter(|rank| ranks & (1 << rank) != 0)
+            .collect::<Vec<_>>();
+        ranks.serialize(serializer)
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        Vec::<u8>::deserialize(deserializer)?
+            .into_iter()
+            .try_fold(0, |ranks, rank| match rank {
+                2..=14 => Ok(ranks | (1 << rank)),
+                _ => Err(serde::de::Error::custom(format!("invalid rank {}", rank))),
+            })
+    }
+}
+
+// stages are written as a list of stages (from 1), and kept as a bit for each stage
+mod stage_set {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(stages: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        let stages = (1..16u8)
+            .filter(|stage| stages & (1 << stage) != 0)
+            .collect::<Vec<_>>();
+        stages.serialize(serializer)
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        Vec::<u8>::deserialize(deserializer)?
+            .into_iter()
+            .try_fold(0, |stages, stage| match stage {
+                1..=15 => Ok(stages | (1 << stage)),
+                _ => Err(serde::de::Error::custom(format!("invalid stage {}", stage))),
+            })
+    }
+}
+
+/// Loads the config from the TOML file at `path`, e.g.
+///
+/// ```toml
+/// aces = "low"
+/// reshuffle = true
+///
+/// [payouts]
+/// color = 2
+/// latitude = 3
+/// contained = 4
+/// suit = 10
+///
+/// [ties]
+/// latitude = "push"
+/// contained = "loss"
+///
+/// [deck]
+/// preset = "short"
+/// jokers = 2
+/// joker = "win"
+/// stripped_ranks = [2, 3, 4, 5, 6]
+/// suits = [1, 1, 2, 2]
+///
+/// [double_down]
+/// multiplier = 2
+/// stages = [3, 4]
+///
+/// [insurance]
+/// stage = 2
+/// fee = 0.25
+/// refund = 0.5
+///
+/// [mulligan]
+/// fee = 0.25
+/// stages = [1]
+/// ```
+pub fn load(path: &Path) -> io::Result<GameConfig> {
+    let config: GameConfig =
+        toml::from_str(&fs::read_to_string(path)?).map_err(io::Error::other)?;
+    if !config.payouts.is_valid() {
+        return Err(io::Error::other("payouts must be positive"));
+    }
+    if config
+        .double_down
+        .is_some_and(|double_down| double_down.multiplier <= 0.0 || double_down.stages == 0)
+    {
+        return Err(io::Error::other(
+            "double down needs a positive multiplier and at least one stage",
+        ));
+    }
+    if config
+        .insurance
+        .is_some_and(|insurance| !insurance.is_valid())
+    {
+        return Err(io::Error::other(
+            "insurance needs a stage, and a fee and refund that aren't negative",
+        ));
+    }
+    if config.mulligan.is_some_and(|mulligan| !mulligan.is_valid()) {
+        return Err(io::Error::other(
+            "a mulligan needs a fee less than the pot and at least one stage",
+        ));
+    }
+    // every stage deals a card, which a deck dealt without replacement has to have
+    if config.deck().len() < 4 {
+        return Err(io::Error::other("the deck needs at least 4 cards"));
+    }
+    Ok(config)
+}
@@ -0,0 +1,129 @@
+use super::{Choice, DiscreteDecision, event::RandomEvent};
+use crate::PlayingCard;
+use std::rc::Rc;
+
+/// Builds a game made of a chain of stages, where winning any choice of a stage
+/// moves on to the next stage (and winning the last stage ends the game)
+///
+/// ```
+/// use ride_the_bus::{DiscreteDecisionTree, PlayingCard, decision::builder::GameBuilder};
+///
+/// let first_decision = GameBuilder::<PlayingCard>::new()
+///     .stage()
+///     .choice("Red", 2.0, |cards| cards[0].color() == 0)
+///     .choice("Black", 2.0, |cards| cards[0].color() == 1)
+///     .stage()
+///     .choice("Higher", 1.5, |cards| cards[0].rank() >= cards[1].rank())
+///     .choice("Lower", 1.5, |cards| cards[0].rank() <= cards[1].rank())
+///     .with_cashout()
+///     .build();
+/// let tree = DiscreteDecisionTree::solve(first_decision, Default::default());
+/// ```
+pub struct GameBuilder<E = PlayingCard> {
+    stages: Vec<Stage<E>>,
+}
+struct Stage<E> {
+    choices: Vec<StageChoice<E>>,
+    cashout: bool,
+}
+/// Whether a choice wins given the history
+type Predicate<E> = Box<dyn Fn(&[E]) -> bool>;
+struct StageChoice<E> {
+    name: String,
+    payout: f64,
+    wins: Predicate<E>,
+}
+impl<E: RandomEvent> Default for GameBuilder<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<E: RandomEvent> GameBuilder<E> {
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+    /// Starts the next stage, which the following choices are added to
+    pub fn stage(mut self) -> Self {
+        self.stages.push(Stage {
+            choices: Vec::new(),
+            cashout: false,
+        });
+        self
+    }
+    /// Adds a choice to the current stage that multiplies the pot by `payout` when
+    /// `wins` is true for the history (backwards, see [`Choice::score`]), otherwise
+    /// the pot is lost
+    pub fn choice(
+        mut self,
+        name: impl Into<String>,
+        payout: f64,
+        wins: impl Fn(&[E]) -> bool + 'static,
+    ) -> Self {
+        if self.stages.is_empty() {
+            self = self.stage();
+        }
+        let stage = self.stages.last_mut().expect("non-empty stages");
+        stage.choices.push(StageChoice {
+            name: name.into(),
+            payout,
+            wins: Box::new(wins),
+        });
+        self
+    }
+    /// Allows cashing out instead of making the current stage's choice
+    pub fn with_cashout(mut self) -> Self {
+        if let Some(stage) = self.stages.last_mut() {
+            stage.cashout = true;
+        }
+        self
+    }
+    /// Builds the first decision of the game
+    pub fn build(self) -> DiscreteDecision<E> {
+        decision(&Rc::new(self.stages), 0)
+    }
+}
+
+/// The decision for the stage at index `stage`
+fn decision<E: RandomEvent>(stages: &Rc<Vec<Stage<E>>>, stage: usize) -> DiscreteDecision<E> {
+    let Some(current) = stages.get(stage) else {
+        return DiscreteDecision::new(std::iter::empty::<BuiltChoice<E>>());
+    };
+    let choices = (0..current.choices.len()).map(|choice| BuiltChoice {
+        stages: stages.clone(),
+        stage,
+        choice,
+    });
+    match current.cashout {
+        true => DiscreteDecision::new_with_cashout(choices),
+        false => DiscreteDecision::new(choices),
+    }
+}
+
+/// A choice of a game built by [`GameBuilder`]
+struct BuiltChoice<E> {
+    stages: Rc<Vec<Stage<E>>>,
+    stage: usize,
+    choice: usize,
+}
+impl<E> BuiltChoice<E> {
+    fn get(&self) -> &StageChoice<E> {
+        &self.stages[self.stage].choices[self.choice]
+    }
+}
+impl<E> std::fmt::Debug for BuiltChoice<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.get().name)
+    }
+}
+impl<E: RandomEvent> Choice<E> for BuiltChoice<E> {
+    fn score(&self, history: &[E]) -> f64 {
+        let choice = self.get();
+        match (choice.wins)(history) {
+            true => choice.payout,
+            false => 0.0,
+        }
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        (self.stage + 1 < self.stages.len()).then(|| decision(&self.stages, self.stage + 1))
+    }
+}
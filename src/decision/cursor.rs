@@ -0,0 +1,102 @@
+use super::event::RandomEvent;
+use super::solver::{ChoiceEval, DiscreteDecisionTree, RandomEventOutcome};
+use crate::PlayingCard;
+
+/// A position in a solved game, which steps through the tree as choices are
+/// taken and can step back again
+pub struct GameCursor<'a, E: RandomEvent = PlayingCard> {
+    root: &'a DiscreteDecisionTree<E>,
+    /// The decisions after the root, with the event that led to each
+    steps: Vec<(&'a DiscreteDecisionTree<E>, Option<E>)>,
+}
+impl<'a, E: RandomEvent> GameCursor<'a, E> {
+    /// Creates a cursor at the first decision of the game
+    pub fn new(root: &'a DiscreteDecisionTree<E>) -> Self {
+        Self {
+            root,
+            steps: Vec::new(),
+        }
+    }
+    /// The first decision of the game
+    pub fn root(&self) -> &'a DiscreteDecisionTree<E> {
+        self.root
+    }
+    /// The decision the cursor is at
+    pub fn tree(&self) -> &'a DiscreteDecisionTree<E> {
+        self.steps.last().map_or(self.root, |&(tree, _)| tree)
+    }
+    /// The events seen so far, in the order they happened
+    pub fn events(&self) -> impl Iterator<Item = E> + '_ {
+        self.steps.iter().filter_map(|&(_, event)| event)
+    }
+    /// The choices taken to get to the decision the cursor is at, each with the
+    /// event that resolved it and the decision it led to
+    pub fn history(&self) -> Vec<(&'a ChoiceEval<E>, Option<E>, &'a DiscreteDecisionTree<E>)> {
+        let mut from = self.root;
+        let mut history = Vec::new();
+        for &(to, event) in &self.steps {
+            if let Some(choice) = Self::choice_between(from, to, event) {
+                history.push((choice, event, to));
+            }
+            from = to;
+        }
+        history
+    }
+    /// The last choice taken, with the event that resolved it, or `None` if the
+    /// cursor is at the first decision
+    pub fn last(&self) -> Option<(&'a ChoiceEval<E>, Option<E>)> {
+        let (&(to, event), before) = self.steps.split_last()?;
+        let from = before.last().map_or(self.root, |&(tree, _)| tree);
+        Some((Self::choice_between(from, to, event)?, event))
+    }
+    /// The choice of `from` whose outcome for the event is the decision `to`
+    fn choice_between(
+        from: &'a DiscreteDecisionTree<E>,
+        to: &'a DiscreteDecisionTree<E>,
+        event: Option<E>,
+    ) -> Option<&'a ChoiceEval<E>> {
+        from.iter().find(|choice| {
+            choice
+                .outcome(event)
+                .and_then(|outcome| outcome.next_decision())
+                .is_some_and(|next| std::ptr::eq(next, to))
+        })
+    }
+    /// The stage the cursor is at, where `1` is the first, counted from the choices
+    /// taken that ended a stage (see [`super::Choice::ends_stage`])
+    pub fn stage(&self) -> usize {
+        let ended = self.history();
+        1 + ended
+            .iter()
+            .filter(|(choice, _, _)| choice.choice.ends_stage())
+            .count()
+    }
+    /// The number of steps taken from the first decision
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+    /// Whether the cursor is at the first decision
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+    /// Takes a choice (by name, case insensitive) that was resolved by the event
+    /// (see [`super::solver::ChoiceEval::outcome`]), moving to the next decision
+    ///
+    /// Returns the outcome, or `None` if the choice or event isn't possible. The
+    /// cursor stays put if the outcome ends the game
+    pub fn descend(&mut self, choice: &str, event: Option<E>) -> Option<&'a RandomEventOutcome<E>> {
+        let outcome = self.tree().choice(choice)?.outcome(event)?;
+        if let Some(next) = outcome.next_decision() {
+            self.steps.push((next, outcome.event));
+        }
+        Some(outcome)
+    }
+    /// Steps back to the previous decision, returning `false` if already at the first
+    pub fn back(&mut self) -> bool {
+        self.steps.pop().is_some()
+    }
+    /// Goes back to the first decision
+    pub fn reset(&mut self) {
+        self.steps.clear();
+    }
+}
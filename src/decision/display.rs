@@ -0,0 +1,78 @@
+use super::event::RandomEvent;
+use super::solver::DiscreteDecisionTree;
+use std::fmt::{self, Display, Formatter};
+
+/// Renders a tree as indented ASCII, see [`DiscreteDecisionTree::fmt_tree`]
+pub struct TreeDisplay<'a, E: RandomEvent> {
+    tree: &'a DiscreteDecisionTree<E>,
+    depth: usize,
+}
+impl<E: RandomEvent> DiscreteDecisionTree<E> {
+    /// Renders the tree as an indented ASCII tree of choices (best first, with the
+    /// optimal ones marked), the events that resolve them, and their EVs
+    ///
+    /// Only `depth` decisions deep are rendered, since every decision adds a
+    /// line for every event of every choice
+    pub fn fmt_tree(&self, depth: usize) -> TreeDisplay<'_, E> {
+        TreeDisplay { tree: self, depth }
+    }
+}
+// displays the choices of this decision and their events
+impl<E: RandomEvent + Display> Display for DiscreteDecisionTree<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(1).fmt(f)
+    }
+}
+impl<E: RandomEvent + Display> Display for TreeDisplay<'_, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_tree(f, self.tree, self.depth, "")
+    }
+}
+
+fn write_tree<E: RandomEvent + Display>(
+    f: &mut Formatter<'_>,
+    tree: &DiscreteDecisionTree<E>,
+    depth: usize,
+    prefix: &str,
+) -> fmt::Result {
+    if depth == 0 {
+        return Ok(());
+    }
+    let ranked = tree.ranked_choices();
+    for (i, ranked_choice) in ranked.iter().enumerate() {
+        let choice = ranked_choice.choice;
+        let (line, indent) = branch(i + 1 == ranked.len());
+        write!(
+            f,
+            "{}{}{:?} = {:.04}",
+            prefix, line, choice.choice, choice.expected_value
+        )?;
+        if ranked_choice.is_optimal {
+            write!(f, " <----")?;
+        }
+        writeln!(f)?;
+
+        let prefix = format!("{}{}", prefix, indent);
+        let outcomes = choice.iter().count();
+        for (j, outcome) in choice.iter().enumerate() {
+            let (line, indent) = branch(j + 1 == outcomes);
+            match outcome.event {
+                Some(event) => write!(f, "{}{}{}", prefix, line, event)?,
+                None => write!(f, "{}{}(no card)", prefix, line)?,
+            }
+            writeln!(f, " = {:.04}", outcome.value)?;
+            if let Some(next) = outcome.next_decision() {
+                write_tree(f, next, depth - 1, &format!("{}{}", prefix, indent))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The branch drawn before a line, and the indent drawn before the lines under it
+fn branch(is_last: bool) -> (&'static str, &'static str) {
+    match is_last {
+        true => ("`-- ", "    "),
+        false => ("|-- ", "|   "),
+    }
+}
@@ -0,0 +1,165 @@
+use crate::PlayingCard;
+use std::fmt::Debug;
+
+/// A random event that resolves a [`super::Choice`], like a card being dealt
+pub trait RandomEvent: Copy + PartialEq + Debug + 'static {
+    /// The suit of the event if it's a card, used to settle a
+    /// [`super::solver::SideBet`]
+    fn suit(self) -> Option<u8> {
+        None
+    }
+    /// The rank of the event if it's a card, used to settle a
+    /// [`super::solver::SideBet`]
+    fn rank(self) -> Option<u8> {
+        None
+    }
+}
+impl RandomEvent for PlayingCard {
+    fn suit(self) -> Option<u8> {
+        (!self.is_joker()).then(|| PlayingCard::suit(self))
+    }
+    fn rank(self) -> Option<u8> {
+        (!self.is_joker()).then(|| PlayingCard::rank(self))
+    }
+}
+// plain numbers, e.g. for dice rolls
+impl RandomEvent for u8 {}
+impl RandomEvent for u16 {}
+impl RandomEvent for u32 {}
+impl RandomEvent for usize {}
+
+/// Where the random events that resolve choices come from
+///
+/// Functions given the history can be used as a source, e.g. for a die:
+/// `|_: &[u8]| (1..=6).map(|roll| (roll, 1.0)).collect()`
+pub trait EventSource<E> {
+    /// Every event that can happen next given the `history` (backwards, see
+    /// [`super::Choice::score`]), with its weight relative to the other events
+    ///
+    /// If there are none (e.g. the deck ran out), the choices that need an event
+    /// aren't offered
+    fn events(&self, history: &[E]) -> Vec<(E, f64)>;
+}
+impl<E, F: Fn(&[E]) -> Vec<(E, f64)>> EventSource<E> for F {
+    fn events(&self, history: &[E]) -> Vec<(E, f64)> {
+        self(history)
+    }
+}
+
+/// A standard deck of 52 cards dealt without replacement, so every card that
+/// hasn't been seen is equally likely
+pub struct Deck;
+impl EventSource<PlayingCard> for Deck {
+    fn events(&self, history: &[PlayingCard]) -> Vec<(PlayingCard, f64)> {
+        PlayingCard::deck_iter()
+            .filter(|card| !history.contains(card))
+            .map(|card| (card, 1.0))
+            .collect()
+    }
+}
+
+/// A deck made of any number of copies of each card, and any number of jokers, so
+/// ranks can be stripped out or suits doubled up
+///
+/// Cards are dealt without replacement, so each card is as likely as the copies
+/// of it that haven't been seen, unless the deck is reshuffled before every card
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeckSpec {
+    /// The copies of each card, indexed like [`PlayingCard::deck_iter`]
+    copies: [u8; 52],
+    jokers: u8,
+    reshuffle: bool,
+}
+impl Default for DeckSpec {
+    fn default() -> Self {
+        Self {
+            copies: [1; 52],
+            jokers: 0,
+            reshuffle: false,
+        }
+    }
+}
+impl DeckSpec {
+    /// Adds `jokers` jokers (see [`PlayingCard::JOKER`])
+    pub fn with_jokers(mut self, jokers: u8) -> Self {
+        self.jokers += jokers;
+        self
+    }
+    /// Takes every card of the rank (2-14) out of the deck
+    pub fn without_rank(mut self, rank: u8) -> Self {
+        for card in PlayingCard::deck_iter().filter(|card| card.rank() == rank) {
+            self.copies[card.index()] = 0;
+        }
+        self
+    }
+    /// Sets the number of copies of every card of the suit (see [`PlayingCard::suit`])
+    pub fn with_suit_copies(mut self, suit: u8, copies: u8) -> Self {
+        for card in PlayingCard::deck_iter().filter(|card| card.suit() == suit) {
+            self.copies[card.index()] = self.copies[card.index()].min(1) * copies;
+        }
+        self
+    }
+    /// Takes a copy of the card out of the deck, e.g. a card known to be burned
+    pub fn without_card(mut self, card: PlayingCard) -> Self {
+        let copies = match card.is_joker() {
+            true => &mut self.jokers,
+            false => &mut self.copies[card.index()],
+        };
+        *copies = copies.saturating_sub(1);
+        self
+    }
+    /// Reshuffles the deck before every card, so cards are dealt with replacement
+    pub fn reshuffled(mut self, reshuffle: bool) -> Self {
+        self.reshuffle = reshuffle;
+        self
+    }
+    /// The number of copies of the card in the full deck
+    pub fn copies(&self, card: PlayingCard) -> u8 {
+        match card.is_joker() {
+            true => self.jokers,
+            false => self.copies[card.index()],
+        }
+    }
+    /// The number of cards in the full deck
+    pub fn len(&self) -> usize {
+        self.copies
+            .iter()
+            .map(|&copies| copies as usize)
+            .sum::<usize>()
+            + self.jokers as usize
+    }
+    /// Whether there are no cards in the deck
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Every card in the full deck, with a card for each copy of it
+    pub fn cards(&self) -> Vec<PlayingCard> {
+        self.events(&[])
+            .into_iter()
+            .flat_map(|(card, copies)| std::iter::repeat_n(card, copies as usize))
+            .collect()
+    }
+    /// Whether the deck is reshuffled before every card, see [`DeckSpec::reshuffled`]
+    pub fn reshuffles(&self) -> bool {
+        self.reshuffle
+    }
+}
+impl EventSource<PlayingCard> for DeckSpec {
+    fn events(&self, history: &[PlayingCard]) -> Vec<(PlayingCard, f64)> {
+        // the copies of each card left, with the jokers last
+        let mut left = [0u8; 53];
+        left[..52].copy_from_slice(&self.copies);
+        left[52] = self.jokers;
+        if !self.reshuffle {
+            for card in history {
+                left[card.index()] = left[card.index()].saturating_sub(1);
+            }
+        }
+        PlayingCard::deck_iter()
+            .chain(std::iter::once(PlayingCard::JOKER))
+            .zip(left)
+            .filter(|&(_, left)| left > 0)
+            .map(|(card, left)| (card, left as f64))
+            .collect()
+    }
+}
@@ -1,11 +1,20 @@
+pub mod builder;
+pub mod cursor;
+pub mod display;
+pub mod event;
 pub mod solver;
 use crate::PlayingCard;
+use event::RandomEvent;
+use std::rc::Rc;
 
 /// A [`Choice`] is an option in a decision
 ///
 /// See the types that implement this trait to get a better understanding
-pub trait Choice: std::fmt::Debug {
-    /// A score for this choice based on the card history
+///
+/// Choices are resolved by random events of type `E`, which are cards unless the
+/// game is solved with another [`event::EventSource`]
+pub trait Choice<E = PlayingCard>: std::fmt::Debug {
+    /// A score for this choice based on the card (event) history
     ///
     /// The score represents the value of this choice with the given
     /// history, with 1.0 being the identity. For example, if the correct
@@ -15,53 +24,527 @@ pub trait Choice: std::fmt::Debug {
     /// # Card History Note
     /// The card history is given backwards, with idx:`0` being the unseen
     /// card, idx:`1` being the last seen card, etc.
-    fn score(&self, history: &[PlayingCard]) -> f64;
+    ///
+    /// If the choice doesn't reveal a card (see [`Choice::reveals_card`]), then
+    /// there is no unseen card and idx:`0` is the last seen card
+    fn score(&self, history: &[E]) -> f64;
 
     /// The next decision to consider after this choice
-    fn next_decision(&self) -> Option<DiscreteDecision>;
+    fn next_decision(&self) -> Option<DiscreteDecision<E>>;
+
+    /// Whether this choice ends the game by keeping the pot
+    fn is_cashout(&self) -> bool {
+        false
+    }
+
+    /// Whether this choice resolves by revealing a card (a random event)
+    ///
+    /// Choices that don't (e.g. passing or buying insurance) are scored once and
+    /// move on to the next decision deterministically
+    fn reveals_card(&self) -> bool {
+        true
+    }
+
+    /// What's returned when this choice loses, in bets (multiples of the pot the game
+    /// starts with), e.g. when it was insured (see [`Insure`])
+    fn refund(&self) -> f64 {
+        0.0
+    }
+
+    /// What's paid out of the pot for this choice before it's scored, in bets, e.g.
+    /// for buying insurance (see [`Insure`])
+    fn fee(&self) -> f64 {
+        0.0
+    }
+
+    /// Whether resolving this choice ends its stage, which it does when it reveals a
+    /// card, unless the card can still be discarded (see [`Mulligan`])
+    fn ends_stage(&self) -> bool {
+        self.reveals_card()
+    }
+
+    /// The card discarded with a [`Mulligan`] (or swap) earlier in the game (counted
+    /// from the first card dealt), which is still in the history but was never kept
+    fn discarded(&self) -> Option<usize> {
+        None
+    }
 }
 /// A [`DiscreteDecision`] is a list of all possible [`Choice`]s available
 /// as an option in a decision
 ///
 /// Usually, it's an enumeration of a type that implements [`Choice`]
-pub struct DiscreteDecision {
-    choices: Vec<Box<dyn Choice>>,
+#[derive(Clone)]
+pub struct DiscreteDecision<E = PlayingCard> {
+    // shared, so the decision can be offered again after doubling down
+    choices: Vec<Rc<dyn Choice<E>>>,
+    // whether the decision was doubled into, which already offers the insurance of
+    // the decision doubled from
+    doubled: bool,
+    // after doubling down, the rest of the game is played out without cashing out
+    committed: bool,
+    insured: bool,
+    // whether the rules of the stage were already offered, e.g. after the card of the
+    // stage was revealed and is being kept or discarded
+    offered: bool,
+    // the card discarded by a mulligan or swap, counted from the first card dealt,
+    // which leaves no mulligan or swap for the rest of the game
+    discarded: Option<usize>,
 }
-impl DiscreteDecision {
+impl<E: RandomEvent> DiscreteDecision<E> {
     /// Creates a new [`DiscreteDecision`] from the given choices
-    pub fn new<T: Choice + 'static>(choices: impl IntoIterator<Item = T>) -> Self {
-        DiscreteDecision {
-            choices: choices
+    pub fn new<T: Choice<E> + 'static>(choices: impl IntoIterator<Item = T>) -> Self {
+        Self::from_choices(
+            choices
                 .into_iter()
-                .map(|c| Box::new(c) as Box<dyn Choice>)
+                .map(|c| Rc::new(c) as Rc<dyn Choice<E>>)
                 .collect(),
+        )
+    }
+    /// Creates a decision from choices that are already shared
+    pub(crate) fn from_choices(choices: Vec<Rc<dyn Choice<E>>>) -> Self {
+        DiscreteDecision {
+            choices,
+            doubled: false,
+            committed: false,
+            insured: false,
+            offered: false,
+            discarded: None,
         }
     }
     /// Creates a new [`DiscreteDecision`] from the given choices, along with
-    /// the option for a [`Cashout`]
+    /// the option to cash out
     ///
-    /// See [`Cashout`] documentation for more info on this choice
-    pub fn new_with_cashout<T: Choice + 'static>(choices: impl IntoIterator<Item = T>) -> Self {
+    /// Cashing out ends the game keeping the pot (see [`Choice::is_cashout`])
+    pub fn new_with_cashout<T: Choice<E> + 'static>(choices: impl IntoIterator<Item = T>) -> Self {
         let mut dd = Self::new(choices);
-        dd.choices.push(Box::new(Cashout));
+        dd.choices.push(Rc::new(Cashout));
         dd
     }
+    /// Adds the option to [`Double`] down, multiplying the pot by `multiplier` in
+    /// exchange for giving up cashing out, unless the game was already doubled down
+    pub(crate) fn with_double_down(mut self, multiplier: f64) -> Self {
+        if !self.committed {
+            let doubled = DiscreteDecision {
+                doubled: true,
+                ..self.clone().committed()
+            };
+            self.choices.push(Rc::new(Double {
+                multiplier,
+                doubled,
+            }));
+        }
+        self
+    }
+    /// The same decision without cashing out, where every decision after it is
+    /// committed as well
+    fn committed(self) -> Self {
+        DiscreteDecision {
+            choices: self
+                .choices
+                .into_iter()
+                .filter(|choice| !choice.is_cashout())
+                .map(|choice| Rc::new(Committed(choice)) as Rc<dyn Choice<E>>)
+                .collect(),
+            committed: true,
+            ..self
+        }
+    }
+    /// The stage of the decision (where `1` is the first), given the number of cards
+    /// seen, or `None` if the rules of the stage were already offered
+    pub(crate) fn stage(&self, seen: usize) -> Option<usize> {
+        (!self.offered).then(|| seen + 1 - usize::from(self.discarded.is_some()))
+    }
+    /// Lets the card revealed by each choice be discarded for `fee` (a fraction of the
+    /// pot) and drawn again (see [`Mulligan`]), unless a mulligan was already taken
+    ///
+    /// `card` is the number of cards dealt before the revealed card
+    pub(crate) fn with_mulligan(self, fee: f64, card: usize) -> Self {
+        self.revealing("Mulligan", fee, card)
+    }
+    /// Lets the card revealed by each choice be swapped for another for `fee` (a
+    /// fraction of the pot), which plays like a mulligan named `Swap`, so it's left out
+    /// after a mulligan as well
+    pub(crate) fn with_swap(self, fee: f64, card: usize) -> Self {
+        self.revealing("Swap", fee, card)
+    }
+    /// Reveals the card of each choice before settling it, so it can be discarded and
+    /// drawn again with the choice named `redraw`
+    fn revealing(mut self, redraw: &'static str, fee: f64, card: usize) -> Self {
+        if self.discarded.is_none() {
+            for choice in &mut self.choices {
+                if choice.reveals_card() && !choice.is_cashout() {
+                    *choice = Rc::new(Revealing {
+                        choice: choice.clone(),
+                        redraw,
+                        fee,
+                        card,
+                    });
+                }
+            }
+        }
+        self
+    }
+    /// The same decision after the card at index `card` (counted from the first card
+    /// dealt) was discarded, where every decision after it leaves the card out as well
+    fn discarding(self, card: usize) -> Self {
+        DiscreteDecision {
+            choices: self
+                .choices
+                .into_iter()
+                .map(|choice| Rc::new(Discarded { choice, card }) as Rc<dyn Choice<E>>)
+                .collect(),
+            discarded: Some(card),
+            ..self
+        }
+    }
+    /// Adds the option to [`Insure`] the upcoming pick for `fee`, refunding `refund` if
+    /// it loses (both in bets), unless it was already insured
+    ///
+    /// Also left out after doubling down, since the decision doubled into already
+    /// offers it
+    pub(crate) fn with_insurance(mut self, fee: f64, refund: f64) -> Self {
+        if !self.insured && !self.doubled {
+            let insured = DiscreteDecision {
+                choices: self
+                    .choices
+                    .iter()
+                    .map(|choice| {
+                        Rc::new(Insured {
+                            choice: choice.clone(),
+                            refund,
+                        }) as Rc<dyn Choice<E>>
+                    })
+                    .collect(),
+                insured: true,
+                ..self.clone()
+            };
+            self.choices.push(Rc::new(Insure { fee, insured }));
+        }
+        self
+    }
 }
-impl IntoIterator for DiscreteDecision {
-    type Item = Box<dyn Choice>;
+impl<E> IntoIterator for DiscreteDecision<E> {
+    type Item = Rc<dyn Choice<E>>;
     type IntoIter = <Vec<Self::Item> as IntoIterator>::IntoIter;
     fn into_iter(self) -> Self::IntoIter {
         self.choices.into_iter()
     }
 }
 
+/// Ends the game keeping the pot, no matter what card comes next
 #[derive(Debug)]
 struct Cashout;
-impl Choice for Cashout {
-    fn score(&self, _: &[PlayingCard]) -> f64 {
+impl<E> Choice<E> for Cashout {
+    fn score(&self, _: &[E]) -> f64 {
         1.0 // cashout gives identity no matter what
     }
-    fn next_decision(&self) -> Option<DiscreteDecision> {
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
         None // after cashout, no other decisions to make
     }
+    fn is_cashout(&self) -> bool {
+        true
+    }
+}
+
+/// A choice as kept in a solved tree, which only has its name and what kind of
+/// choice it is
+///
+/// It isn't a [`Choice`], since its rules aren't kept (so trees can be serialized).
+/// Solving a decision again starts from the rules of the game instead, see
+/// [`solver::DiscreteDecisionTree::solve_at`]
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NamedChoice {
+    name: String,
+    is_cashout: bool,
+    reveals_card: bool,
+    ends_stage: bool,
+}
+impl NamedChoice {
+    pub(crate) fn of<E>(choice: &dyn Choice<E>) -> Self {
+        Self {
+            name: format!("{:?}", choice),
+            is_cashout: choice.is_cashout(),
+            reveals_card: choice.reveals_card(),
+            ends_stage: choice.ends_stage(),
+        }
+    }
+    /// The name of the choice, as it's shown
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// See [`Choice::is_cashout`]
+    pub fn is_cashout(&self) -> bool {
+        self.is_cashout
+    }
+    /// See [`Choice::reveals_card`]
+    pub fn reveals_card(&self) -> bool {
+        self.reveals_card
+    }
+    /// See [`Choice::ends_stage`]
+    pub fn ends_stage(&self) -> bool {
+        self.ends_stage
+    }
+}
+impl std::fmt::Debug for NamedChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Multiplies the pot before the stage resolves, then makes the same decision again,
+/// where neither it nor any decision after it can be cashed out
+struct Double<E> {
+    /// What the pot is multiplied by
+    multiplier: f64,
+    doubled: DiscreteDecision<E>,
+}
+impl<E> std::fmt::Debug for Double<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Double")
+    }
+}
+impl<E: RandomEvent> Choice<E> for Double<E> {
+    fn score(&self, _: &[E]) -> f64 {
+        self.multiplier
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        Some(self.doubled.clone())
+    }
+    fn reveals_card(&self) -> bool {
+        false
+    }
+}
+
+/// A choice made after doubling down, which plays the same as the choice except
+/// that the decisions after it can't be cashed out
+struct Committed<E>(Rc<dyn Choice<E>>);
+impl<E> std::fmt::Debug for Committed<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+impl<E: RandomEvent> Choice<E> for Committed<E> {
+    fn score(&self, history: &[E]) -> f64 {
+        self.0.score(history)
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        self.0.next_decision().map(DiscreteDecision::committed)
+    }
+    fn is_cashout(&self) -> bool {
+        self.0.is_cashout()
+    }
+    fn reveals_card(&self) -> bool {
+        self.0.reveals_card()
+    }
+    fn refund(&self) -> f64 {
+        self.0.refund()
+    }
+    fn fee(&self) -> f64 {
+        self.0.fee()
+    }
+    fn discarded(&self) -> Option<usize> {
+        self.0.discarded()
+    }
+}
+
+/// Pays a fee to insure the upcoming pick, then makes the same decision again, where
+/// losing the pick refunds part of the pot instead of losing all of it
+struct Insure<E> {
+    /// What's paid for the insurance, in bets
+    fee: f64,
+    insured: DiscreteDecision<E>,
+}
+impl<E> std::fmt::Debug for Insure<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Insure")
+    }
+}
+impl<E: RandomEvent> Choice<E> for Insure<E> {
+    fn score(&self, _: &[E]) -> f64 {
+        1.0 // the fee is paid out of the pot instead
+    }
+    fn fee(&self) -> f64 {
+        self.fee
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        Some(self.insured.clone())
+    }
+    fn reveals_card(&self) -> bool {
+        false
+    }
+}
+
+/// A choice made after insuring, which plays the same as the choice except that
+/// losing it refunds part of the pot
+struct Insured<E> {
+    choice: Rc<dyn Choice<E>>,
+    /// What's refunded, in bets
+    refund: f64,
+}
+impl<E> std::fmt::Debug for Insured<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.choice.fmt(f)
+    }
+}
+impl<E: RandomEvent> Choice<E> for Insured<E> {
+    fn score(&self, history: &[E]) -> f64 {
+        self.choice.score(history)
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        // only the upcoming pick is insured
+        self.choice.next_decision()
+    }
+    fn is_cashout(&self) -> bool {
+        self.choice.is_cashout()
+    }
+    fn reveals_card(&self) -> bool {
+        self.choice.reveals_card()
+    }
+    fn refund(&self) -> f64 {
+        self.refund
+    }
+    fn discarded(&self) -> Option<usize> {
+        self.choice.discarded()
+    }
+}
+
+/// Reveals the card of a choice without settling it, so that the card can be kept
+/// or discarded with a [`Mulligan`]
+struct Revealing<E> {
+    choice: Rc<dyn Choice<E>>,
+    /// The name of the mulligan, e.g. `Swap` for the card swap
+    redraw: &'static str,
+    /// The fraction of the pot paid for a mulligan
+    fee: f64,
+    /// The number of cards dealt before the revealed card
+    card: usize,
+}
+impl<E> std::fmt::Debug for Revealing<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.choice.fmt(f)
+    }
+}
+impl<E: RandomEvent> Choice<E> for Revealing<E> {
+    fn score(&self, _: &[E]) -> f64 {
+        1.0 // settled once the card is kept, or the card drawn after a mulligan
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        let keep = Rc::new(Keep(self.choice.clone())) as Rc<dyn Choice<E>>;
+        let mulligan = Rc::new(Mulligan {
+            choice: self.choice.clone(),
+            name: self.redraw,
+            fee: self.fee,
+            card: self.card,
+        });
+        Some(DiscreteDecision {
+            offered: true,
+            ..DiscreteDecision::from_choices(vec![keep, mulligan])
+        })
+    }
+    fn ends_stage(&self) -> bool {
+        false // the stage ends once the card is kept or drawn again
+    }
+}
+
+/// Keeps the card that was revealed, settling the choice with it
+struct Keep<E>(Rc<dyn Choice<E>>);
+impl<E> std::fmt::Debug for Keep<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Keep")
+    }
+}
+impl<E: RandomEvent> Choice<E> for Keep<E> {
+    fn score(&self, history: &[E]) -> f64 {
+        // no card is revealed, so the card being kept is the last one seen
+        self.0.score(history)
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        self.0.next_decision()
+    }
+    fn reveals_card(&self) -> bool {
+        false
+    }
+    fn refund(&self) -> f64 {
+        self.0.refund()
+    }
+    fn ends_stage(&self) -> bool {
+        true
+    }
+}
+
+/// Pays a fee to discard the card that was revealed and draw another, settling the
+/// choice with the new card instead, which can only be done once per game
+struct Mulligan<E> {
+    choice: Rc<dyn Choice<E>>,
+    name: &'static str,
+    /// The fraction of the pot paid for the mulligan
+    fee: f64,
+    /// The number of cards dealt before the discarded card
+    card: usize,
+}
+impl<E> std::fmt::Debug for Mulligan<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+impl<E: RandomEvent> Choice<E> for Mulligan<E> {
+    fn score(&self, history: &[E]) -> f64 {
+        // the discarded card is right after the new one
+        let history = [&history[..1], &history[2..]].concat();
+        (1.0 - self.fee) * self.choice.score(&history)
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        let card = self.card;
+        self.choice
+            .next_decision()
+            .map(|decision| decision.discarding(card))
+    }
+    fn refund(&self) -> f64 {
+        self.choice.refund()
+    }
+    fn discarded(&self) -> Option<usize> {
+        Some(self.card)
+    }
+}
+
+/// A choice made after a mulligan, which plays the same as the choice with the
+/// discarded card left out of the history
+struct Discarded<E> {
+    choice: Rc<dyn Choice<E>>,
+    /// The discarded card, counted from the first card dealt
+    card: usize,
+}
+impl<E> std::fmt::Debug for Discarded<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.choice.fmt(f)
+    }
+}
+impl<E: RandomEvent> Choice<E> for Discarded<E> {
+    fn score(&self, history: &[E]) -> f64 {
+        // the history is backwards, so the discarded card is counted from the end
+        let idx = history.len() - 1 - self.card;
+        let history = [&history[..idx], &history[idx + 1..]].concat();
+        self.choice.score(&history)
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision<E>> {
+        let card = self.card;
+        self.choice
+            .next_decision()
+            .map(|decision| decision.discarding(card))
+    }
+    fn is_cashout(&self) -> bool {
+        self.choice.is_cashout()
+    }
+    fn reveals_card(&self) -> bool {
+        self.choice.reveals_card()
+    }
+    fn refund(&self) -> f64 {
+        self.choice.refund()
+    }
+    fn fee(&self) -> f64 {
+        self.choice.fee()
+    }
+    fn discarded(&self) -> Option<usize> {
+        Some(self.card)
+    }
 }
@@ -1,5 +1,285 @@
-use super::{Choice, DiscreteDecision};
+use super::event::{Deck, EventSource, RandomEvent};
+use super::{Choice, DiscreteDecision, NamedChoice};
 use crate::PlayingCard;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, rc::Rc, time::Instant};
+use tracing::{debug, debug_span, info, info_span};
+
+/// What the solver maximizes when selecting the optimal choice of a decision
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Objective {
+    /// Maximize the expected value of the final pot
+    #[default]
+    ExpectedValue,
+    /// Maximize the probability of finishing with a pot at or above the
+    /// target multiplier (e.g. `4.0` to at least quadruple the bet)
+    TargetProbability(f64),
+    /// Maximize the expected utility of the bankroll after the game, which
+    /// prefers safer choices (like cashing out) the more risk averse it is
+    Utility(Utility),
+}
+impl Objective {
+    /// The value of finishing the game with the given pot, the solver
+    /// maximizes the average of this value
+    pub fn terminal_value(self, pot: f64) -> f64 {
+        match self {
+            Self::ExpectedValue => pot,
+            Self::TargetProbability(target) if pot >= target - 1e-6 => 1.0,
+            Self::TargetProbability(_) => 0.0,
+            Self::Utility(utility) => utility.of(pot),
+        }
+    }
+}
+
+/// A constant relative risk aversion (CRRA) utility of the player's bankroll
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Utility {
+    /// How risk averse the player is, where `0.0` is risk neutral (the same as
+    /// maximizing EV) and `1.0` is logarithmic utility
+    pub risk_aversion: f64,
+    /// The bankroll before the game, as a multiple of the bet, which must be more
+    /// than the bet so that losing it doesn't leave nothing
+    pub bankroll: f64,
+}
+impl Utility {
+    /// The utility of finishing the game with the given pot
+    pub fn of(self, pot: f64) -> f64 {
+        let wealth = self.bankroll - 1.0 + pot;
+        match self.risk_aversion {
+            gamma if (gamma - 1.0).abs() < 1e-9 => wealth.ln(),
+            gamma => (wealth.powf(1.0 - gamma) - 1.0) / (1.0 - gamma),
+        }
+    }
+    /// The pot that, if it was certain, would be worth the given (expected) utility
+    pub fn certainty_equivalent(self, utility: f64) -> f64 {
+        let wealth = match self.risk_aversion {
+            gamma if (gamma - 1.0).abs() < 1e-9 => utility.exp(),
+            gamma => (utility * (1.0 - gamma) + 1.0).powf(1.0 / (1.0 - gamma)),
+        };
+        wealth - self.bankroll + 1.0
+    }
+}
+
+/// How the solver separates choices with (nearly) equal objective values
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum TieBreak {
+    /// Prefer the higher EV, which is usually equal as well
+    #[default]
+    None,
+    /// Prefer the choice with the lowest variance of the final pot
+    Variance,
+    /// Prefer the choice with the lowest probability of losing the pot
+    BustProbability,
+}
+
+/// A wager placed before the game on the suit or rank of a card that will be dealt,
+/// e.g. the first card being an ace
+///
+/// The wager is settled when the game ends, so it's only won if the game lasts long
+/// enough for the card to be dealt (i.e. cashing out or losing earlier forfeits it)
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SideBet {
+    /// Which card the wager is on, where `0` is the first card dealt
+    pub card: usize,
+    /// What the card has to be for the wager to win
+    pub target: SideBetTarget,
+    /// The wager, as a multiple of the main bet
+    pub stake: f64,
+    /// What the stake is multiplied by when the wager wins (including the stake, so
+    /// `11.0` pays 10:1)
+    pub payout: f64,
+}
+/// What a [`SideBet`] wagers the card will be
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SideBetTarget {
+    /// The suit of the card (see [`PlayingCard::suit`])
+    Suit(u8),
+    /// The rank of the card (see [`PlayingCard::rank`])
+    Rank(u8),
+}
+impl SideBet {
+    /// What the wager wins at the end of a game less the stake, given the cards that
+    /// were dealt (backwards, see [`Choice::score`])
+    fn settle<E: RandomEvent>(&self, dealt: &[E]) -> f64 {
+        // the history is backwards, so the nth card dealt is counted from the end
+        let card = dealt
+            .len()
+            .checked_sub(self.card + 1)
+            .and_then(|idx| dealt.get(idx));
+        match card {
+            Some(&card) if self.target.matches(card) => self.stake * (self.payout - 1.0),
+            _ => -self.stake,
+        }
+    }
+}
+/// A progressive jackpot awarded for winning the game with a final card of the
+/// target suit or rank, e.g. winning the suit stage with an ace
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Jackpot {
+    /// What the final card has to be for the jackpot to be awarded
+    pub target: SideBetTarget,
+    /// The current size of the jackpot, as a multiple of the bet
+    pub amount: f64,
+}
+impl SideBetTarget {
+    /// Whether the card is the one wagered on
+    fn matches<E: RandomEvent>(self, card: E) -> bool {
+        match self {
+            Self::Suit(suit) => card.suit() == Some(suit),
+            Self::Rank(rank) => card.rank() == Some(rank),
+        }
+    }
+}
+
+/// Options for how a [`DiscreteDecisionTree`] is solved
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SolveOptions {
+    pub objective: Objective,
+    pub tie_break: TieBreak,
+    /// A side bet evaluated together with the game, so that values include what
+    /// it returns
+    pub side_bet: Option<SideBet>,
+    /// A jackpot awarded at the end of the game, so that values include it
+    pub jackpot: Option<Jackpot>,
+    /// Allows swapping the card revealed at one stage of the game for a fee
+    pub card_swap: Option<CardSwap>,
+    /// Allows doubling down once per game, at any of the stages it's offered at
+    pub double_down: Option<DoubleDown>,
+    /// Allows insuring the pick of one stage of the game for a fee
+    pub insurance: Option<Insurance>,
+    /// Allows discarding a revealed card and drawing again once per game, at any of
+    /// the stages it's offered at
+    pub mulligan: Option<Mulligan>,
+}
+
+impl SolveOptions {
+    /// What the side bet returns less its stake if the game ends with the choice, given
+    /// the cards dealt (backwards, with the card that resolved the choice first)
+    ///
+    /// Cashing out doesn't deal the card, and a card discarded with a mulligan was
+    /// never kept, so neither is considered
+    fn side_return<E: RandomEvent>(&self, choice: &dyn Choice<E>, dealt: &[E]) -> f64 {
+        let Some(side_bet) = self.side_bet else {
+            return 0.0;
+        };
+        let mut dealt = match choice.is_cashout() {
+            true => dealt[1..].to_vec(),
+            false => dealt.to_vec(),
+        };
+        if let Some(idx) = choice
+            .discarded()
+            .and_then(|card| dealt.len().checked_sub(card + 1))
+        {
+            dealt.remove(idx);
+        }
+        side_bet.settle(&dealt)
+    }
+    /// The jackpot awarded for winning the game with the choice, given the cards dealt
+    /// (backwards, with the card that resolved the choice first)
+    ///
+    /// Cashing out doesn't award it (since the card isn't dealt), and the card is the
+    /// one the stage ended with (the card kept, when keeping one instead of a mulligan)
+    fn jackpot_won<E: RandomEvent>(&self, choice: &dyn Choice<E>, dealt: &[E]) -> f64 {
+        let settled = choice
+            .ends_stage()
+            .then(|| dealt.first().copied())
+            .flatten();
+        match (self.jackpot, settled) {
+            (Some(jackpot), Some(card)) if !choice.is_cashout() && jackpot.target.matches(card) => {
+                jackpot.amount
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// A variant rule where, after the card of a stage is revealed, the player may pay a
+/// fraction of the pot to swap it for another before the stage resolves
+///
+/// It plays like a [`Mulligan`] at one stage, which it takes the place of there, and
+/// like a mulligan it's only offered if no card was discarded earlier in the game
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardSwap {
+    /// The stage the swap is offered at, where `1` is the first decision
+    pub stage: usize,
+    /// The fraction of the pot paid to swap
+    pub fee: f64,
+}
+
+/// A house rule where, before a stage resolves, the player may multiply the pot in
+/// exchange for playing the rest of the game out without cashing out
+///
+/// Every stage it's offered at adds a copy of the tree from that stage on, so
+/// offering it at every stage of Ride The Bus takes about 5x the memory
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DoubleDown {
+    /// The stages it's offered at, as a bit for each stage, where bit `1` is the
+    /// first decision
+    pub stages: u16,
+    /// What the pot is multiplied by, e.g. `2.0` to double it
+    pub multiplier: f64,
+}
+/// A variant rule where, before a stage resolves, the player may pay a fee out of
+/// the pot to insure the pick, so that losing it refunds part of the bet
+///
+/// It's only offered where the pot is more than the fee
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Insurance {
+    /// The stage the insurance is offered at, where `1` is the first decision
+    pub stage: usize,
+    /// What's paid for the insurance, in bets
+    pub fee: f64,
+    /// What's refunded if the pick loses, in bets
+    pub refund: f64,
+}
+
+/// A variant rule where, after the card of a stage is revealed, the player may pay a
+/// fraction of the pot to discard it and draw another, once per game
+///
+/// Every card that can be discarded is drawn again from the rest of the deck, which
+/// multiplies the size of the tree from that stage on by the cards left, so it's
+/// only feasible for small decks or games
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mulligan {
+    /// The stages it's offered at, as a bit for each stage, where bit `1` is the
+    /// first decision
+    pub stages: u16,
+    /// The fraction of the pot paid for the mulligan
+    pub fee: f64,
+}
+impl Mulligan {
+    /// Whether the mulligan is offered at the stage, where `1` is the first decision
+    pub fn is_offered_at(self, stage: usize) -> bool {
+        stage < 16 && self.stages & (1 << stage) != 0
+    }
+}
+
+impl DoubleDown {
+    /// Whether doubling down is offered at the stage, where `1` is the first decision
+    pub fn is_offered_at(self, stage: usize) -> bool {
+        stage < 16 && self.stages & (1 << stage) != 0
+    }
+}
+
+/// The criterion that separated the optimal choice from the next best choice
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Criterion {
+    Objective,
+    Variance,
+    BustProbability,
+    ExpectedValue,
+}
+
+/// Compares two values rounded to a multiple of `1e-9`, so values within floating
+/// point error of each other are equal
+///
+/// Rounding (rather than checking their difference) keeps the ordering transitive,
+/// which sorting relies on
+fn approx_cmp(v1: f64, v2: f64) -> Ordering {
+    // adding zero turns -0.0 into 0.0, which would otherwise be less
+    let round = |v: f64| (v / 1e-9).round() + 0.0;
+    f64::total_cmp(&round(v1), &round(v2))
+}
 
 /// An Evaluated Decision
 ///
@@ -11,27 +291,179 @@ use crate::PlayingCard;
 /// In a decision, you have the ability to select which choice you want.
 /// The [`DiscreteDecisionTree::optimal`] choice is the [`ChoiceEval`] with the highest
 /// expected value (EV), i.e. it's the choice you want to select for the best returns
-pub struct DiscreteDecisionTree {
-    choices: Vec<ChoiceEval>,
+///
+/// When solved for another [`Objective`], the optimal choice is instead the one with
+/// the highest [`ChoiceEval::objective_value`], and EVs are those of following that strategy.
+/// Ties are broken by the [`TieBreak`] the tree was solved with
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: Deserialize<'de>"))]
+pub struct DiscreteDecisionTree<E: RandomEvent = PlayingCard> {
+    choices: Vec<ChoiceEval<E>>,
     outcomes: usize,
+    pot: f64,
+    options: SolveOptions,
 }
 impl DiscreteDecisionTree {
-    /// Create/compute a decision tree from a starting decision
-    pub fn solve(first_decision: DiscreteDecision) -> Self {
-        Self::compute(first_decision, 1.0, &[])
+    /// Create/compute a decision tree from a starting decision, selecting
+    /// optimal choices using the given [`SolveOptions`]
+    ///
+    /// Choices are resolved by dealing from a standard [`Deck`]
+    pub fn solve(first_decision: DiscreteDecision, options: SolveOptions) -> Self {
+        Self::solve_with(first_decision, &Deck, options)
+    }
+}
+impl<E: RandomEvent> DiscreteDecisionTree<E> {
+    /// Create/compute a decision tree from a starting decision, where choices are
+    /// resolved by random events from `source`
+    pub fn solve_with(
+        first_decision: DiscreteDecision<E>,
+        source: &impl EventSource<E>,
+        options: SolveOptions,
+    ) -> Self {
+        let _span = info_span!("solve", ?options).entered();
+        let start = Instant::now();
+        let tree = Self::compute(first_decision, 1.0, &[], source, options);
+        info!(
+            outcomes = tree.outcomes,
+            decisions = tree.decision_count(),
+            elapsed = ?start.elapsed(),
+            "solved"
+        );
+        tree
+    }
+    /// Solves the decision reached from `first_decision` by a path of choices (by name,
+    /// case insensitive) and the events that resolved them, where choices are resolved
+    /// by random events from `source` (e.g. a deck missing cards dealt to other players)
+    ///
+    /// This only needs the rules of the game, so it works the same for trees that were
+    /// deserialized. The event is ignored for choices that don't reveal a card
+    ///
+    /// Returns `None` if the path isn't possible, or the game ends along it
+    pub fn solve_at(
+        first_decision: DiscreteDecision<E>,
+        path: &[(&str, Option<E>)],
+        source: &impl EventSource<E>,
+        options: SolveOptions,
+    ) -> Option<Self> {
+        let mut decision = first_decision;
+        let mut pot = 1.0;
+        // backwards, like the history of every other decision
+        let mut history = Vec::new();
+        for &(name, event) in path {
+            let choice = Self::offer_rules(decision, pot, history.len(), options)
+                .into_iter()
+                .find(|choice| format!("{:?}", choice).eq_ignore_ascii_case(name))?;
+            if choice.reveals_card() {
+                history.insert(0, event?);
+            }
+            pot = (pot - choice.fee()) * choice.score(&history);
+            if pot < 1e-6 {
+                return None;
+            }
+            decision = choice.next_decision()?;
+        }
+        let _span = debug_span!("solve_at", pot, seen = history.len()).entered();
+        let start = Instant::now();
+        let tree = Self::compute(decision, pot, &history, source, options);
+        debug!(outcomes = tree.outcomes, elapsed = ?start.elapsed(), "solved");
+        Some(tree)
+    }
+
+    /// Plays a hand by the rules of `first_decision` (the decision this tree was solved
+    /// from), taking the optimal choice at every decision and dealing the cards that
+    /// resolve them with `deal`, given the cards dealt so far (backwards)
+    ///
+    /// Returns the final value of the hand (the pot, and side bet if any), which is
+    /// scored from the cards dealt and the rules rather than the values in the tree, so
+    /// averaging it over many hands checks the solved EV. Returns `None` if `deal` runs
+    /// out of cards, or deals one the tree has no outcome for
+    pub fn play(
+        &self,
+        first_decision: DiscreteDecision<E>,
+        mut deal: impl FnMut(&[E]) -> Option<E>,
+    ) -> Option<f64> {
+        let (mut tree, mut decision, mut pot) = (self, first_decision, self.pot);
+        let mut history = Vec::new();
+        loop {
+            let optimal = tree.optimal()?;
+            let choice = Self::offer_rules(decision, pot, history.len(), self.options)
+                .into_iter()
+                .find(|choice| format!("{:?}", choice) == optimal.choice.name())?;
+            // cashing out still takes the card off the deck, it just isn't revealed
+            let event = match choice.reveals_card() {
+                true => Some(deal(&history)?),
+                false => None,
+            };
+            history.splice(0..0, event);
+            let side_value = self.options.side_return(&*choice, &history);
+            let new_pot = (pot - choice.fee()) * choice.score(&history);
+            if new_pot < 1e-6 {
+                return Some(choice.refund() + side_value);
+            }
+            match choice.next_decision() {
+                Some(next) => {
+                    tree = optimal.outcome(event)?.next_decision()?;
+                    decision = next;
+                    pot = new_pot;
+                }
+                None => {
+                    let jackpot = self.options.jackpot_won(&*choice, &history);
+                    return Some(new_pot + jackpot + side_value);
+                }
+            }
+        }
+    }
+
+    /// Offers insurance, doubling down, the card swap, and the mulligan at a decision
+    /// with `pot` if it's one of their stages, after `seen` cards (one is seen per
+    /// stage passed)
+    fn offer_rules(
+        decision: DiscreteDecision<E>,
+        pot: f64,
+        seen: usize,
+        options: SolveOptions,
+    ) -> DiscreteDecision<E> {
+        let stage = decision.stage(seen);
+        let decision = match options.insurance {
+            Some(insurance) if stage == Some(insurance.stage) && pot > insurance.fee => {
+                decision.with_insurance(insurance.fee, insurance.refund)
+            }
+            _ => decision,
+        };
+        let decision = match options.double_down {
+            Some(double_down) if stage.is_some_and(|stage| double_down.is_offered_at(stage)) => {
+                decision.with_double_down(double_down.multiplier)
+            }
+            _ => decision,
+        };
+        match (options.card_swap, options.mulligan) {
+            (Some(swap), _) if stage == Some(swap.stage) => decision.with_swap(swap.fee, seen),
+            (_, Some(mulligan)) if stage.is_some_and(|stage| mulligan.is_offered_at(stage)) => {
+                decision.with_mulligan(mulligan.fee, seen)
+            }
+            _ => decision,
+        }
     }
 
     /// Computes the DDTree (evaluates all choices in the decision) for the
     /// given pot value and card history
     ///
     /// # First Call
-    /// * `pot` = bet size, or 1.0 if calculating expected values
+    /// * `pot` = 1.0, since fees and refunds are in bets (multiples of the starting pot)
     /// * `history` = &[] (unless you wanna perform some shenanigans w/ small sets of cards)
-    fn compute(decision: DiscreteDecision, pot: f64, history: &[PlayingCard]) -> Self {
-        // evaluate each choice recursively
+    fn compute(
+        decision: DiscreteDecision<E>,
+        pot: f64,
+        history: &[E],
+        source: &dyn EventSource<E>,
+        options: SolveOptions,
+    ) -> Self {
+        let decision = Self::offer_rules(decision, pot, history.len(), options);
+
+        // evaluate each choice recursively, skipping the ones the source has no event for
         let evaluated_choices = decision
             .into_iter()
-            .map(|choice| ChoiceEval::evaluate(choice, pot, history))
+            .filter_map(|choice| ChoiceEval::evaluate(choice, pot, history, source, options))
             .collect::<Vec<_>>();
 
         // find the # of outcomes by summing the count at each outcome
@@ -41,22 +473,117 @@ impl DiscreteDecisionTree {
             .map(|o| o.count())
             .sum();
 
-        Self {
+        let mut tree = Self {
             choices: evaluated_choices,
             outcomes,
+            pot,
+            options,
+        };
+        // the regret of each choice is only known once they're all evaluated
+        let optimal_ev = tree.optimal().map_or(0.0, |optimal| optimal.expected_value);
+        for choice in &mut tree.choices {
+            choice.regret = optimal_ev - choice.expected_value;
         }
+        tree
     }
 
+    /// Compares two choices by their objective value, then by the tie break
+    /// (lower risk is better), then by EV
+    ///
+    /// Also returns the criterion that separated them, or `None` if they're equal
+    fn compare(&self, c1: &ChoiceEval<E>, c2: &ChoiceEval<E>) -> (Ordering, Option<Criterion>) {
+        let risk = match self.options.tie_break {
+            TieBreak::None => Ordering::Equal,
+            TieBreak::Variance => approx_cmp(c2.variance(), c1.variance()),
+            TieBreak::BustProbability => approx_cmp(c2.bust_probability, c1.bust_probability),
+        };
+        let risk_criterion = match self.options.tie_break {
+            TieBreak::Variance => Criterion::Variance,
+            _ => Criterion::BustProbability,
+        };
+        [
+            (
+                approx_cmp(c1.objective_value, c2.objective_value),
+                Criterion::Objective,
+            ),
+            (risk, risk_criterion),
+            (
+                f64::total_cmp(&c1.expected_value, &c2.expected_value),
+                Criterion::ExpectedValue,
+            ),
+        ]
+        .into_iter()
+        .find(|(ordering, _)| ordering.is_ne())
+        .map_or((Ordering::Equal, None), |(ordering, criterion)| {
+            (ordering, Some(criterion))
+        })
+    }
     /// Finds the optimal choice (or `None` if no choices are available)
     ///
-    /// The optimal choice is the choice with the highest expected value
-    pub fn optimal(&self) -> Option<&ChoiceEval> {
+    /// The optimal choice is the choice with the highest objective value,
+    /// which is the expected value unless solved for another [`Objective`].
+    /// Choices with (nearly) equal objective values are separated by the
+    /// [`TieBreak`], then by their EV
+    pub fn optimal(&self) -> Option<&ChoiceEval<E>> {
+        self.choices.iter().max_by(|c1, c2| self.compare(c1, c2).0)
+    }
+    /// All choices ranked from best to worst, i.e. the optimal choice first
+    ///
+    /// Choices that are equally good keep the order they were given in the decision
+    pub fn ranked_choices(&self) -> Vec<RankedChoice<'_, E>> {
+        let mut ranked = self.choices.iter().collect::<Vec<_>>();
+        ranked.sort_by(|c1, c2| self.compare(c2, c1).0);
+        let Some(&best) = ranked.first() else {
+            return Vec::new();
+        };
+        ranked
+            .into_iter()
+            .map(|choice| RankedChoice {
+                choice,
+                ev_gap: choice.regret,
+                is_optimal: self.compare(choice, best).0.is_eq(),
+            })
+            .collect()
+    }
+    /// The criterion that made the optimal choice better than the next best
+    /// choice, or `None` if they're equally good (or there are less than two choices)
+    pub fn optimal_criterion(&self) -> Option<Criterion> {
+        let optimal = self.optimal()?;
+        let runner_up = self
+            .choices
+            .iter()
+            .filter(|c| !std::ptr::eq(*c, optimal))
+            .max_by(|c1, c2| self.compare(c1, c2).0)?;
+        self.compare(optimal, runner_up).1
+    }
+    /// Finds the choice with the highest expected value (or `None` if no
+    /// choices are available)
+    ///
+    /// This is the same as [`DiscreteDecisionTree::optimal`] when solving for EV,
+    /// otherwise it shows where the strategy diverges from maximizing EV
+    pub fn ev_optimal(&self) -> Option<&ChoiceEval<E>> {
         self.choices
             .iter()
             .max_by(|c1, c2| f64::total_cmp(&c1.expected_value, &c2.expected_value))
     }
+    /// The options this tree was solved with
+    pub fn options(&self) -> SolveOptions {
+        self.options
+    }
+    /// The objective this tree was solved for
+    pub fn objective(&self) -> Objective {
+        self.options.objective
+    }
+    /// The side bet this tree was solved with, if any
+    pub fn side_bet(&self) -> Option<SideBet> {
+        self.options.side_bet
+    }
+    /// The jackpot this tree was solved with, if any
+    pub fn jackpot(&self) -> Option<Jackpot> {
+        self.options.jackpot
+    }
     /// An iterator over all evaluated choices
-    pub fn iter(&self) -> impl Iterator<Item = &ChoiceEval> {
+    pub fn iter(&self) -> impl Iterator<Item = &ChoiceEval<E>> {
         self.choices.iter()
     }
     /// The total number of outcomes in this decision tree
@@ -65,62 +592,458 @@ impl DiscreteDecisionTree {
     pub fn outcome_count(&self) -> usize {
         self.outcomes
     }
+    /// The pot (multiplier of the bet) at this decision, i.e. what cashing
+    /// out right now would give
+    pub fn pot(&self) -> f64 {
+        self.pot
+    }
+    /// The number of stages in the longest path through this tree (including
+    /// this decision), where each stage ends with a choice that ends it (see
+    /// [`Choice::ends_stage`])
+    pub fn depth(&self) -> usize {
+        let child_depth = self
+            .iter()
+            .flat_map(|c| c.iter().map(|o| (c.choice.ends_stage(), o)))
+            .filter_map(|(ends_stage, o)| Some((ends_stage, o.next_decision()?)))
+            .map(|(ends_stage, ddt)| match ends_stage {
+                true => ddt.depth(),
+                // the next decision is still this stage
+                false => ddt.depth() - 1,
+            })
+            .max()
+            .unwrap_or(0);
+        child_depth + 1
+    }
+    /// The number of decisions in this tree, including this one
+    pub fn decision_count(&self) -> usize {
+        let children = self
+            .iter()
+            .flat_map(|c| c.iter())
+            .filter_map(|o| o.next_decision())
+            .map(|ddt| ddt.decision_count())
+            .sum::<usize>();
+        children + 1
+    }
+    /// An estimate of the memory used by this tree in bytes, counting what every
+    /// decision, choice, and outcome allocates
+    pub fn memory_size(&self) -> usize {
+        let choices = self
+            .choices
+            .iter()
+            .map(|c| {
+                let probabilities = c.probabilities.as_ref().map_or(0, Vec::capacity);
+                let children = c
+                    .iter()
+                    .filter_map(|o| o.next_decision())
+                    .map(|ddt| ddt.memory_size())
+                    .sum::<usize>();
+                c.choice.name().len()
+                    + c.reach_probabilities.len() * size_of::<f64>()
+                    + c.random_events.capacity() * size_of::<RandomEventOutcome<E>>()
+                    + probabilities * size_of::<f64>()
+                    + children
+            })
+            .sum::<usize>();
+        size_of::<Self>() + self.choices.capacity() * size_of::<ChoiceEval<E>>() + choices
+    }
+    /// An iterator over every complete path through the tree, from this decision
+    /// to the end of the game, taking every choice (not just the optimal ones)
+    ///
+    /// Paths are yielded depth first, in the order of the choices and events
+    pub fn leaf_paths(&self) -> LeafPaths<'_, E> {
+        LeafPaths {
+            stack: vec![LeafFrame {
+                tree: self,
+                choice: 0,
+                outcome: 0,
+                probability: 1.0,
+            }],
+            path: Vec::new(),
+        }
+    }
+    /// Finds a choice by its name, case insensitive
+    pub fn choice(&self, name: &str) -> Option<&ChoiceEval<E>> {
+        self.iter()
+            .find(|c| format!("{:?}", c.choice).eq_ignore_ascii_case(name))
+    }
+    /// The decision reached by taking a choice (by name, case insensitive) and the
+    /// event that resolved it (see [`ChoiceEval::outcome`])
+    ///
+    /// Returns `None` if the choice or event isn't possible, or the game ends
+    pub fn descend(&self, choice: &str, event: Option<E>) -> Option<&DiscreteDecisionTree<E>> {
+        self.choice(choice)?.outcome(event)?.next_decision()
+    }
+    /// Walks the tree along a path of choices (by name, case insensitive) and the
+    /// events that resolved them, grading each step against the optimal choice
+    ///
+    /// The event is `None` for choices that don't reveal a card, and is ignored
+    /// when cashing out
+    pub fn evaluate_path(
+        &self,
+        path: &[(&str, Option<E>)],
+    ) -> Result<Vec<PathStep>, InvalidPathError> {
+        let mut tree = Some(self);
+        let mut steps = Vec::with_capacity(path.len());
+        for (step, &(name, event)) in path.iter().enumerate() {
+            let current = tree.ok_or(InvalidPathError::GameOver { step })?;
+            let choice = current
+                .choice(name)
+                .ok_or(InvalidPathError::UnknownChoice { step })?;
+            let outcome = choice
+                .outcome(event)
+                .ok_or(InvalidPathError::UnknownEvent { step })?;
+            let optimal = current.optimal().expect("a choice was found");
+
+            tree = outcome.next_decision();
+            steps.push(PathStep {
+                expected_value: choice.expected_value,
+                optimal_value: optimal.expected_value,
+                is_optimal: current.compare(choice, optimal).0.is_eq(),
+                pot: tree.map_or(outcome.value, |next| next.pot),
+            });
+        }
+        Ok(steps)
+    }
+}
+
+/// A complete path through the tree, yielded by [`DiscreteDecisionTree::leaf_paths`]
+pub struct LeafPath<'a, E: RandomEvent = PlayingCard> {
+    /// The choices taken and the events that resolved them, in order (the event is
+    /// `None` for choices that don't reveal a card)
+    pub steps: Vec<(&'a ChoiceEval<E>, Option<E>)>,
+    /// The probability of the events along the path, given the choices taken
+    pub probability: f64,
+    /// The final value of the path (the pot, and side bet if any)
+    pub payout: f64,
+}
+
+/// An iterator over every complete path through a tree, see
+/// [`DiscreteDecisionTree::leaf_paths`]
+pub struct LeafPaths<'a, E: RandomEvent = PlayingCard> {
+    // a depth first traversal, with a frame for each decision on the current path
+    stack: Vec<LeafFrame<'a, E>>,
+    path: Vec<(&'a ChoiceEval<E>, Option<E>)>,
+}
+struct LeafFrame<'a, E: RandomEvent> {
+    tree: &'a DiscreteDecisionTree<E>,
+    /// The index of the next choice and outcome to visit
+    choice: usize,
+    outcome: usize,
+    /// The probability of reaching this decision
+    probability: f64,
+}
+impl<'a, E: RandomEvent> Iterator for LeafPaths<'a, E> {
+    type Item = LeafPath<'a, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            let Some(choice) = frame.tree.choices.get(frame.choice) else {
+                // every choice was visited, so go back to the previous decision
+                self.stack.pop();
+                self.path.pop();
+                continue;
+            };
+            let Some(outcome) = choice.random_events.get(frame.outcome) else {
+                frame.choice += 1;
+                frame.outcome = 0;
+                continue;
+            };
+            let probability = frame.probability * choice.probability(frame.outcome);
+            frame.outcome += 1;
+            match outcome.next_decision() {
+                Some(next) => {
+                    self.path.push((choice, outcome.event));
+                    self.stack.push(LeafFrame {
+                        tree: next,
+                        choice: 0,
+                        outcome: 0,
+                        probability,
+                    });
+                }
+                None => {
+                    let mut steps = self.path.clone();
+                    steps.push((choice, outcome.event));
+                    return Some(LeafPath {
+                        steps,
+                        probability,
+                        payout: outcome.value,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// A step of a path through the tree, graded by [`DiscreteDecisionTree::evaluate_path`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathStep {
+    /// The EV of the choice that was taken
+    pub expected_value: f64,
+    /// The EV of the optimal choice, which is the most that could be expected
+    /// unless solved for another [`Objective`]
+    pub optimal_value: f64,
+    /// Whether the choice taken was optimal
+    pub is_optimal: bool,
+    /// The pot after the step, or the final value if the game ended
+    pub pot: f64,
+}
+
+/// Why a path couldn't be followed through the tree, with the index of the step
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvalidPathError {
+    /// The step's choice isn't in the decision
+    UnknownChoice { step: usize },
+    /// The step's event can't happen after its choice (e.g. a card that was already seen)
+    UnknownEvent { step: usize },
+    /// The game already ended before the step
+    GameOver { step: usize },
+}
+
+/// A choice as ranked by [`DiscreteDecisionTree::ranked_choices`]
+pub struct RankedChoice<'a, E: RandomEvent = PlayingCard> {
+    pub choice: &'a ChoiceEval<E>,
+    /// How much less EV this choice has than the best (first ranked) choice
+    ///
+    /// This can be negative when solving for another [`Objective`], since the
+    /// best choice for the objective may not have the highest EV
+    pub ev_gap: f64,
+    /// Whether the choice is as good as the best choice (i.e. it's optimal too)
+    pub is_optimal: bool,
 }
 
 /// An Evaluated Choice
 ///
 /// A choice is evaluated by evaluating a [`RandomEventOutcome`] for every
 /// random event possible with this choice, then averaging the [`RandomEventOutcome::value`]
-/// of each  outcome (since each RE is equally likely)
-pub struct ChoiceEval {
-    pub choice: Box<dyn Choice>,
+/// of each outcome, weighted by the probability of each RE
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: Deserialize<'de>"))]
+pub struct ChoiceEval<E: RandomEvent = PlayingCard> {
+    pub choice: NamedChoice,
     pub expected_value: f64,
-    random_events: Vec<RandomEventOutcome>,
+    /// The average value of the [`Objective`] the tree was solved for, e.g.
+    /// the probability of reaching the target for [`Objective::TargetProbability`]
+    pub objective_value: f64,
+    /// The probability of losing the pot by the end of the game, following the
+    /// optimal choices after this one
+    pub bust_probability: f64,
+    /// The expected return of the side bet less its stake, which is included in the
+    /// expected value, following the optimal choices after this one (`0` without a
+    /// side bet)
+    pub side_value: f64,
+    /// The expected value of the final pot squared, used for the variance
+    second_moment: f64,
+    /// How much less EV this choice has than the optimal choice of its decision
+    regret: f64,
+    win_probability: f64,
+    /// The probability of reaching each of the following stages
+    reach_probabilities: Box<[f64]>,
+    random_events: Vec<RandomEventOutcome<E>>,
+    /// The probability of each random event, or `None` if they're equally likely
+    /// (which saves storing them for a deck)
+    probabilities: Option<Vec<f64>>,
 }
-impl ChoiceEval {
+impl<E: RandomEvent> ChoiceEval<E> {
     /// Compute a [`ChoiceEval`] for the given choice, pot, and RE history
-    fn evaluate(choice: Box<dyn Choice>, pot: f64, history: &[PlayingCard]) -> Self {
-        // sum of all expected values, used to get average expected value
-        // for this choice over all random events
+    ///
+    /// Returns `None` if the choice reveals an event but no event can happen (e.g.
+    /// the deck ran out), since it can't be resolved
+    fn evaluate(
+        choice: Rc<dyn Choice<E>>,
+        pot: f64,
+        history: &[E],
+        source: &dyn EventSource<E>,
+        options: SolveOptions,
+    ) -> Option<Self> {
+        // weighted sum of all expected values, used to get the (weighted) average
+        // expected value for this choice over all random events
         let mut ev_sum = 0.0;
-        let mut all_random_events = Vec::with_capacity(52); // TODO: unhardcode
-
-        // compute the EV for each random event given the choice,
-        // then average all EVs (since each event is equally likely) to get
-        // the overall EV for this choice
-        let card_iter = PlayingCard::deck_iter().filter(|card| !history.contains(card));
-        for card in card_iter {
-            let random_event = RandomEventOutcome::evaluate(card, &*choice, pot, history);
-            ev_sum += random_event.value;
+        let mut objective_sum = 0.0;
+        let mut bust_sum = 0.0;
+        let mut side_sum = 0.0;
+        let mut second_moment_sum = 0.0;
+        let mut win_sum = 0.0;
+        let mut reach_sums = Vec::new();
+
+        // compute the EV for each random event given the choice, then average all
+        // EVs (weighted by how likely each event is) to get the overall EV for this choice
+        // a choice that doesn't reveal a card has a single (certain) outcome instead
+        let events = match choice.reveals_card() {
+            true => source
+                .events(history)
+                .into_iter()
+                .map(|(event, weight)| (Some(event), weight))
+                .collect(),
+            false => vec![(None, 1.0)],
+        };
+        let total_weight = events.iter().fold(0.0, |sum, (_, weight)| sum + weight);
+        if total_weight <= 0.0 {
+            return None;
+        }
+        let uniform = events.iter().all(|&(_, weight)| weight == events[0].1);
+
+        let mut all_random_events = Vec::with_capacity(events.len());
+        let mut probabilities = Vec::new();
+        for (event, weight) in events {
+            let (random_event, new_pot, lost) =
+                RandomEventOutcome::evaluate(event, &*choice, pot, history, source, options);
+            ev_sum += weight * random_event.value;
+            win_sum += if lost { 0.0 } else { weight };
+            // like the value, the other statistics come from the optimal choice of the
+            // next decision, or from the final value if there is no next decision
+            match random_event.next_decision().and_then(|ddt| ddt.optimal()) {
+                Some(next) => {
+                    objective_sum += weight * next.objective_value;
+                    bust_sum += weight * next.bust_probability;
+                    side_sum += weight * next.side_value;
+                    second_moment_sum += weight * next.second_moment;
+                    // reaching the next decision reaches the next stage, unless the
+                    // choice didn't end the stage (e.g. no card was revealed, or it can
+                    // still be discarded), which leaves it in this stage
+                    let reached = match choice.ends_stage() {
+                        true => std::iter::once(&1.0)
+                            .chain(next.reach_probabilities.iter())
+                            .collect::<Vec<_>>(),
+                        false => next.reach_probabilities.iter().collect(),
+                    };
+                    if reach_sums.len() < reached.len() {
+                        reach_sums.resize(reached.len(), 0.0);
+                    }
+                    for (sum, probability) in reach_sums.iter_mut().zip(reached) {
+                        *sum += weight * probability;
+                    }
+                }
+                None => {
+                    objective_sum += weight * options.objective.terminal_value(random_event.value);
+                    bust_sum += if lost { weight } else { 0.0 };
+                    // the final value is the pot left plus what the side bet returned
+                    side_sum += weight * (random_event.value - new_pot);
+                    second_moment_sum += weight * random_event.value.powi(2);
+                }
+            }
             all_random_events.push(random_event);
+            if !uniform {
+                probabilities.push(weight / total_weight);
+            }
         }
 
-        let expected_value = ev_sum / all_random_events.len() as f64;
-        Self {
-            choice,
-            expected_value,
+        Some(Self {
+            choice: NamedChoice::of(&*choice),
+            expected_value: ev_sum / total_weight,
+            objective_value: objective_sum / total_weight,
+            bust_probability: bust_sum / total_weight,
+            side_value: side_sum / total_weight,
+            second_moment: second_moment_sum / total_weight,
+            // set by the decision once every choice is evaluated
+            regret: 0.0,
+            win_probability: win_sum / total_weight,
+            reach_probabilities: reach_sums
+                .into_iter()
+                .map(|sum| sum / total_weight)
+                .collect(),
             random_events: all_random_events,
+            probabilities: (!uniform).then_some(probabilities),
+        })
+    }
+
+    /// How much EV is lost by taking this choice instead of the optimal choice of
+    /// its decision (`0` for the optimal choice)
+    ///
+    /// This can be negative when solving for another [`Objective`], since the
+    /// optimal choice may not have the highest EV
+    pub fn regret(&self) -> f64 {
+        self.regret
+    }
+    /// The probability that this choice succeeds (doesn't lose the pot)
+    pub fn win_probability(&self) -> f64 {
+        self.win_probability
+    }
+    /// The probability of reaching each of the stages after this one, following the
+    /// optimal choices after this one (index 0 is the next stage)
+    ///
+    /// Stages that can't be reached with this choice (e.g. after cashing out) are left out
+    pub fn reach_probabilities(&self) -> &[f64] {
+        &self.reach_probabilities
+    }
+
+    /// The variance of the final pot, following the optimal choices after this one
+    pub fn variance(&self) -> f64 {
+        // clamp, since floating point error can make a zero variance slightly negative
+        f64::max(self.second_moment - self.expected_value.powi(2), 0.0)
+    }
+    /// The standard deviation of the final pot, following the optimal choices after
+    /// this one
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// The probability of each final pot following this choice, and the optimal
+    /// choices after it
+    ///
+    /// Returns `(pot, probability)` pairs sorted by pot, with probabilities summing to 1
+    pub fn payout_distribution(&self) -> Vec<(f64, f64)> {
+        let mut distribution = Vec::new();
+        self.add_payouts(1.0, &mut distribution);
+        distribution.sort_by(|(p1, _), (p2, _)| f64::total_cmp(p1, p2));
+        distribution
+    }
+    fn add_payouts(&self, probability: f64, distribution: &mut Vec<(f64, f64)>) {
+        for (outcome_probability, outcome) in self.iter_probabilities() {
+            let probability = probability * outcome_probability;
+            match outcome.next_decision().and_then(|ddt| ddt.optimal()) {
+                Some(next) => next.add_payouts(probability, distribution),
+                None => match distribution
+                    .iter_mut()
+                    .find(|(pot, _)| (pot - outcome.value).abs() < 1e-6)
+                {
+                    Some((_, p)) => *p += probability,
+                    None => distribution.push((outcome.value, probability)),
+                },
+            }
         }
     }
 
     /// An iterator over all random events and their outcomes
-    pub fn iter(&self) -> impl Iterator<Item = &RandomEventOutcome> {
+    pub fn iter(&self) -> impl Iterator<Item = &RandomEventOutcome<E>> {
         self.random_events.iter()
     }
+    /// An iterator over all random events and their outcomes, along with the
+    /// probability of each
+    pub fn iter_probabilities(&self) -> impl Iterator<Item = (f64, &RandomEventOutcome<E>)> {
+        self.iter()
+            .enumerate()
+            .map(|(i, outcome)| (self.probability(i), outcome))
+    }
+    /// The probability of the random event at the index
+    fn probability(&self, idx: usize) -> f64 {
+        match &self.probabilities {
+            Some(probabilities) => probabilities[idx],
+            None => 1.0 / self.random_events.len() as f64,
+        }
+    }
     /// Get an outcome based on the random event
-    pub fn get(&self, event: PlayingCard) -> Option<&RandomEventOutcome> {
-        self.iter().find(|outcome| outcome.event == event)
+    pub fn get(&self, event: E) -> Option<&RandomEventOutcome<E>> {
+        self.iter().find(|outcome| outcome.event == Some(event))
+    }
+    /// Get the outcome of taking this choice, where the event is `None` for choices
+    /// that don't reveal a card, and is ignored when cashing out
+    pub fn outcome(&self, event: Option<E>) -> Option<&RandomEventOutcome<E>> {
+        match self.choice.is_cashout() {
+            // every card keeps the pot when cashing out
+            true => self.iter().next(),
+            false => self.iter().find(|outcome| outcome.event == event),
+        }
     }
 }
 
-
 /// An Evaluated Random Event (RE) for a [`Choice`] (evaluated choice+card)
 ///
 /// A random event is evaluated by finding the [`Choice::score`] of the choice+card:
 /// 1. If `score` == 0.0, then we lost and the value is 0.0
 /// 2. Elif `choice.next_decision().is_some()`, then we evaluate that decision with
-/// [`DiscreteDecisionTree`] using `new_pot = pot * score` and use the optimal choice's EV
-/// as the value
+///    [`DiscreteDecisionTree`] using `new_pot = pot * score` and use the optimal choice's EV
+///    as the value
 /// 3. Else, because there's no next decision, the value is the `pot * score`
 ///
 /// # Score
@@ -128,58 +1051,91 @@ impl ChoiceEval {
 /// essentially the pot multiplier depending on the RE. For example, if you
 /// chose Red and got a red card, the score would be `2.0` (2x multiplier),
 /// and if you got a black card the score would be `0.0` (you lost your money)
-pub struct RandomEventOutcome {
-    pub event: PlayingCard,
+///
+/// Choices that don't reveal a card (see [`Choice::reveals_card`]) have a single
+/// outcome, which has no event
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: Deserialize<'de>"))]
+pub struct RandomEventOutcome<E: RandomEvent = PlayingCard> {
+    pub event: Option<E>,
     pub value: f64,
-    next_decision_tree: Option<DiscreteDecisionTree>,
+    lost: bool,
+    // boxed, since most outcomes are leaves and shouldn't pay for the size of a tree
+    next_decision_tree: Option<Box<DiscreteDecisionTree<E>>>,
 }
-impl RandomEventOutcome {
+impl<E: RandomEvent> RandomEventOutcome<E> {
     /// Evaluate the outcome (most importantly value) of a choice+card
     /// (Random Event given a choice)
+    ///
+    /// Also returns the pot after the random event (with the jackpot if it was won,
+    /// or what was refunded if it was lost), and whether it was lost
     fn evaluate(
-        event: PlayingCard,
-        choice: &dyn Choice,
+        event: Option<E>,
+        choice: &dyn Choice<E>,
         pot: f64,
-        history: &[PlayingCard],
-    ) -> Self {
+        history: &[E],
+        source: &dyn EventSource<E>,
+        options: SolveOptions,
+    ) -> (Self, f64, bool) {
         // create a new history with this card prepended (essentially a backwards history)
         let mut new_history = Vec::with_capacity(history.len() + 1);
-        new_history.push(event);
+        new_history.extend(event);
         new_history.extend_from_slice(history);
 
+        // what the side bet returns if the game ends here
+        let side_value = options.side_return(choice, &new_history);
+
         // calculate the outcome score for this choice+card
-        let new_pot = pot * choice.score(&new_history);
+        let new_pot = (pot - choice.fee()) * choice.score(&new_history);
         if new_pot < 1e-6 {
-            // we lost (new_pot == 0), so there is no next decision tree
-            return Self {
+            // we lost (new_pot == 0), so there is no next decision tree, only a refund
+            // if the choice was insured
+            let refund = choice.refund();
+            let outcome = Self {
                 event,
-                value: 0.0,
+                value: refund + side_value,
+                lost: true,
                 next_decision_tree: None,
             };
+            return (outcome, refund, true);
         }
 
         // compute the decision tree for the next decision (if it exists)
         let next_decision_tree = choice
             .next_decision()
-            .map(|decision| DiscreteDecisionTree::compute(decision, new_pot, &new_history));
+            .map(|decision| {
+                DiscreteDecisionTree::compute(decision, new_pot, &new_history, source, options)
+            })
+            .map(Box::new);
+        let new_pot = match next_decision_tree {
+            Some(_) => new_pot,
+            None => new_pot + options.jackpot_won(choice, &new_history),
+        };
         // get the value of this outcome
         // the value is the expected value of the optimal choice of the next decision
-        // if there is no next decision, then the value is simply the new_pot
+        // if there is no next decision, then the value is simply the new_pot (and side bet)
         let value = next_decision_tree
             .as_ref()
             .and_then(|ddt| ddt.optimal())
             .map(|choice| choice.expected_value)
-            .unwrap_or(new_pot);
-        Self {
+            .unwrap_or(new_pot + side_value);
+        let outcome = Self {
             event,
             value,
+            lost: false,
             next_decision_tree,
-        }
+        };
+        (outcome, new_pot, false)
     }
 
+    /// Whether the choice lost the pot with this event (ending the game), where the
+    /// value is only what was refunded or returned by the side bet
+    pub fn is_lost(&self) -> bool {
+        self.lost
+    }
     /// The child decision tree for this outcome
-    pub fn next_decision(&self) -> Option<&DiscreteDecisionTree> {
-        self.next_decision_tree.as_ref()
+    pub fn next_decision(&self) -> Option<&DiscreteDecisionTree<E>> {
+        self.next_decision_tree.as_deref()
     }
     /// The count is the total number of outcomes for this event
     ///
@@ -192,3 +1148,454 @@ impl RandomEventOutcome {
             .unwrap_or(1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::{builder::GameBuilder, cursor::GameCursor};
+
+    fn card(card: &str) -> PlayingCard {
+        card.parse().ok().unwrap()
+    }
+    /// A small deck of `cards`, dealt without replacement
+    fn deck(cards: &[&str]) -> impl EventSource<PlayingCard> + use<> {
+        let cards = cards.iter().map(|c| card(c)).collect::<Vec<_>>();
+        move |history: &[PlayingCard]| {
+            cards
+                .iter()
+                .filter(|card| !history.contains(card))
+                .map(|&card| (card, 1.0))
+                .collect()
+        }
+    }
+    /// A single stage that pays 4x for a heart
+    fn hearts() -> DiscreteDecision {
+        GameBuilder::new()
+            .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+            .build()
+    }
+    fn ev(tree: &DiscreteDecisionTree) -> f64 {
+        tree.optimal().unwrap().expected_value
+    }
+    fn assert_close(value: f64, expected: f64) {
+        assert!((value - expected).abs() < 1e-9, "{value} != {expected}");
+    }
+    const FOUR_CARDS: [&str; 4] = ["AH", "AS", "KS", "KC"];
+
+    #[test]
+    fn mulligan_that_isnt_worth_taking_leaves_the_ev_unchanged() {
+        let jackpot = Some(Jackpot {
+            target: SideBetTarget::Suit(0),
+            amount: 10.0,
+        });
+        let options = SolveOptions {
+            jackpot,
+            ..Default::default()
+        };
+        // a heart wins 4x and the jackpot
+        let tree = DiscreteDecisionTree::solve_with(hearts(), &deck(&FOUR_CARDS), options);
+        assert_close(ev(&tree), 0.25 * (4.0 + 10.0));
+
+        // a mulligan that costs the whole pot is never taken, so keeping the heart
+        // still wins the jackpot
+        let options = SolveOptions {
+            mulligan: Some(Mulligan {
+                stages: 1 << 1,
+                fee: 1.0,
+            }),
+            ..options
+        };
+        let tree = DiscreteDecisionTree::solve_with(hearts(), &deck(&FOUR_CARDS), options);
+        assert_close(ev(&tree), 0.25 * (4.0 + 10.0));
+    }
+
+    #[test]
+    fn side_bet_that_loses_on_average_lowers_the_ev() {
+        let without =
+            DiscreteDecisionTree::solve_with(hearts(), &deck(&FOUR_CARDS), SolveOptions::default());
+        // a heart pays 3x the stake back, but only 1 of the 4 cards is a heart
+        let options = SolveOptions {
+            side_bet: Some(SideBet {
+                card: 0,
+                target: SideBetTarget::Suit(0),
+                stake: 1.0,
+                payout: 3.0,
+            }),
+            ..Default::default()
+        };
+        let with = DiscreteDecisionTree::solve_with(hearts(), &deck(&FOUR_CARDS), options);
+        let optimal = with.optimal().unwrap();
+        assert_close(optimal.side_value, 0.25 * 2.0 - 0.75 * 1.0);
+        assert_close(ev(&with), ev(&without) + optimal.side_value);
+        assert!(ev(&with) < ev(&without));
+    }
+
+    #[test]
+    fn playing_every_deal_averages_to_the_ev() {
+        let options = SolveOptions {
+            side_bet: Some(SideBet {
+                card: 0,
+                target: SideBetTarget::Rank(14),
+                stake: 0.5,
+                payout: 3.0,
+            }),
+            jackpot: Some(Jackpot {
+                target: SideBetTarget::Suit(0),
+                amount: 10.0,
+            }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(hearts(), &deck(&FOUR_CARDS), options);
+        // each card of the deck dealt first, which are equally likely
+        let total = FOUR_CARDS
+            .iter()
+            .map(|&dealt| tree.play(hearts(), |_| Some(card(dealt))).unwrap())
+            .sum::<f64>();
+        assert_close(total / 4.0, ev(&tree));
+        assert_eq!(tree.play(hearts(), |_| None), None);
+    }
+
+    #[test]
+    fn mulligan_draws_again_from_the_rest_of_the_deck() {
+        let options = SolveOptions {
+            mulligan: Some(Mulligan {
+                stages: 1 << 1,
+                fee: 0.5,
+            }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(hearts(), &deck(&FOUR_CARDS), options);
+        // a spade or club is discarded for half the pot, then 1 of the 3 cards left wins
+        assert_close(ev(&tree), 0.25 * 4.0 + 0.75 * (0.5 * 4.0 / 3.0));
+    }
+
+    #[test]
+    fn side_bet_settles_the_card_kept_after_a_mulligan() {
+        let any = GameBuilder::new()
+            .choice("Any", 1.0, |_: &[PlayingCard]| true)
+            .build();
+        let options = SolveOptions {
+            side_bet: Some(SideBet {
+                card: 0,
+                target: SideBetTarget::Suit(0),
+                stake: 1.0,
+                payout: 3.0,
+            }),
+            mulligan: Some(Mulligan {
+                stages: 1 << 1,
+                fee: 0.5,
+            }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(any, &deck(&FOUR_CARDS), options);
+        // a heart is kept for the side bet (winning 2x the stake on top of it), anything
+        // else is discarded for half the pot and another go at the side bet (which the
+        // discarded card doesn't win, and 1 of the 3 cards left does)
+        assert_close(
+            ev(&tree),
+            0.25 * (1.0 + 2.0) + 0.75 * (0.5 + (2.0 - 2.0 * 1.0) / 3.0),
+        );
+    }
+
+    #[test]
+    fn choices_are_skipped_once_the_source_runs_out() {
+        let two_stages = GameBuilder::new()
+            .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+            .stage()
+            .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+            .with_cashout()
+            .build();
+        let tree =
+            DiscreteDecisionTree::solve_with(two_stages, &deck(&["AH"]), SolveOptions::default());
+        // the second stage has no card left to deal, so the hand ends with the pot
+        assert_close(ev(&tree), 4.0);
+        let second = tree.optimal().unwrap().iter().next().unwrap();
+        assert_eq!(second.next_decision().unwrap().iter().count(), 0);
+    }
+
+    #[test]
+    fn mulligan_doesnt_add_a_stage() {
+        let two_stages = || {
+            GameBuilder::new()
+                .choice("Any", 1.0, |_: &[PlayingCard]| true)
+                .stage()
+                .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+                .build()
+        };
+        let options = SolveOptions {
+            mulligan: Some(Mulligan {
+                stages: 1 << 2,
+                fee: 0.5,
+            }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(two_stages(), &deck(&FOUR_CARDS), options);
+        assert_eq!(tree.optimal().unwrap().reach_probabilities(), [1.0]);
+    }
+
+    #[test]
+    fn mulligan_leaves_the_depth_unchanged() {
+        let two_stages = || {
+            GameBuilder::new()
+                .choice("Any", 1.0, |_: &[PlayingCard]| true)
+                .stage()
+                .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+                .build()
+        };
+        let tree = DiscreteDecisionTree::solve_with(
+            two_stages(),
+            &deck(&FOUR_CARDS),
+            SolveOptions::default(),
+        );
+        assert_eq!(tree.depth(), 2);
+
+        let options = SolveOptions {
+            mulligan: Some(Mulligan {
+                stages: 1 << 1 | 1 << 2,
+                fee: 0.5,
+            }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(two_stages(), &deck(&FOUR_CARDS), options);
+        assert_eq!(tree.depth(), 2);
+    }
+
+    /// Any card doubles the pot, then a heart wins 4x
+    fn any_then_hearts() -> DiscreteDecision {
+        GameBuilder::new()
+            .choice("Any", 2.0, |_: &[PlayingCard]| true)
+            .stage()
+            .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+            .build()
+    }
+    const INSURANCE: Insurance = Insurance {
+        stage: 2,
+        fee: 0.5,
+        refund: 1.5,
+    };
+
+    #[test]
+    fn insurance_pays_its_fee_and_refund_in_bets() {
+        let options = SolveOptions {
+            insurance: Some(INSURANCE),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(any_then_hearts(), &deck(&FOUR_CARDS), options);
+        // the pot of 2 is insured for 0.5, then a heart wins 4x what's left and anything
+        // else refunds 1.5, where no heart is left after the ace of hearts
+        assert_close(
+            ev(&tree),
+            0.25 * 1.5 + 0.75 * ((2.0 - 0.5) * 4.0 / 3.0 + 2.0 / 3.0 * 1.5),
+        );
+        let after_heart = tree.descend("Any", Some(card("AH"))).unwrap();
+        assert_eq!(after_heart.optimal().unwrap().choice.name(), "Insure");
+    }
+
+    #[test]
+    fn insurance_is_offered_after_doubling_down_at_the_doubled_pot() {
+        let options = SolveOptions {
+            insurance: Some(INSURANCE),
+            double_down: Some(DoubleDown {
+                stages: 1 << 2,
+                multiplier: 2.0,
+            }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(any_then_hearts(), &deck(&FOUR_CARDS), options);
+        let doubled = tree
+            .descend("Any", Some(card("AS")))
+            .and_then(|tree| tree.descend("Double", None))
+            .unwrap();
+        // the fee is still 0.5 out of the doubled pot of 4
+        let insure = doubled.choice("Insure").unwrap();
+        assert_close(
+            insure.expected_value,
+            (4.0 - 0.5) * 4.0 / 3.0 + 2.0 / 3.0 * 1.5,
+        );
+    }
+
+    #[test]
+    fn approx_cmp_is_transitive() {
+        // each value is within 1e-9 of the next, but not of the one after it
+        let values = [0.0, 0.6e-9, 1.2e-9, -0.4e-9, 1.0, 1.0 + 0.5e-9];
+        for a in values {
+            for b in values {
+                for c in values {
+                    let (ab, bc, ac) = (approx_cmp(a, b), approx_cmp(b, c), approx_cmp(a, c));
+                    if ab == bc {
+                        assert_eq!(ac, ab, "{a} {b} {c}");
+                    }
+                }
+            }
+        }
+        assert_eq!(approx_cmp(-1e-12, 1e-12), Ordering::Equal);
+    }
+
+    #[test]
+    fn swap_draws_again_after_the_card_is_revealed() {
+        let options = SolveOptions {
+            card_swap: Some(CardSwap { stage: 1, fee: 0.5 }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(hearts(), &deck(&FOUR_CARDS), options);
+        // like a mulligan, a spade or club is swapped for half the pot, then 1 of the 3
+        // cards left wins
+        assert_close(ev(&tree), 0.25 * 4.0 + 0.75 * (0.5 * 4.0 / 3.0));
+        let revealed = tree.descend("Hearts", Some(card("AS"))).unwrap();
+        assert_eq!(revealed.optimal().unwrap().choice.name(), "Swap");
+        let kept = tree.descend("Hearts", Some(card("AH"))).unwrap();
+        assert_eq!(kept.optimal().unwrap().choice.name(), "Keep");
+    }
+
+    #[test]
+    fn evaluate_path_grades_each_step_and_reports_where_it_fails() {
+        let tree = DiscreteDecisionTree::solve_with(
+            any_then_hearts(),
+            &deck(&FOUR_CARDS),
+            SolveOptions::default(),
+        );
+        let (spade, heart) = (Some(card("AS")), Some(card("AH")));
+        let steps = tree
+            .evaluate_path(&[("any", spade), ("Hearts", heart)])
+            .unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_close(steps[0].expected_value, ev(&tree));
+        assert_close(steps[0].pot, 2.0);
+        // 1 of the 3 cards left after the ace of spades is a heart
+        assert_close(steps[1].expected_value, 8.0 / 3.0);
+        assert_close(steps[1].pot, 8.0);
+        assert!(steps.iter().all(|step| step.is_optimal));
+
+        let invalid = |path: &[(&str, Option<PlayingCard>)]| tree.evaluate_path(path).err();
+        assert_eq!(
+            invalid(&[("Hearts", spade)]),
+            Some(InvalidPathError::UnknownChoice { step: 0 })
+        );
+        assert_eq!(
+            invalid(&[("Any", spade), ("Hearts", spade)]),
+            Some(InvalidPathError::UnknownEvent { step: 1 })
+        );
+        assert_eq!(
+            invalid(&[("Any", spade), ("Hearts", heart), ("Hearts", None)]),
+            Some(InvalidPathError::GameOver { step: 2 })
+        );
+    }
+
+    #[test]
+    fn cursor_steps_through_the_tree_and_back() {
+        let tree = DiscreteDecisionTree::solve_with(
+            any_then_hearts(),
+            &deck(&FOUR_CARDS),
+            SolveOptions::default(),
+        );
+        let spade = Some(card("AS"));
+        let mut cursor = GameCursor::new(&tree);
+        let outcome = cursor.descend("Any", spade).unwrap();
+        let second = tree.descend("Any", spade).unwrap();
+        assert!(std::ptr::eq(cursor.tree(), second));
+        assert!(std::ptr::eq(outcome.next_decision().unwrap(), second));
+        assert_eq!(cursor.stage(), 2);
+        assert_eq!(cursor.events().collect::<Vec<_>>(), [card("AS")]);
+        // a card that was already dealt can't be dealt again
+        assert!(cursor.descend("Hearts", spade).is_none());
+
+        // the outcome that ends the game leaves the cursor at the last decision
+        let last = cursor.descend("Hearts", Some(card("AH"))).unwrap();
+        assert!(last.next_decision().is_none());
+        assert_close(last.value, 8.0);
+        assert_eq!(cursor.len(), 1);
+
+        assert!(cursor.back());
+        assert!(cursor.is_empty());
+        assert!(std::ptr::eq(cursor.tree(), &tree));
+        assert!(!cursor.back());
+    }
+
+    #[test]
+    fn leaf_paths_cover_every_deal_with_its_probability() {
+        let tree = DiscreteDecisionTree::solve_with(
+            any_then_hearts(),
+            &deck(&FOUR_CARDS),
+            SolveOptions::default(),
+        );
+        let paths = tree.leaf_paths().collect::<Vec<_>>();
+        // any of the 4 cards, then any of the 3 left
+        assert_eq!(paths.len(), 12);
+        let first = &paths[0];
+        let names = first.steps.iter().map(|(choice, _)| choice.choice.name());
+        assert_eq!(names.collect::<Vec<_>>(), ["Any", "Hearts"]);
+        assert_eq!(first.steps[0].1, Some(card("AH")));
+        assert_close(first.probability, 1.0 / 12.0);
+        // only one choice is offered at each decision, so every path is optimal
+        let total = paths.iter().map(|path| path.probability).sum::<f64>();
+        let value = paths
+            .iter()
+            .map(|path| path.probability * path.payout)
+            .sum::<f64>();
+        assert_close(total, 1.0);
+        assert_close(value, ev(&tree));
+    }
+
+    #[test]
+    fn double_down_is_taken_when_it_beats_cashing_out() {
+        // any card doubles the pot, then a heart multiplies it by `payout`, or it's
+        // cashed out
+        let game = |payout: f64| {
+            GameBuilder::new()
+                .choice("Any", 2.0, |_: &[PlayingCard]| true)
+                .stage()
+                .choice("Hearts", payout, |cards: &[PlayingCard]| {
+                    cards[0].suit() == 0
+                })
+                .with_cashout()
+                .build()
+        };
+        let options = SolveOptions {
+            double_down: Some(DoubleDown {
+                stages: 1 << 2,
+                multiplier: 2.0,
+            }),
+            ..Default::default()
+        };
+        let spade = Some(card("AS"));
+        // 1 of the 3 cards left after the ace of spades is a heart
+        let tree = DiscreteDecisionTree::solve_with(game(4.0), &deck(&FOUR_CARDS), options);
+        let second = tree.descend("Any", spade).unwrap();
+        assert_eq!(second.optimal().unwrap().choice.name(), "Double");
+        assert_close(second.optimal().unwrap().expected_value, 4.0 * 4.0 / 3.0);
+        // doubling down gives up cashing out
+        let doubled = second.descend("Double", None).unwrap();
+        assert_close(doubled.pot(), 4.0);
+        assert!(doubled.choice("Cashout").is_none());
+
+        // the doubled pot is worth 4 * 1.2 / 3, less than cashing out the pot of 2
+        let tree = DiscreteDecisionTree::solve_with(game(1.2), &deck(&FOUR_CARDS), options);
+        let second = tree.descend("Any", spade).unwrap();
+        assert_eq!(second.optimal().unwrap().choice.name(), "Cashout");
+        assert_close(second.choice("Double").unwrap().expected_value, 1.6);
+    }
+
+    #[test]
+    fn insurance_that_costs_more_than_it_refunds_is_only_taken_on_a_lost_cause() {
+        let options = SolveOptions {
+            insurance: Some(Insurance {
+                stage: 2,
+                fee: 1.0,
+                refund: 0.5,
+            }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(any_then_hearts(), &deck(&FOUR_CARDS), options);
+        let second = tree.descend("Any", Some(card("AS"))).unwrap();
+        assert_eq!(second.optimal().unwrap().choice.name(), "Hearts");
+        // the pot of 2 less the fee wins 4x on 1 of the 3 cards left, else 0.5 is refunded
+        assert_close(
+            second.choice("Insure").unwrap().expected_value,
+            (2.0 - 1.0) * 4.0 / 3.0 + 2.0 / 3.0 * 0.5,
+        );
+        // no heart is left after the ace of hearts, so only the refund is worth anything
+        let lost = tree.descend("Any", Some(card("AH"))).unwrap();
+        assert_eq!(lost.optimal().unwrap().choice.name(), "Insure");
+        assert_close(ev(&tree), 0.25 * 0.5 + 0.75 * 2.0 * 4.0 / 3.0);
+    }
+}
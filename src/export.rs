@@ -0,0 +1,277 @@
+use crate::PlayingCard;
+use crate::decision::solver::DiscreteDecisionTree;
+use crate::render;
+use ride_the_bus::strategy::{self, csv_field};
+use std::{collections::BTreeMap, fs::File, io, io::Write, path::Path};
+
+/// One row of the stage table: the optimal choice for a stage in a given state
+pub struct StageRow {
+    pub choice: String,
+    pub win_probability: f64,
+    pub expected_value: f64,
+}
+
+/// The optimal choice for each stage and state (see [`strategy::optimal_choices`]),
+/// along with its win probability and EV
+pub fn stage_strategy(
+    tree: &DiscreteDecisionTree,
+    state_of: impl Fn(&[PlayingCard]) -> String,
+) -> BTreeMap<(usize, String), StageRow> {
+    strategy::optimal_choices(tree, state_of)
+        .into_iter()
+        .map(|(key, optimal)| {
+            let row = StageRow {
+                choice: format!("{:?}", optimal.choice),
+                win_probability: optimal.win_probability(),
+                expected_value: optimal.expected_value,
+            };
+            (key, row)
+        })
+        .collect()
+}
+
+/// Writes the per-stage strategy table (see [`stage_strategy`]) as CSV to `path`
+///
+/// Returns the number of rows written
+pub fn write_stage_table(
+    tree: &DiscreteDecisionTree,
+    path: &Path,
+    state_of: impl Fn(&[PlayingCard]) -> String,
+) -> io::Result<usize> {
+    let rows = stage_strategy(tree, state_of);
+
+    let mut file = io::BufWriter::new(File::create(path)?);
+    writeln!(file, "stage,state,choice,win_probability,expected_value")?;
+    for ((stage, state), row) in &rows {
+        writeln!(
+            file,
+            "{},{},{},{:.06},{:.06}",
+            stage,
+            csv_field(state),
+            csv_field(&row.choice),
+            row.win_probability,
+            row.expected_value
+        )?;
+    }
+    file.flush()?;
+    Ok(rows.len())
+}
+
+/// Writes a row for every outcome of every choice of the decision as CSV to `path`,
+/// or of every decision under it too if `whole_tree`
+///
+/// `seen` is the cards seen before the decision, in the order they were dealt.
+/// Returns the number of rows written
+pub fn write_outcome_table(
+    tree: &DiscreteDecisionTree,
+    path: &Path,
+    seen: &[PlayingCard],
+    whole_tree: bool,
+) -> io::Result<usize> {
+    fn write_rows(
+        file: &mut impl Write,
+        tree: &DiscreteDecisionTree,
+        seen: &mut Vec<PlayingCard>,
+        whole_tree: bool,
+    ) -> io::Result<usize> {
+        let history = seen
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut rows = 0;
+        for choice in tree.iter() {
+            for (probability, outcome) in choice.iter_probabilities() {
+                let card = outcome.event.map(|c| c.to_string()).unwrap_or_default();
+                writeln!(
+                    file,
+                    "{},{},{},{},{:.06},{:.06}",
+                    seen.len() + 1,
+                    history,
+                    csv_field(choice.choice.name()),
+                    card,
+                    outcome.value,
+                    probability
+                )?;
+                rows += 1;
+            }
+        }
+        if !whole_tree {
+            return Ok(rows);
+        }
+        for outcome in tree.iter().flat_map(|choice| choice.iter()) {
+            if let Some(next) = outcome.next_decision() {
+                seen.extend(outcome.event);
+                rows += write_rows(file, next, seen, whole_tree)?;
+                if outcome.event.is_some() {
+                    seen.pop();
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    let mut file = io::BufWriter::new(File::create(path)?);
+    writeln!(file, "stage,history,choice,card,value,probability")?;
+    let rows = write_rows(&mut file, tree, &mut seen.to_vec(), whole_tree)?;
+    file.flush()?;
+    Ok(rows)
+}
+
+/// Writes the tree as a Graphviz DOT graph to `path`, `depth` decisions deep
+///
+/// Decisions and choices are nodes (optimal choices in bold), and the cards that
+/// resolve a choice are edges labeled with their EVs, leading to the next decision
+/// or to the final value. Returns the number of nodes written
+pub fn write_dot(tree: &DiscreteDecisionTree, path: &Path, depth: usize) -> io::Result<usize> {
+    let mut file = io::BufWriter::new(File::create(path)?);
+    writeln!(file, "digraph ride_the_bus {{")?;
+    writeln!(file, "    rankdir=LR;")?;
+    let mut nodes = 0;
+    write_dot_decision(&mut file, tree, depth, &mut nodes)?;
+    writeln!(file, "}}")?;
+    file.flush()?;
+    Ok(nodes)
+}
+
+/// Writes a decision node and everything under it, returning the id of its node
+fn write_dot_decision(
+    file: &mut impl Write,
+    tree: &DiscreteDecisionTree,
+    depth: usize,
+    nodes: &mut usize,
+) -> io::Result<usize> {
+    let decision = *nodes;
+    *nodes += 1;
+    writeln!(
+        file,
+        "    n{} [shape=box, label=\"pot {:.02}x\"];",
+        decision,
+        tree.pot()
+    )?;
+    if depth == 0 {
+        return Ok(decision);
+    }
+    for ranked in tree.ranked_choices() {
+        let choice = ranked.choice;
+        let node = *nodes;
+        *nodes += 1;
+        let (node_style, edge_style) = match ranked.is_optimal {
+            true => (", style=bold", " [style=bold]"),
+            false => ("", ""),
+        };
+        writeln!(
+            file,
+            "    n{} [label=\"{:?}\\n{}\"{}];",
+            node,
+            choice.choice,
+            render::ev(choice.expected_value),
+            node_style
+        )?;
+        writeln!(file, "    n{} -> n{}{};", decision, node, edge_style)?;
+        for outcome in choice.iter() {
+            let event = outcome
+                .event
+                .map_or("(no card)".to_owned(), |event| event.to_string());
+            let next = match outcome.next_decision() {
+                Some(next) => write_dot_decision(file, next, depth - 1, nodes)?,
+                None => {
+                    let leaf = *nodes;
+                    *nodes += 1;
+                    writeln!(
+                        file,
+                        "    n{} [shape=plaintext, label=\"{:.02}x\"];",
+                        leaf, outcome.value
+                    )?;
+                    leaf
+                }
+            };
+            writeln!(
+                file,
+                "    n{} -> n{} [label=\"{} = {}\"];",
+                node,
+                next,
+                event,
+                render::ev(outcome.value)
+            )?;
+        }
+    }
+    Ok(decision)
+}
+
+/// The value of a stage when playing optimally, averaged over every history that
+/// reaches it
+pub struct StageValue {
+    /// The probability of reaching the stage
+    pub reach_probability: f64,
+    /// The expected final pot once at the stage
+    pub expected_value: f64,
+    /// The expected final pot once the stage is survived (its card didn't lose
+    /// the pot), or `None` if the stage is never played
+    pub survived_value: Option<f64>,
+    /// The probability of losing the pot at the stage (not just once reached it)
+    pub bust_probability: f64,
+}
+
+/// The value of each stage that can be reached (index 0 is this decision) when
+/// playing optimally (see [`StageValue`])
+pub fn stage_values(tree: &DiscreteDecisionTree) -> Vec<StageValue> {
+    // probability weighted sums, turned into averages at the end
+    #[derive(Default)]
+    struct Sums {
+        reach: f64,
+        ev: f64,
+        survived: f64,
+        survived_ev: f64,
+        busted: f64,
+    }
+    fn walk(tree: &DiscreteDecisionTree, depth: usize, probability: f64, out: &mut Vec<Sums>) {
+        let Some(optimal) = tree.optimal() else {
+            return;
+        };
+        // the stage isn't over until a card is revealed (and kept, if it can be discarded
+        // with a mulligan), so count it where the choice leads
+        if !optimal.choice.ends_stage() {
+            for (outcome_probability, outcome) in optimal.iter_probabilities() {
+                if let Some(next) = outcome.next_decision() {
+                    walk(next, depth, probability * outcome_probability, out);
+                }
+            }
+            return;
+        }
+        if out.len() <= depth {
+            out.resize_with(depth + 1, Sums::default);
+        }
+        out[depth].reach += probability;
+        out[depth].ev += probability * optimal.expected_value;
+        if optimal.choice.is_cashout() {
+            return;
+        }
+
+        for (outcome_probability, outcome) in optimal.iter_probabilities() {
+            let probability = probability * outcome_probability;
+            match outcome.next_decision() {
+                Some(next) => walk(next, depth + 1, probability, out),
+                // a leaf that didn't lose the pot survived the stage, even if something
+                // was refunded or returned by a side bet when it did
+                None if outcome.is_lost() => {
+                    out[depth].busted += probability;
+                    continue;
+                }
+                None => {}
+            }
+            out[depth].survived += probability;
+            out[depth].survived_ev += probability * outcome.value;
+        }
+    }
+    let mut sums = Vec::new();
+    walk(tree, 0, 1.0, &mut sums);
+    sums.into_iter()
+        .map(|sums| StageValue {
+            reach_probability: sums.reach,
+            expected_value: sums.ev / sums.reach,
+            survived_value: (sums.survived > 0.0).then(|| sums.survived_ev / sums.survived),
+            bust_probability: sums.busted,
+        })
+        .collect()
+}
@@ -0,0 +1,85 @@
+use crate::PlayingCard;
+use ride_the_bus::card::{RANK_NAMES, SUIT_NAMES};
+
+/// The number of single character edits (insertions, deletions, substitutions, and
+/// swaps of adjacent characters) needed to turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    // d[i][j] is the distance between the first i chars of a and first j chars of b
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Finds the candidate closest to `word` (case insensitive), if it's close enough
+/// to be considered a typo
+///
+/// Short words must match more closely, so `11` won't be mistaken for `10`
+pub fn closest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    let max_distance = match word.chars().count() {
+        0..=2 => 0,
+        3..=4 => 1,
+        _ => 2,
+    };
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&word, &candidate.to_lowercase()), candidate))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Parses a card that was spelled out or misspelled, like `queen of hearts`,
+/// `quen of harts`, or `10 harts`
+pub fn parse_card(s: &str) -> Option<PlayingCard> {
+    let words = s
+        .split_whitespace()
+        .filter(|word| !word.eq_ignore_ascii_case("of"))
+        .collect::<Vec<_>>();
+    let [rank, suit] = words[..] else {
+        return None;
+    };
+
+    // ranks can be given as either the label on the card or its name
+    let rank = PlayingCard::deck_iter()
+        .find(|card| card.rank_label().eq_ignore_ascii_case(rank))
+        .map(|card| card.rank())
+        .or_else(|| {
+            let name = closest(rank, RANK_NAMES.iter().copied())?;
+            RANK_NAMES
+                .iter()
+                .position(|&n| n == name)
+                .map(|idx| idx as u8 + 2)
+        })?;
+    // suits can be given as either their letter or their name (singular or plural)
+    let suit = PlayingCard::deck_iter()
+        .find(|card| card.suit_label().eq_ignore_ascii_case(suit))
+        .map(|card| card.suit())
+        .or_else(|| {
+            let suit = suit.strip_suffix(['s', 'S']).unwrap_or(suit);
+            let name = closest(suit, SUIT_NAMES.iter().map(|n| &n[..n.len() - 1]))?;
+            SUIT_NAMES
+                .iter()
+                .position(|n| n.starts_with(name))
+                .map(|idx| idx as u8)
+        })?;
+
+    PlayingCard::deck_iter().find(|card| card.rank() == rank && card.suit() == suit)
+}
@@ -0,0 +1,251 @@
+use crate::config::{GameConfig, JokerRule, TieRule};
+use ride_the_bus::{DiscreteDecision, PlayingCard, decision::builder::GameBuilder};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::HashSet, fs, io, path::Path};
+use tracing::debug;
+
+/// A game of stages declared in a TOML file (see [`load`]), for house variants that
+/// add, drop, or change the stages of Ride The Bus
+///
+/// The deck and aces are still ruled by the [`GameConfig`], but its payouts and
+/// ties are replaced by the ones declared here
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GameFile {
+    pub stages: Vec<StageDef>,
+}
+/// A stage of the game, where a card is dealt for one of the picks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StageDef {
+    /// The pot, as a multiple of the bet, after winning the stage
+    pub pot: f64,
+    /// Whether cashing out is allowed instead of picking
+    #[serde(default = "cashout_allowed")]
+    pub cashout: bool,
+    pub picks: Vec<PickDef>,
+}
+fn cashout_allowed() -> bool {
+    true
+}
+/// A pick of a stage, which wins if the card dealt for it matches the predicate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PickDef {
+    /// The name of the choice, e.g. `Red`
+    pub name: String,
+    #[serde(flatten)]
+    pub predicate: Predicate,
+    /// How a tie is ruled for `latitude` and `range` picks
+    #[serde(default, skip_serializing_if = "is_standard")]
+    pub ties: TieRule,
+}
+fn is_standard(ties: &TieRule) -> bool {
+    *ties == TieRule::Standard
+}
+
+/// What the card dealt for a pick has to be for the pick to win
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Predicate {
+    /// The card has the color
+    Color(Color),
+    /// The card ranks higher or lower than the last card, where ties count as
+    /// Higher by default
+    Latitude(Latitude),
+    /// The card ranks inside or outside the last two cards, where ties count as
+    /// Inside by default
+    Range(Range),
+    /// The card has the suit
+    Suit(Suit),
+    /// The card has the rank (2-14)
+    Rank(u8),
+}
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Red,
+    Black,
+}
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Latitude {
+    Higher,
+    Lower,
+}
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Range {
+    Inside,
+    Outside,
+}
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Suit {
+    Hearts,
+    Diamonds,
+    Spades,
+    Clubs,
+}
+
+impl Predicate {
+    /// The number of cards the predicate is settled by, including the card dealt for it
+    fn cards(self) -> usize {
+        match self {
+            Self::Color(_) | Self::Suit(_) | Self::Rank(_) => 1,
+            Self::Latitude(_) => 2,
+            Self::Range(_) => 3,
+        }
+    }
+    /// Whether the pick wins given the history (backwards, see [`ride_the_bus::Choice::score`])
+    fn wins(self, cards: &[PlayingCard], ties: TieRule, config: &GameConfig) -> bool {
+        // jokers have no rank or suit, so they settle any pick they're part of
+        if cards[..self.cards()].iter().any(|card| card.is_joker()) {
+            return config.deck.joker == JokerRule::Win;
+        }
+        let wins_tie = |wins_standard| match ties {
+            TieRule::Standard => wins_standard,
+            TieRule::Win => true,
+            TieRule::Loss | TieRule::Push => false,
+        };
+        // the pick wins if it wins with the aces ranked any way the rules allow
+        let any_ranking = |wins: &dyn Fn(fn(PlayingCard) -> u8) -> bool| {
+            config.aces.rankings().iter().any(|&rank| wins(rank))
+        };
+        match self {
+            Self::Color(color) => cards[0].color() == color as u8,
+            Self::Suit(suit) => cards[0].suit() == suit as u8,
+            Self::Rank(rank) => cards[0].rank() == rank,
+            Self::Latitude(latitude) => any_ranking(&|rank| match (
+                latitude,
+                rank(cards[0]).cmp(&rank(cards[1])),
+            ) {
+                (_, Ordering::Equal) => wins_tie(latitude == Latitude::Higher),
+                (Latitude::Higher, ordering) => ordering == Ordering::Greater,
+                (Latitude::Lower, ordering) => ordering == Ordering::Less,
+            }),
+            Self::Range(range) => any_ranking(&|rank| {
+                let (card, c1, c2) = (rank(cards[0]), rank(cards[1]), rank(cards[2]));
+                if card == c1 || card == c2 {
+                    return wins_tie(range == Range::Inside);
+                }
+                let inside = (c1.min(c2)..=c1.max(c2)).contains(&card);
+                inside == (range == Range::Inside)
+            }),
+        }
+    }
+}
+
+impl GameFile {
+    /// The first decision of the game, which every other decision follows from,
+    /// under the deck and ace rules of `config`
+    pub fn first_decision(&self, config: GameConfig) -> DiscreteDecision {
+        debug!(
+            stages = self.stages.len(),
+            picks = self
+                .stages
+                .iter()
+                .map(|stage| stage.picks.len())
+                .sum::<usize>(),
+            "building game file"
+        );
+        let mut builder = GameBuilder::new();
+        let mut pot = 1.0;
+        for stage in &self.stages {
+            builder = builder.stage();
+            for pick in &stage.picks {
+                let (predicate, ties) = (pick.predicate, pick.ties);
+                builder = builder.choice(&pick.name, stage.pot / pot, move |cards| {
+                    predicate.wins(cards, ties, &config)
+                });
+            }
+            if stage.cashout {
+                builder = builder.with_cashout();
+            }
+            pot = stage.pot;
+        }
+        builder.build()
+    }
+    /// Why the game can't be solved, if it can't
+    fn validate(&self) -> Result<(), String> {
+        if self.stages.is_empty() {
+            return Err("the game needs at least one stage".to_owned());
+        }
+        for (i, stage) in self.stages.iter().enumerate() {
+            let n = i + 1;
+            if stage.pot <= 0.0 {
+                return Err(format!("stage {} needs a positive pot", n));
+            }
+            if stage.picks.is_empty() {
+                return Err(format!("stage {} needs at least one pick", n));
+            }
+            let mut names = HashSet::new();
+            for pick in &stage.picks {
+                // every stage deals one card, so only the stages before it have cards to compare against
+                if pick.predicate.cards() > n {
+                    return Err(format!(
+                        "'{}' in stage {} compares against cards that haven't been dealt",
+                        pick.name, n
+                    ));
+                }
+                if let Predicate::Rank(rank) = pick.predicate
+                    && !(2..=14).contains(&rank)
+                {
+                    return Err(format!("'{}' has an invalid rank {}", pick.name, rank));
+                }
+                if pick.ties == TieRule::Push {
+                    return Err(format!("'{}' can't push ties in a game file", pick.name));
+                }
+                if pick.ties != TieRule::Standard && pick.predicate.cards() == 1 {
+                    return Err(format!("'{}' can't tie, so it can't have ties", pick.name));
+                }
+                if pick.name.eq_ignore_ascii_case("cashout") {
+                    return Err("'Cashout' is the name of cashing out".to_owned());
+                }
+                if !names.insert(pick.name.to_lowercase()) {
+                    return Err(format!("stage {} has more than one '{}'", n, pick.name));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads the game from the TOML file at `path`, e.g. Ride The Bus with a last
+/// stage of guessing the rank instead of the suit
+///
+/// Games can have any number of stages, but every stage multiplies the size of the
+/// solved tree by its picks and the cards left, so games longer than Ride The Bus
+/// need fewer picks or a smaller deck (e.g. `--deck piquet`) to fit in memory
+///
+/// ```toml
+/// [[stages]]
+/// pot = 2
+/// picks = [
+///     { name = "Red", color = "red" },
+///     { name = "Black", color = "black" },
+/// ]
+///
+/// [[stages]]
+/// pot = 3
+/// picks = [
+///     { name = "Higher", latitude = "higher" },
+///     { name = "Lower", latitude = "lower", ties = "win" },
+/// ]
+///
+/// [[stages]]
+/// pot = 4
+/// picks = [
+///     { name = "Inside", range = "inside" },
+///     { name = "Outside", range = "outside" },
+/// ]
+///
+/// [[stages]]
+/// pot = 40
+/// cashout = false
+/// picks = [{ name = "Ace", rank = 14 }, { name = "King", rank = 13 }]
+/// ```
+pub fn load(path: &Path) -> io::Result<GameFile> {
+    let game: GameFile = toml::from_str(&fs::read_to_string(path)?).map_err(io::Error::other)?;
+    game.validate().map_err(io::Error::other)?;
+    Ok(game)
+}
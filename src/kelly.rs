@@ -0,0 +1,38 @@
+/// The fraction of a bankroll to bet that maximizes its long run growth (the Kelly
+/// criterion), given the `(pot, probability)` distribution of the final pot as a
+/// multiple of the bet
+///
+/// Returns `0.0` if the bet isn't favorable, i.e. it has an expected value of at most 1
+pub fn fraction(distribution: &[(f64, f64)]) -> f64 {
+    // the derivative of the expected log growth for betting fraction f, which is
+    // decreasing in f since the growth is concave
+    let growth_slope = |f: f64| {
+        distribution
+            .iter()
+            .map(|&(pot, probability)| probability * (pot - 1.0) / (1.0 + f * (pot - 1.0)))
+            .fold(0.0, |sum, slope| sum + slope)
+    };
+    if growth_slope(0.0) <= 0.0 {
+        return 0.0;
+    }
+
+    // a bet that can't lose is worth the whole bankroll
+    let max_loss = distribution
+        .iter()
+        .map(|&(pot, _)| 1.0 - pot)
+        .fold(0.0, f64::max);
+    if max_loss <= 0.0 {
+        return 1.0;
+    }
+    // find where the slope crosses zero by bisection, below the fraction that could
+    // lose the whole bankroll
+    let (mut low, mut high) = (0.0, f64::min(1.0 / max_loss, 1.0));
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        match growth_slope(mid) > 0.0 {
+            true => low = mid,
+            false => high = mid,
+        }
+    }
+    low
+}
@@ -0,0 +1,338 @@
+use std::{str::FromStr, sync::OnceLock};
+
+/// A language the help, tutorial, and prompts are translated to
+#[derive(Clone, Copy, Default)]
+pub enum Lang {
+    #[default]
+    English,
+    Spanish,
+}
+impl FromStr for Lang {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "en" | "english" => Ok(Self::English),
+            "es" | "spanish" | "español" => Ok(Self::Spanish),
+            _ => Err(()),
+        }
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+/// Sets the language of the text, which can only be done once
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+/// The text in the language set with [`set_lang`] (English by default)
+pub fn text() -> &'static Catalog {
+    match LANG.get().copied().unwrap_or_default() {
+        Lang::English => &ENGLISH,
+        Lang::Spanish => &SPANISH,
+    }
+}
+/// Fills the `{}` in a piece of text with the `args`, in order
+pub fn fill(text: &str, args: &[&str]) -> String {
+    args.iter()
+        .fold(text.to_owned(), |text, arg| text.replacen("{}", arg, 1))
+}
+
+/// How each command is typed, which is the same in every language
+///
+/// Each language describes the commands in this order (see [`Catalog::commands`])
+pub const COMMAND_USAGES: [&str; 49] = [
+    "help",
+    "exit",
+    "list",
+    "list {choice_name|'optimal'}",
+    "list ... --all --sort {ev|card} --suit {suit} --rank {rank}",
+    "list ... --format {text|markdown|json}",
+    "tree {depth?}",
+    "payouts {choice_name?}",
+    "why {choice_name?}",
+    "stages",
+    "bust",
+    "strategy",
+    "strategy {file}",
+    "report {file}",
+    "export stages {file}",
+    "export charts {directory}",
+    "export csv {file} {--all?}",
+    "export dot {file} {depth?}",
+    "compare {strategy} {strategy}",
+    "compare",
+    "record {file}",
+    "record stop",
+    "replay {file}",
+    "copy",
+    "kelly {bankroll}",
+    "bankroll {amount?}",
+    "ruin {bankroll} {bet} {hands}",
+    "autoplay {hands}",
+    "verify {hands?}",
+    "deal",
+    "pick {choice_name}",
+    "game new {bet?}",
+    "game list",
+    "game switch {id}",
+    "practice {bet?}",
+    "practice stop",
+    "bet {amount}",
+    "reset",
+    "back",
+    "forward",
+    "history",
+    "stats",
+    "career",
+    "luck",
+    "remove {cards}",
+    "table {seat} {cards}",
+    "table list",
+    "table clear",
+    "{card}",
+];
+/// The strategies given as examples in the help, which are the same in every language
+pub const STRATEGY_EXAMPLES: [&str; 5] = [
+    "ev",
+    "target=4",
+    "target=4,bust",
+    "utility=1:20",
+    "ev,variance",
+];
+/// The cards given as examples in the help, which are the same in every language
+pub const CARD_EXAMPLES: [&str; 4] = ["2H", "10C", "QD", "AS"];
+
+/// The help, tutorial, and prompts in one language
+pub struct Catalog {
+    /// The headings of the sections of the help, in order
+    pub headings: [&'static str; 5],
+    /// What each command does, in the order of [`COMMAND_USAGES`]
+    pub commands: [&'static str; COMMAND_USAGES.len()],
+    /// What else to know about entering commands
+    pub command_notes: [&'static str; 3],
+    /// What a strategy is
+    pub strategy_format: &'static str,
+    /// What each strategy does, in the order of [`STRATEGY_EXAMPLES`]
+    pub strategies: [&'static str; STRATEGY_EXAMPLES.len()],
+    /// How cards are typed
+    pub card_format: &'static str,
+    /// The card of each example, in the order of [`CARD_EXAMPLES`]
+    pub cards: [&'static str; CARD_EXAMPLES.len()],
+    /// The word introducing examples
+    pub examples: &'static str,
+    /// The steps of playing with the tool
+    pub tutorial: [&'static str; 5],
+    /// The labels of the stage, pot, and EV in the prompt, and what it says without a cashout
+    pub status: [&'static str; 4],
+    pub invalid_command: &'static str,
+    /// Asks whether a correction (the `{}`) was meant
+    pub did_you_mean: &'static str,
+    /// The choices given after a yes or no question
+    pub yes_no: &'static str,
+    /// The answers taken as yes
+    pub yes: &'static [&'static str],
+    /// Points out the choice interpreted from a card (the `{}`)
+    pub you_chose: &'static str,
+    pub invalid_card: &'static str,
+    /// Announces the optimal choices (the first `{}`) and their EV (the second)
+    /// for screen readers
+    pub optimal_sentence: &'static str,
+    /// Joins tied choices
+    pub or: &'static str,
+}
+
+static ENGLISH: Catalog = Catalog {
+    headings: [
+        "Commands",
+        "Options",
+        "Strategy Format",
+        "Card Format",
+        "Tutorial",
+    ],
+    commands: [
+        "This command",
+        "Quit the program",
+        "Prints the choices and the expected values",
+        "Prints the random events associated with a choice",
+        "Also lists losing cards, sorts by EV, or only lists a suit or ranks (e.g. '--rank >=10')",
+        "Lists as text, as markdown tables to paste elsewhere, or as JSON",
+        "Prints the choices, their cards, and the EVs as a tree, this many decisions deep (1 by default)",
+        "Prints the probability of each final pot after a choice (optimal by default)",
+        "Explains the EV of a choice from the cards it wins on and what winning is worth (optimal by default)",
+        "Prints the expected value of reaching each stage, and of surviving it, under optimal play",
+        "Prints the chance of losing the pot at this stage, at each stage after it, and by the end, under optimal play",
+        "Prints the optimal choice for every stage and state",
+        "Writes the optimal choice for every stage and state as JSON (for .json files) or CSV",
+        "Writes a standalone HTML report of the stages, charts, payouts, and optimal choices",
+        "Writes the optimal choice, win probability, and EV for every stage and state as CSV",
+        "Draws charts of the EV by stage and the payout distribution as SVG files",
+        "Writes the value and probability of every card for each choice as CSV, for every later decision too with --all",
+        "Writes the tree from this decision as a Graphviz graph, this many decisions deep (1 by default)",
+        "Solves the game for two strategies and compares them side by side",
+        "Compares the EV and payouts of optimal play against simple policies, like never cashing out",
+        "Starts recording the session to a file: the commands you enter, the cards dealt, the choices and EVs shown, and how each hand finishes",
+        "Stops recording",
+        "Runs the commands recorded in a file, as if you entered them, which plays the session out the same way again",
+        "Copies the optimal choice, its EV, and its win probability to the clipboard",
+        "Prints how much of your bankroll to bet on a hand to grow it fastest (Kelly criterion)",
+        "Prints the balance of your bankroll, kept across runs and updated by each hand, with bets sized for it (sets it first if given)",
+        "Simulates the chance of going broke within this many hands, and before doubling up",
+        "Plays this many hands optimally, dealing the cards itself, and prints each one",
+        "Checks the solved EV against the mean pot of this many simulated hands (100000 by default)",
+        "Deals a random card from the rest of the deck, as if the dealer dealt it",
+        "Take a choice that doesn't reveal a card, or cash out, or pick the choice the next card is dealt for (instead of the one it wins)",
+        "Starts tracking another game, with its own hand, bet (1 by default), and stats",
+        "Prints every game, with its bet, hands finished, net winnings, and hand in progress",
+        "Switches to another game",
+        "Starts a practice game, where you pick choices without seeing their EVs and cards are dealt for you",
+        "Stops practicing, printing your results against the optimal EV",
+        "Sets the amount bet on each hand of this game, showing EVs and payouts in amounts too",
+        "Start over (new hand)",
+        "Go back to previous choice (useful if you input the wrong card)",
+        "Take the choice you went back from again",
+        "Prints the cards entered this hand, the choice each was taken for, and the pot after each",
+        "Prints the decisions, outcomes, depth, and memory of the solved tree and of this decision's subtree",
+        "Prints the hands played across runs, the return against the expected return, the biggest win, and the longest losing streak (with --career)",
+        "Prints how the cards dealt this hand compare to the EV at each decision, and the running luck of every hand finished (also printed after each hand)",
+        "Takes cards known to be gone (e.g. burned, or dealt to other players) out of the deck, then solves again and starts over",
+        "Takes cards dealt to another player (seat 2 and up, you're seat 1) out of the deck, then solves your hand again for the rest of the round",
+        "Prints the cards dealt to each seat this round",
+        "Starts a new round at the table, putting the other seats' cards back in the deck",
+        "Input a card (your choice can be interpreted)",
+    ],
+    command_notes: [
+        "Typos in commands and cards are corrected after asking you to confirm",
+        "Up and down bring back earlier commands, and tab completes commands, choices, and cards",
+        "Output is colored in a terminal, unless the NO_COLOR environment variable is set",
+    ],
+    strategy_format: "Strategies are what the solver maximizes, optionally followed by how it breaks ties",
+    strategies: [
+        "Maximize expected value",
+        "Maximize the chance of finishing with at least 4x",
+        "Same as above, breaking ties by the lowest bust probability",
+        "Maximize the log utility of a bankroll of 20 bets",
+        "Maximize expected value, breaking ties by the lowest variance",
+    ],
+    card_format: "Card formats are pretty simple. It's the rank (number or letter) of the card, plus the suit, case insensitive",
+    cards: [
+        "2 of hearts",
+        "10 of clubs",
+        "Queen of diamonds",
+        "Ace of spades",
+    ],
+    examples: "Examples:",
+    tutorial: [
+        "This tool is intended to be used while playing Ride The Bus (a fictional casino game) in Schedule I",
+        "1. You are shown a series of Choices and their Expected Values. In Schedule I, choose the option with the highest indicated expected value here",
+        "2. Once your option is selected, a dealer then places another card in front of you. Input the shown card here using the CLI",
+        "3. After inputting your card, your choice is automatically interpreted and a new series of choices is shown",
+        "4. Repeat Step 1-3 until you either lose or cashout, then restart with '? reset'",
+    ],
+    status: ["stage", "pot", "EV", "no cashout"],
+    invalid_command: "invalid command",
+    did_you_mean: "did you mean '{}'?",
+    yes_no: "[y/n]",
+    yes: &["y", "yes"],
+    you_chose: "So you chose {}",
+    invalid_card: "INVALID CARD PROVIDED",
+    optimal_sentence: "The optimal choice is {}, with an expected value of {}.",
+    or: "or",
+};
+
+static SPANISH: Catalog = Catalog {
+    headings: [
+        "Comandos",
+        "Opciones",
+        "Formato de estrategia",
+        "Formato de cartas",
+        "Tutorial",
+    ],
+    commands: [
+        "Este comando",
+        "Sale del programa",
+        "Muestra las opciones y sus valores esperados",
+        "Muestra los eventos aleatorios de una opción",
+        "También muestra las cartas perdedoras, ordena por EV, o solo muestra un palo o unos rangos (p. ej. '--rank >=10')",
+        "Muestra como texto, como tablas markdown para pegar en otro sitio, o como JSON",
+        "Muestra las opciones, sus cartas y los EV como un árbol, con esta profundidad de decisiones (1 por defecto)",
+        "Muestra la probabilidad de cada bote final tras una opción (la óptima por defecto)",
+        "Explica el EV de una opción a partir de las cartas con las que gana y lo que vale ganar (la óptima por defecto)",
+        "Muestra el valor esperado de llegar a cada etapa, y de superarla, jugando de forma óptima",
+        "Muestra la probabilidad de perder el bote en esta etapa, en cada etapa siguiente y al final, jugando de forma óptima",
+        "Muestra la opción óptima para cada etapa y estado",
+        "Escribe la opción óptima para cada etapa y estado como JSON (para archivos .json) o CSV",
+        "Escribe un informe HTML independiente de las etapas, gráficos, pagos y opciones óptimas",
+        "Escribe la opción óptima, la probabilidad de ganar y el EV de cada etapa y estado como CSV",
+        "Dibuja gráficos del EV por etapa y de la distribución de pagos como archivos SVG",
+        "Escribe el valor y la probabilidad de cada carta para cada opción como CSV, y de cada decisión posterior con --all",
+        "Escribe el árbol desde esta decisión como un grafo de Graphviz, con esta profundidad de decisiones (1 por defecto)",
+        "Resuelve el juego con dos estrategias y las compara lado a lado",
+        "Compara el EV y los pagos del juego óptimo con políticas simples, como no retirarse nunca",
+        "Empieza a grabar la sesión en un archivo: los comandos que introduces, las cartas repartidas, las opciones y EVs mostrados, y cómo termina cada mano",
+        "Deja de grabar",
+        "Ejecuta los comandos grabados en un archivo, como si los introdujeras tú, lo que juega la sesión de la misma forma otra vez",
+        "Copia al portapapeles la opción óptima, su EV y su probabilidad de ganar",
+        "Muestra cuánto de tu banca apostar en una mano para hacerla crecer lo más rápido posible (criterio de Kelly)",
+        "Muestra el saldo de tu banca, guardado entre ejecuciones y actualizado con cada mano, con apuestas a su medida (la fija antes si se indica)",
+        "Simula la probabilidad de arruinarte en este número de manos, y antes de duplicar la banca",
+        "Juega este número de manos de forma óptima, repartiendo las cartas, y muestra cada una",
+        "Comprueba el EV resuelto con el bote medio de este número de manos simuladas (100000 por defecto)",
+        "Reparte una carta aleatoria del resto de la baraja, como si la repartiera el crupier",
+        "Toma una opción que no revela una carta, o retírate, o elige la opción para la que se reparte la siguiente carta (en vez de la que gana)",
+        "Empieza a seguir otra partida, con su propia mano, apuesta (1 por defecto) y estadísticas",
+        "Muestra cada partida, con su apuesta, manos terminadas, ganancias netas y mano en curso",
+        "Cambia a otra partida",
+        "Empieza una partida de práctica, en la que eliges sin ver los EV y las cartas se reparten solas",
+        "Deja de practicar, mostrando tus resultados frente al EV óptimo",
+        "Fija la cantidad apostada en cada mano de esta partida, mostrando también los EV y los pagos en cantidades",
+        "Empieza de nuevo (nueva mano)",
+        "Vuelve a la opción anterior (útil si introdujiste una carta equivocada)",
+        "Vuelve a tomar la opción de la que retrocediste",
+        "Muestra las cartas introducidas en esta mano, la opción de cada una y el bote tras cada una",
+        "Muestra las decisiones, resultados, profundidad y memoria del árbol resuelto y del subárbol de esta decisión",
+        "Muestra las manos jugadas entre ejecuciones, el retorno frente al retorno esperado, la mayor ganancia y la racha de derrotas más larga (con --career)",
+        "Muestra cómo se comparan las cartas repartidas en esta mano con el EV de cada decisión, y la suerte acumulada de cada mano terminada (también se muestra tras cada mano)",
+        "Saca de la baraja cartas que ya no están (p. ej. quemadas o repartidas a otros jugadores), luego resuelve de nuevo y empieza de cero",
+        "Saca de la baraja las cartas repartidas a otro jugador (asiento 2 en adelante, tú eres el asiento 1), luego resuelve tu mano de nuevo para el resto de la ronda",
+        "Muestra las cartas repartidas a cada asiento en esta ronda",
+        "Empieza una nueva ronda en la mesa, devolviendo a la baraja las cartas de los otros asientos",
+        "Introduce una carta (tu opción se puede interpretar)",
+    ],
+    command_notes: [
+        "Las erratas en comandos y cartas se corrigen tras pedirte confirmación",
+        "Arriba y abajo recuperan comandos anteriores, y el tabulador completa comandos, opciones y cartas",
+        "La salida tiene color en una terminal, salvo que la variable de entorno NO_COLOR esté definida",
+    ],
+    strategy_format: "Las estrategias son lo que maximiza el solucionador, opcionalmente seguido de cómo desempata",
+    strategies: [
+        "Maximiza el valor esperado",
+        "Maximiza la probabilidad de terminar con al menos 4x",
+        "Igual que arriba, desempatando por la menor probabilidad de perder",
+        "Maximiza la utilidad logarítmica de una banca de 20 apuestas",
+        "Maximiza el valor esperado, desempatando por la menor varianza",
+    ],
+    card_format: "El formato de las cartas es sencillo: el rango (número o letra) de la carta, más el palo en inglés (H, D, S o C), sin distinguir mayúsculas",
+    cards: [
+        "2 de corazones",
+        "10 de tréboles",
+        "Reina de diamantes",
+        "As de picas",
+    ],
+    examples: "Ejemplos:",
+    tutorial: [
+        "Esta herramienta está pensada para usarse mientras juegas a Ride The Bus (un juego de casino ficticio) en Schedule I",
+        "1. Se te muestra una serie de opciones y sus valores esperados. En Schedule I, elige la opción con el mayor valor esperado indicado aquí",
+        "2. Una vez elegida tu opción, el crupier coloca otra carta delante de ti. Introduce aquí la carta mostrada usando la CLI",
+        "3. Tras introducir tu carta, tu opción se interpreta automáticamente y se muestra una nueva serie de opciones",
+        "4. Repite los pasos 1-3 hasta que pierdas o te retires, y luego empieza de nuevo con '? reset'",
+    ],
+    status: ["etapa", "bote", "VE", "sin retirada"],
+    invalid_command: "comando no válido",
+    did_you_mean: "¿quisiste decir '{}'?",
+    yes_no: "[s/n]",
+    yes: &["s", "si", "sí", "y", "yes"],
+    you_chose: "Así que elegiste {}",
+    invalid_card: "CARTA NO VÁLIDA",
+    optimal_sentence: "La opción óptima es {}, con un valor esperado de {}.",
+    or: "o",
+};
@@ -0,0 +1,12 @@
+//! Solver for Ride The Bus, and other games made of a chain of discrete decisions
+//! that are each resolved by drawing a card
+//!
+//! Define a game with types implementing [`Choice`], then solve it from its first
+//! [`DiscreteDecision`] with [`DiscreteDecisionTree::solve`]
+
+pub mod card;
+pub mod decision;
+pub mod strategy;
+
+pub use card::PlayingCard;
+pub use decision::{Choice, DiscreteDecision, solver::DiscreteDecisionTree};
@@ -0,0 +1,26 @@
+use std::{fs::File, io, path::Path, sync::Mutex};
+use tracing::level_filters::LevelFilter;
+
+/// Starts logging what the solver and prompt do at `level` and above, to the
+/// `file` if given or else stderr (so it isn't mixed into output read by scripts)
+///
+/// Nothing is logged unless a level or file is given, and a file alone logs at
+/// the info level
+pub fn init(level: Option<LevelFilter>, file: Option<&Path>) -> io::Result<()> {
+    let level = match (level, file) {
+        (Some(level), _) => level,
+        (None, Some(_)) => LevelFilter::INFO,
+        (None, None) => return Ok(()),
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false);
+    match file {
+        Some(path) => builder
+            .with_writer(Mutex::new(File::create(path)?))
+            .with_ansi(false)
+            .init(),
+        None => builder.with_writer(io::stderr).init(),
+    }
+    Ok(())
+}
@@ -1,28 +1,133 @@
-mod card;
-mod decision;
+mod autoplay;
+mod bankroll;
+mod cache;
+mod career;
+mod chart;
+mod clipboard;
+mod compare;
+#[cfg(not(target_os = "wasi"))]
+mod completion;
+mod config;
+mod export;
+mod fuzzy;
+mod game_file;
+mod kelly;
+mod lang;
+mod logging;
+mod render;
+mod report;
+mod review;
+mod ruin;
+mod session;
+#[cfg(not(target_os = "wasi"))]
+mod tui;
 
-use card::PlayingCard;
-use decision::{Choice, DiscreteDecision, solver::DiscreteDecisionTree};
-use std::{io, str::FromStr, time::Instant};
+use clap::{CommandFactory, Parser, Subcommand, error::ErrorKind};
+#[cfg(not(target_os = "wasi"))]
+use completion::CommandCompleter;
+use config::{
+    Aces, DeckPreset, DoubleDownConfig, GameConfig, InsuranceConfig, MulliganConfig, Payouts, Ties,
+};
+use game_file::GameFile;
+use lang::Lang;
+use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+use ride_the_bus::{
+    Choice, DiscreteDecision, DiscreteDecisionTree, PlayingCard, decision,
+    decision::cursor::GameCursor,
+    decision::event::{DeckSpec, EventSource},
+    decision::solver::{
+        CardSwap, ChoiceEval, Criterion, Insurance, Jackpot, Objective, RandomEventOutcome,
+        RankedChoice, SideBet, SideBetTarget, SolveOptions, TieBreak, Utility,
+    },
+    strategy::StrategyTable,
+};
+#[cfg(not(target_os = "wasi"))]
+use rustyline::{Editor, error::ReadlineError, history::DefaultHistory};
+use session::Session;
+use std::io::IsTerminal;
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    fmt::{Debug, Display},
+    fs::File,
+    io::{self, BufRead, BufWriter, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, info, level_filters::LevelFilter, warn};
+
+/// A pick for one of the stages, paid by the [`GameConfig`] it was offered with
+///
+/// Formats as the pick alone (e.g. `Red`), since that's the name of the choice
+struct Ruled<P> {
+    pick: P,
+    config: GameConfig,
+}
+impl<P: Debug> Debug for Ruled<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.pick.fmt(f)
+    }
+}
+/// The picks of a stage of Ride The Bus, see [`Choice`]
+trait Pick: Debug + 'static {
+    /// The number of cards the pick is settled by, including the card dealt for it
+    const CARDS: usize;
+    /// The score for winning the pick, which takes the pot from the payout of the
+    /// previous stage to the payout of this one (see [`Payouts`])
+    fn payout(payouts: &Payouts) -> f64;
+    /// Scores the pick like [`Choice::score`], under the rules of the config
+    fn score(&self, cards: &[PlayingCard], config: &GameConfig) -> f64;
+    /// The decision of the next stage, like [`Choice::next_decision`]
+    fn next_decision(config: GameConfig) -> Option<DiscreteDecision>;
+}
+impl<P: Pick> Choice for Ruled<P> {
+    fn score(&self, cards: &[PlayingCard]) -> f64 {
+        // jokers have no rank or suit, so they settle any pick they're part of
+        if cards[..P::CARDS].iter().any(|card| card.is_joker()) {
+            return self
+                .config
+                .deck
+                .joker
+                .score(P::payout(&self.config.payouts));
+        }
+        self.pick.score(cards, &self.config)
+    }
+    fn next_decision(&self) -> Option<DiscreteDecision> {
+        P::next_decision(self.config)
+    }
+}
+/// A decision between the picks of a stage, or cashing out
+fn stage_decision<P: Pick>(
+    picks: impl IntoIterator<Item = P>,
+    config: GameConfig,
+) -> DiscreteDecision {
+    DiscreteDecision::new_with_cashout(picks.into_iter().map(|pick| Ruled { pick, config }))
+}
 
 #[derive(Debug)]
 enum PickColor {
     Red,
     Black,
 }
-impl Choice for PickColor {
-    fn score(&self, cards: &[PlayingCard]) -> f64 {
+impl Pick for PickColor {
+    const CARDS: usize = 1;
+    fn payout(payouts: &Payouts) -> f64 {
+        payouts.color
+    }
+    fn score(&self, cards: &[PlayingCard], config: &GameConfig) -> f64 {
         match (self, cards[0].color()) {
-            (Self::Red, 0) | (Self::Black, 1) => 2.0, // 1x -> 2x
+            (Self::Red, 0) | (Self::Black, 1) => Self::payout(&config.payouts),
             (_, 0..=1) => 0.0,
             _ => unreachable!(),
         }
     }
-    fn next_decision(&self) -> Option<DiscreteDecision> {
-        Some(DiscreteDecision::new_with_cashout([
-            PickLatitude::Higher,
-            PickLatitude::Lower,
-        ]))
+    fn next_decision(config: GameConfig) -> Option<DiscreteDecision> {
+        Some(stage_decision(
+            [PickLatitude::Higher, PickLatitude::Lower],
+            config,
+        ))
     }
 }
 #[derive(Debug)]
@@ -30,18 +135,35 @@ enum PickLatitude {
     Higher,
     Lower,
 }
-impl Choice for PickLatitude {
-    fn score(&self, cards: &[PlayingCard]) -> f64 {
-        match (self, cards[0].rank() >= cards[1].rank()) {
-            (Self::Higher, true) | (Self::Lower, false) => 3.0 / 2.0, // 2x -> 3x
-            (Self::Higher, false) | (Self::Lower, true) => 0.0,
-        }
+impl Pick for PickLatitude {
+    const CARDS: usize = 2;
+    fn payout(payouts: &Payouts) -> f64 {
+        payouts.latitude / payouts.color
     }
-    fn next_decision(&self) -> Option<DiscreteDecision> {
-        Some(DiscreteDecision::new_with_cashout([
-            PickContained::Inside,
-            PickContained::Outside,
-        ]))
+    fn score(&self, cards: &[PlayingCard], config: &GameConfig) -> f64 {
+        let payout = Self::payout(&config.payouts);
+        let score = |rank: fn(PlayingCard) -> u8| match (self, rank(cards[0]).cmp(&rank(cards[1])))
+        {
+            (_, Ordering::Equal) => config
+                .ties
+                .latitude
+                .score(payout, matches!(self, Self::Higher)),
+            (Self::Higher, Ordering::Greater) | (Self::Lower, Ordering::Less) => payout,
+            (Self::Higher, Ordering::Less) | (Self::Lower, Ordering::Greater) => 0.0,
+        };
+        // the pick wins if it wins with the aces ranked any way the rules allow
+        config
+            .aces
+            .rankings()
+            .iter()
+            .map(|&rank| score(rank))
+            .fold(0.0, f64::max)
+    }
+    fn next_decision(config: GameConfig) -> Option<DiscreteDecision> {
+        Some(stage_decision(
+            [PickContained::Inside, PickContained::Outside],
+            config,
+        ))
     }
 }
 #[derive(Debug)]
@@ -49,23 +171,47 @@ enum PickContained {
     Inside,
     Outside,
 }
-impl Choice for PickContained {
-    fn score(&self, cards: &[PlayingCard]) -> f64 {
-        let c1 = cards[1].rank(); // last card seen
-        let c2 = cards[2].rank(); // 2nd last card seen
-        let bounds = u8::min(c1, c2)..=u8::max(c1, c2);
-        match (self, bounds.contains(&cards[0].rank())) {
-            (Self::Inside, true) | (Self::Outside, false) => 4.0 / 3.0, // 3x -> 4x
-            (Self::Inside, false) | (Self::Outside, true) => 0.0,
-        }
+impl Pick for PickContained {
+    const CARDS: usize = 3;
+    fn payout(payouts: &Payouts) -> f64 {
+        payouts.contained / payouts.latitude
     }
-    fn next_decision(&self) -> Option<DiscreteDecision> {
-        Some(DiscreteDecision::new_with_cashout([
-            PickSuit::Hearts,
-            PickSuit::Diamonds,
-            PickSuit::Spades,
-            PickSuit::Clubs,
-        ]))
+    fn score(&self, cards: &[PlayingCard], config: &GameConfig) -> f64 {
+        let payout = Self::payout(&config.payouts);
+        let score = |rank: fn(PlayingCard) -> u8| {
+            let c1 = rank(cards[1]); // last card seen
+            let c2 = rank(cards[2]); // 2nd last card seen
+            let card = rank(cards[0]);
+            if card == c1 || card == c2 {
+                return config
+                    .ties
+                    .contained
+                    .score(payout, matches!(self, Self::Inside));
+            }
+            let bounds = u8::min(c1, c2)..=u8::max(c1, c2);
+            match (self, bounds.contains(&card)) {
+                (Self::Inside, true) | (Self::Outside, false) => payout,
+                (Self::Inside, false) | (Self::Outside, true) => 0.0,
+            }
+        };
+        // the pick wins if it wins with the aces ranked any way the rules allow
+        config
+            .aces
+            .rankings()
+            .iter()
+            .map(|&rank| score(rank))
+            .fold(0.0, f64::max)
+    }
+    fn next_decision(config: GameConfig) -> Option<DiscreteDecision> {
+        Some(stage_decision(
+            [
+                PickSuit::Hearts,
+                PickSuit::Diamonds,
+                PickSuit::Spades,
+                PickSuit::Clubs,
+            ],
+            config,
+        ))
     }
 }
 #[derive(Debug)]
@@ -75,32 +221,597 @@ enum PickSuit {
     Spades,
     Clubs,
 }
-impl Choice for PickSuit {
-    fn score(&self, cards: &[PlayingCard]) -> f64 {
+impl Pick for PickSuit {
+    const CARDS: usize = 1;
+    fn payout(payouts: &Payouts) -> f64 {
+        payouts.suit / payouts.contained
+    }
+    fn score(&self, cards: &[PlayingCard], config: &GameConfig) -> f64 {
         match (self, cards[0].suit()) {
             (Self::Hearts, 0) | (Self::Diamonds, 1) | (Self::Spades, 2) | (Self::Clubs, 3) => {
-                10.0 / 4.0 // 4x -> 10x
+                Self::payout(&config.payouts)
             }
             (_, 0..=3) => 0.0,
             _ => unreachable!(),
         }
     }
-    fn next_decision(&self) -> Option<DiscreteDecision> {
+    fn next_decision(_: GameConfig) -> Option<DiscreteDecision> {
         None
     }
 }
 
+/// Solves Ride The Bus, then helps you play it
+///
+/// Every option can be given before or after the command
+#[derive(Parser)]
+#[command(name = "ride-the-bus")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    /// Show the cards seen this game as card faces above the choices
+    #[arg(long, global = true)]
+    cards: bool,
+    /// Show bars comparing the EVs and win probabilities of the choices
+    #[arg(long, global = true)]
+    bars: bool,
+    /// Make the output easier to follow with a screen reader: cards are spelled out
+    /// (e.g. 'queen of diamonds'), symbols like arrows are left out, and the optimal
+    /// choice is announced in a sentence
+    #[arg(long, global = true)]
+    accessible: bool,
+    /// The language of the help, tutorial, and prompts
+    #[arg(long, global = true, value_name = "en|es", value_parser = arg_from_str::<Lang>("expected either 'en' or 'es'"))]
+    lang: Option<Lang>,
+    /// Play in a full screen UI with panels for the choices, cards, deck, and history
+    #[arg(long, global = true)]
+    tui: bool,
+    /// Print only the optimal choice at each decision (e.g. for an overlay reading the output)
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Print the cards and payouts of the choices at each decision, along with the choices
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    /// Maximize the chance of finishing with at least this multiplier, instead of EV
+    #[arg(
+        long,
+        global = true,
+        value_name = "multiplier",
+        conflicts_with = "risk_aversion"
+    )]
+    target: Option<f64>,
+    /// Maximize the expected utility of your bankroll (in bets, 10 by default) instead of EV, where 1 is log utility
+    #[arg(long, global = true, value_name = "gamma[:bankroll]", value_parser = arg(parse_utility, "expected a risk aversion, optionally with a bankroll in bets (e.g. '1:20')"))]
+    risk_aversion: Option<Utility>,
+    /// Break ties between optimal choices by the lowest variance or bust probability
+    #[arg(long, global = true, value_name = "variance|bust", value_parser = arg(parse_tie_break, "expected either 'variance' or 'bust'"))]
+    tie_break: Option<TieBreak>,
+    /// Include a side bet on the suit or rank of a stage's card (the final one by default) in the values
+    #[arg(long, global = true, value_name = "stage?:suit|rank:stake:payout", value_parser = arg(parse_side_bet, "expected an optional stage, a suit or rank, stake, and payout (e.g. 'H:0.2:3.5' or '1:A:0.1:11')"))]
+    side_bet: Option<(Option<usize>, SideBet)>,
+    /// Include a progressive jackpot (in bets) for winning with a final card of the suit or rank in the values
+    #[arg(long, global = true, value_name = "suit|rank:amount", value_parser = arg(parse_jackpot, "expected the suit or rank of the final card, and the jackpot in bets (e.g. 'A:500')"))]
+    jackpot: Option<Jackpot>,
+    /// Allow paying a fraction of the pot to swap the card revealed at a stage for another
+    #[arg(long, global = true, value_name = "stage:fee", value_parser = arg(parse_card_swap, "expected a stage and the fee as a fraction of the pot (e.g. '4:0.1')"))]
+    swap: Option<CardSwap>,
+    /// Allow multiplying the pot once before any of the stages, giving up cashing out for the rest of the game
+    #[arg(long, global = true, value_name = "stages:multiplier", value_parser = arg(DoubleDownConfig::parse, "expected the stages and what the pot is multiplied by (e.g. '3,4:2')"))]
+    double_down: Option<DoubleDownConfig>,
+    /// Allow paying a fee (in bets) to insure the pick of a stage (2 by default), refunding part of the bet if it loses
+    #[arg(long, global = true, value_name = "stage?:fee:refund", value_parser = arg(InsuranceConfig::parse, "expected an optional stage, the fee, and the refund in bets (e.g. '0.25:0.5' or '3:0.5:1')"))]
+    insurance: Option<InsuranceConfig>,
+    /// Allow paying a fraction of the pot to discard a revealed card and draw again once per game (only feasible with small decks)
+    #[arg(long, global = true, value_name = "stages:fee", value_parser = arg(MulliganConfig::parse, "expected the stages and the fee as a fraction of the pot (e.g. '4:0.25')"))]
+    mulligan: Option<MulliganConfig>,
+    /// Load the payouts from a TOML file, for other versions of the game or house variants
+    ///
+    /// Options for the payouts and rules override the ones in the file
+    #[arg(long, global = true, value_name = "file", value_parser = |path: &str| config::load(Path::new(path)).map_err(|err| format!("failed to load config: {}", err)))]
+    config: Option<GameConfig>,
+    /// Solve the stages, picks, and payouts declared in a TOML file instead of Ride The Bus
+    #[arg(long, global = true, value_name = "file", value_parser = |path: &str| game_file::load(Path::new(path)).map_err(|err| format!("failed to load game file: {}", err)))]
+    game_file: Option<GameFile>,
+    /// The pot after winning each stage (2:3:4:10 by default)
+    #[arg(long, global = true, value_name = "color:latitude:contained:suit", value_parser = arg(Payouts::parse, "expected the pot after each stage, all positive (e.g. '2:3:4:10')"))]
+    payouts: Option<Payouts>,
+    /// How ties are ruled for Higher/Lower and Inside/Outside, each 'standard' (Higher and Inside win), 'loss', 'push', or 'win'
+    #[arg(long, global = true, value_name = "latitude:contained", value_parser = arg(Ties::parse, "expected the rule for Higher/Lower and Inside/Outside ties, each 'standard', 'loss', 'push', or 'win' (e.g. 'push:loss')"))]
+    ties: Option<Ties>,
+    /// Rank aces above kings, below twos, or either way (whichever wins), when comparing ranks
+    #[arg(long, global = true, value_name = "high|low|both", value_parser = arg_from_str::<Aces>("expected either 'high', 'low', or 'both'"))]
+    aces: Option<Aces>,
+    /// Deal every card from a full deck, as if the dealer reshuffles before each one
+    #[arg(long, global = true)]
+    reshuffle: bool,
+    /// Play with a 52 card deck, a 32 card deck (7 through ace), or a 36 card deck (6 through ace)
+    #[arg(long, global = true, value_name = "standard|piquet|short", value_parser = arg_from_str::<DeckPreset>("expected either 'standard', 'piquet', or 'short'"))]
+    deck: Option<DeckPreset>,
+    /// Take cards known to be gone (e.g. burned, or dealt to other players) out of the deck, separated by commas
+    #[arg(long, global = true, value_name = "cards", value_delimiter = ',', value_parser = arg_from_str::<PlayingCard>("expected cards separated by commas (e.g. '2H,10S,KC')"))]
+    removed: Vec<PlayingCard>,
+    /// Seed the cards dealt by 'deal' and the simulations, so they can be repeated
+    #[arg(long, global = true, value_name = "number")]
+    seed: Option<u64>,
+    /// Print the choices and events as text, as markdown tables to paste elsewhere, or as JSON
+    #[arg(long, global = true, value_name = "text|markdown|json", value_parser = arg_from_str::<Format>("expected either 'text', 'markdown', or 'json'"))]
+    format: Option<Format>,
+    /// Print lists, payouts, stages, and the strategy as JSON (one object per line) for scripts
+    #[arg(long, global = true)]
+    json: bool,
+    /// The decimal places of the EVs and probabilities printed (4 by default)
+    #[arg(long, global = true, value_name = "places")]
+    precision: Option<usize>,
+    /// Print probabilities as percentages
+    #[arg(long, global = true)]
+    percent: bool,
+    /// Print EVs as the return on the bet (e.g. '+22.50%') instead of a multiplier
+    #[arg(long, global = true)]
+    ev_return: bool,
+    /// Save the solved game to a file, and load it instead of solving on later runs
+    #[arg(long, global = true, value_name = "file")]
+    cache: Option<PathBuf>,
+    /// Record the session from the start like the 'record' command, writing every card entered,
+    /// choice taken, and EV shown to a file as it's played, which can be played again with 'replay'
+    #[arg(long, global = true, value_name = "file")]
+    record: Option<PathBuf>,
+    /// Keep the result of every hand played in a file across runs, for the 'career' command
+    #[arg(long, global = true, value_name = "file")]
+    career: Option<PathBuf>,
+    /// Log what the solver and prompt do at this level and above, to stderr unless a log file is given
+    #[arg(long, global = true, value_name = "off|error|warn|info|debug|trace", value_parser = arg_from_str::<LevelFilter>("expected either 'off', 'error', 'warn', 'info', 'debug', or 'trace'"))]
+    log_level: Option<LevelFilter>,
+    /// Write the log to a file instead of stderr (at the info level unless a log level is given)
+    #[arg(long, global = true, value_name = "file")]
+    log_file: Option<PathBuf>,
+}
+/// What to do with the solved game
+#[derive(Subcommand, Default)]
+enum CliCommand {
+    /// Play hands with the choices shown at every decision (the default)
+    #[default]
+    Interactive,
+    /// Print the choices and stages of the first decision, then exit
+    Solve,
+    /// Print the optimal choice at each step of a hand with the cards dealt, then exit
+    ///
+    /// The cards are read from stdin (one per line) if they aren't given, so hands can
+    /// be scripted
+    Batch {
+        /// The cards dealt so far, in order and separated by commas (e.g. 7H,KD,9S)
+        #[arg(value_name = "cards", value_delimiter = ',', value_parser = arg_from_str::<PlayingCard>("expected cards separated by commas (e.g. '7H,KD,9S')"))]
+        hand: Vec<PlayingCard>,
+    },
+    /// Print the EVs of the choices after the cards dealt so far, and nothing else
+    Ev {
+        /// The cards dealt so far, in order (e.g. 7H KD)
+        #[arg(value_name = "cards", value_parser = arg_from_str::<PlayingCard>("expected a card (e.g. '7H')"))]
+        hand: Vec<PlayingCard>,
+    },
+    /// Play hands optimally with random cards, printing how they went, then exit
+    Simulate {
+        /// The number of hands to play
+        #[arg(long, default_value_t = 10_000)]
+        hands: usize,
+    },
+    /// Export the solved game to a file, then exit
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// Step through a session recorded with --record (or 'record'), showing the choices, the
+    /// optimal ones, and the choice taken at each decision (without solving the game or
+    /// running its commands, which the interactive 'replay' does)
+    Review {
+        /// The session log
+        path: PathBuf,
+    },
+}
+/// What the solved game is exported as
+#[derive(Subcommand)]
+enum ExportCommand {
+    /// An HTML report of the stages, payouts, and strategy
+    Report { path: PathBuf },
+    /// The optimal choice for every stage and state, as JSON (for .json files) or CSV
+    Strategy { path: PathBuf },
+    /// The optimal choice, win probability, and EV for every stage and state, as CSV
+    Stages { path: PathBuf },
+    /// Charts of the EV by stage and the payout distribution, as SVG files in the directory
+    Charts { dir: PathBuf },
+    /// The value and probability of every card for each choice as CSV, for every later
+    /// decision too with --all
+    Csv {
+        path: PathBuf,
+        #[arg(long)]
+        all: bool,
+    },
+    /// The tree from the first decision as a Graphviz graph, this many decisions deep
+    Dot {
+        path: PathBuf,
+        #[arg(long, default_value_t = 1)]
+        depth: usize,
+    },
+}
+/// Parses a flag's value with `parse`, failing with `err` (e.g. an example of the format)
+fn arg<T>(
+    parse: impl Fn(&str) -> Option<T> + Clone + Send + Sync + 'static,
+    err: &'static str,
+) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync + 'static {
+    move |s| parse(s).ok_or_else(|| err.to_owned())
+}
+/// Parses a flag's value with [`FromStr`], failing with `err`
+fn arg_from_str<T: FromStr + 'static>(
+    err: &'static str,
+) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync + 'static {
+    arg(|s| s.parse().ok(), err)
+}
+/// Parses the tie break of `--tie-break`
+fn parse_tie_break(spec: &str) -> Option<TieBreak> {
+    match spec {
+        "variance" => Some(TieBreak::Variance),
+        "bust" => Some(TieBreak::BustProbability),
+        _ => None,
+    }
+}
+
+/// Options given on the command line when starting the program
+#[derive(Default)]
+struct Options {
+    /// Render the cards seen this game as card faces above the choices
+    card_faces: bool,
+    /// Render bars next to the EVs and win probabilities of each choice
+    bars: bool,
+    /// Make the output easier to follow with a screen reader, see [`render::accessible`]
+    accessible: bool,
+    /// The language of the help, tutorial, and prompts
+    lang: Lang,
+    /// Play in a full screen terminal UI instead of the line based prompt
+    tui: bool,
+    /// How much is printed at each decision
+    verbosity: Verbosity,
+    /// Print each decision as a line of JSON, without the tutorial, prompts, or
+    /// other decorations, for programs reading the output
+    ///
+    /// Used when stdout isn't a terminal, unless a format is given
+    machine: bool,
+    /// What to do once the game is solved
+    command: CliCommand,
+    /// What the solver maximizes and how it breaks ties
+    solve: SolveOptions,
+    /// The seed for dealing cards and simulations, so they can be repeated
+    ///
+    /// Picked from the clock at startup if not given
+    seed: Option<u64>,
+    /// Where the solved tree is cached between runs
+    cache: Option<PathBuf>,
+    /// Where the session is logged as it's played, see [`Session`]
+    record: Option<PathBuf>,
+    /// Where the hands played are kept across runs, if they are
+    career: Option<PathBuf>,
+    /// The level logged at and above, if logging
+    log_level: Option<LevelFilter>,
+    /// Where the log is written instead of stderr
+    log_file: Option<PathBuf>,
+    /// How the choices and events are listed, unless given with the command
+    format: Format,
+    /// How EVs and probabilities are printed in text and markdown
+    numbers: render::NumberFormat,
+    /// The payouts and rules of the game being solved
+    config: GameConfig,
+    /// The stages of the game being solved, if not Ride The Bus
+    game: Option<GameFile>,
+    /// Cards known to be out of the deck, e.g. burned or dealt to other players
+    removed: Vec<PlayingCard>,
+}
+/// How much the interactive prompt prints at each decision
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Verbosity {
+    /// Only the optimal choice, without the solver's progress or the tutorial
+    Quiet,
+    /// The choices and their values
+    #[default]
+    Normal,
+    /// The choices, along with the cards each wins on and the payouts of the optimal one
+    Verbose,
+}
+impl Options {
+    fn from_args() -> Self {
+        let cli = Cli::parse();
+        let mut options = Options {
+            // the card faces and bars are drawn with symbols that can't be read out
+            card_faces: cli.cards && !cli.accessible,
+            bars: cli.bars && !cli.accessible,
+            accessible: cli.accessible,
+            lang: cli.lang.unwrap_or_default(),
+            tui: cli.tui,
+            verbosity: match (cli.quiet, cli.verbose) {
+                (true, _) => Verbosity::Quiet,
+                (_, true) => Verbosity::Verbose,
+                _ => Verbosity::Normal,
+            },
+            machine: !io::stdout().is_terminal() && cli.format.is_none() && !cli.json,
+            seed: cli.seed,
+            cache: cli.cache,
+            record: cli.record,
+            career: cli.career,
+            log_level: cli.log_level,
+            log_file: cli.log_file,
+            format: cli.format.unwrap_or_default(),
+            numbers: render::NumberFormat {
+                precision: cli.precision.unwrap_or(4),
+                percent: cli.percent,
+                ev_return: cli.ev_return,
+            },
+            config: cli.config.unwrap_or_default(),
+            game: cli.game_file,
+            command: cli.command.unwrap_or_default(),
+            ..Options::default()
+        };
+        if cli.json {
+            options.format = Format::Json;
+        }
+        if let Some(target) = cli.target {
+            options.solve.objective = Objective::TargetProbability(target);
+        }
+        if let Some(utility) = cli.risk_aversion {
+            options.solve.objective = Objective::Utility(utility);
+        }
+        if let Some(tie_break) = cli.tie_break {
+            options.solve.tie_break = tie_break;
+        }
+        options.solve.side_bet = cli.side_bet.map(|(_, side_bet)| side_bet);
+        options.solve.jackpot = cli.jackpot;
+        options.solve.card_swap = cli.swap;
+        // the rules given as options override the ones in the config file
+        let config = &mut options.config;
+        config.double_down = cli.double_down.or(config.double_down);
+        config.insurance = cli.insurance.or(config.insurance);
+        config.mulligan = cli.mulligan.or(config.mulligan);
+        config.payouts = cli.payouts.unwrap_or(config.payouts);
+        config.ties = cli.ties.unwrap_or(config.ties);
+        config.aces = cli.aces.unwrap_or(config.aces);
+        config.reshuffle |= cli.reshuffle;
+        if let Some(preset) = cli.deck {
+            config.deck.preset = preset;
+        }
+        let side_bet_stage = cli.side_bet.and_then(|(stage, _)| stage);
+        let removed = cli.removed;
+        // the deck may be changed after the cards are removed
+        if let Some(card) = options.unremovable(&removed) {
+            Self::usage(&format!("{} isn't left in the deck to remove", card));
+        }
+        options.removed = removed;
+        // the game file may come after the options that depend on its stages
+        let stages = options.stages();
+        if options.undealable(&[]) {
+            Self::usage("the deck needs a card for every stage of the game");
+        }
+        if let Some(side_bet) = &mut options.solve.side_bet {
+            // the side bet is on the final card, unless it's on the card of another stage
+            let stage = side_bet_stage.unwrap_or(stages);
+            if stage > stages {
+                Self::usage(&format!("--side-bet requires a stage from 1 to {}", stages));
+            }
+            side_bet.card = stage - 1;
+        }
+        if options
+            .solve
+            .card_swap
+            .is_some_and(|swap| swap.stage > stages)
+        {
+            Self::usage(&format!("--swap requires a stage from 1 to {}", stages));
+        }
+        if let Some(double_down) = options.config.double_down
+            && double_down.last_stage() > stages
+        {
+            Self::usage(&format!("double down requires stages from 1 to {}", stages));
+        }
+        if let Some(mulligan) = options.config.mulligan
+            && mulligan.last_stage() > stages
+        {
+            Self::usage(&format!("a mulligan requires stages from 1 to {}", stages));
+        }
+        if let Some(insurance) = options.config.insurance {
+            if insurance.stage > stages {
+                Self::usage(&format!("insurance requires a stage from 1 to {}", stages));
+            }
+            if insurance.fee >= options.stage_pot(insurance.stage) {
+                Self::usage("insurance requires a fee less than the pot at its stage");
+            }
+        }
+        options
+    }
+    /// The pot, as a multiple of the bet, at the decision of a stage (from 1)
+    fn stage_pot(&self, stage: usize) -> f64 {
+        if stage == 1 {
+            return 1.0;
+        }
+        match &self.game {
+            Some(game) => game.stages[stage - 2].pot,
+            None => {
+                let payouts = self.config.payouts;
+                [payouts.color, payouts.latitude, payouts.contained][stage - 2]
+            }
+        }
+    }
+    /// The insurance the game is solved with
+    fn insurance(&self) -> Option<Insurance> {
+        self.config.insurance.map(|insurance| Insurance {
+            stage: insurance.stage,
+            fee: insurance.fee,
+            refund: insurance.refund,
+        })
+    }
+    /// The number of stages of the game being solved
+    fn stages(&self) -> usize {
+        self.game.as_ref().map_or(4, |game| game.stages.len())
+    }
+    /// The deck the cards are dealt from, without the cards known to be removed
+    fn deck(&self) -> DeckSpec {
+        self.removed
+            .iter()
+            .fold(self.config.deck(), |deck, &card| deck.without_card(card))
+    }
+    /// The first of `cards` that isn't left in the deck to be removed, if any
+    fn unremovable(&self, cards: &[PlayingCard]) -> Option<PlayingCard> {
+        let deck = self.config.deck();
+        cards.iter().enumerate().find_map(|(i, &card)| {
+            let removed = self
+                .removed
+                .iter()
+                .chain(&cards[..=i])
+                .filter(|&&removed| removed == card)
+                .count();
+            (removed > deck.copies(card) as usize).then_some(card)
+        })
+    }
+    /// Whether the deck is left without enough cards to deal a hand once `cards` are
+    /// also removed, which is a card for every stage unless the deck is reshuffled
+    fn undealable(&self, cards: &[PlayingCard]) -> bool {
+        let deck = cards
+            .iter()
+            .fold(self.deck(), |deck, &card| deck.without_card(card));
+        let needed = if self.config.reshuffle {
+            1
+        } else {
+            self.stages()
+        };
+        deck.len() < needed
+    }
+    fn usage(err: &str) -> ! {
+        Cli::command().error(ErrorKind::ValueValidation, err).exit()
+    }
+}
+
 struct InvalidCommandErr;
+/// The names of every command, used to correct typos
+const COMMAND_NAMES: &[&str] = &[
+    "help", "exit", "list", "tree", "payouts", "why", "stages", "bust", "strategy", "report",
+    "export", "compare", "record", "replay", "copy", "kelly", "bankroll", "ruin", "autoplay",
+    "verify", "deal", "pick", "game", "practice", "bet", "reset", "back", "forward", "history",
+    "stats", "career", "luck", "remove", "table",
+];
 enum Command {
     Help,
     Exit,
-    ListChoices,
-    ListEvents(String),
+    ListChoices(Option<Format>),
+    ListEvents(String, Option<Format>, EventFilter),
+    Tree(usize),
+    Payouts(String),
+    /// Explains how the EV of a choice adds up
+    Why(String),
+    ListStages,
+    /// Prints the probability of busting at each stage from the current decision
+    Bust,
+    Strategy(Option<PathBuf>),
+    Report(PathBuf),
+    ExportStages(PathBuf),
+    ExportCharts(PathBuf),
+    /// Exports the tree from the current decision, this many decisions deep
+    ExportDot(PathBuf, usize),
+    /// Exports the outcomes of the current decision, or of the whole tree under it
+    ExportCsv(PathBuf, bool),
+    Compare(Box<[(String, SolveOptions); 2]>),
+    /// Compares the optimal strategy against simple policies
+    ComparePolicies,
+    Record(Option<PathBuf>),
+    Replay(PathBuf),
+    Copy,
+    Kelly(f64),
+    /// Prints the balance kept in the ledger and bet sizes for it, after setting it if given
+    Bankroll(Option<f64>),
+    /// Estimates the risk of ruin for a bankroll, bet, and number of hands
+    Ruin(f64, f64, usize),
+    /// Plays this many hands optimally, dealing the cards itself
+    Autoplay(usize),
+    /// Checks the solved EV against this many simulated hands
+    Verify(usize),
 
+    Deal,
+    Pick(String),
+    Game(GameCommand),
+    Practice(PracticeCommand),
+    /// Sets the amount bet on each hand of the current game
+    Bet(f64),
     Reset,
     Back,
+    /// Takes the last step taken back with `Back` again
+    Forward,
+    /// Prints the cards and choices of the hand in progress
+    History,
+    /// Prints the size of the solved tree and of the current decision's subtree
+    Stats,
+    /// Prints the totals of every hand kept in the career file
+    Career,
+    /// Prints the luck of the hand in progress and of every finished hand
+    Luck,
+    /// Takes cards known to be gone out of the deck, solving the game again
+    Remove(Vec<PlayingCard>),
+    Table(TableCommand),
     Card(PlayingCard),
 }
+enum GameCommand {
+    /// Starts a new game with the given bet, if any
+    New(Option<f64>),
+    List,
+    /// Switches to the game with the given id (numbered from 1)
+    Switch(usize),
+}
+enum PracticeCommand {
+    /// Starts a practice game with the given bet, if any
+    Start(Option<f64>),
+    /// Stops the current practice game, printing how it went
+    Stop,
+}
+/// Which cards of a choice `list` prints, and in what order
+#[derive(Default)]
+struct EventFilter {
+    /// Also print the cards the choice loses on
+    all: bool,
+    /// Sort the cards by EV (highest first), instead of in the order of the deck
+    by_ev: bool,
+    /// Only print cards of this suit
+    suit: Option<u8>,
+    /// Only print cards with ranks in this range (aces are 14)
+    ranks: Option<RangeInclusive<u8>>,
+}
+impl EventFilter {
+    /// Parses a rank, optionally compared with `=`, `<`, `<=`, `>`, or `>=` (e.g. `>=10`)
+    fn parse_ranks(spec: &str) -> Option<RangeInclusive<u8>> {
+        let (rank, ranks): (_, fn(u8) -> RangeInclusive<u8>) =
+            match spec.split_at(spec.find(|c: char| c.is_ascii_alphanumeric())?) {
+                ("" | "=", rank) => (rank, |rank| rank..=rank),
+                ("<", rank) => (rank, |rank| 2..=rank - 1),
+                ("<=", rank) => (rank, |rank| 2..=rank),
+                (">", rank) => (rank, |rank| rank + 1..=14),
+                (">=", rank) => (rank, |rank| rank..=14),
+                _ => return None,
+            };
+        match parse_target(rank)? {
+            SideBetTarget::Rank(rank) => Some(ranks(rank)),
+            SideBetTarget::Suit(_) => None,
+        }
+    }
+    /// Whether the event passes the suit and rank filters (which an outcome without
+    /// a card never does)
+    fn matches(&self, event: Option<PlayingCard>) -> bool {
+        match event {
+            Some(card) => {
+                self.suit
+                    .is_none_or(|suit| !card.is_joker() && card.suit() == suit)
+                    && self
+                        .ranks
+                        .as_ref()
+                        .is_none_or(|ranks| !card.is_joker() && ranks.contains(&card.rank()))
+            }
+            None => self.suit.is_none() && self.ranks.is_none(),
+        }
+    }
+}
+enum TableCommand {
+    /// Takes the cards dealt to a seat (numbered from 2, you're seat 1) out of the deck
+    Deal(usize, Vec<PlayingCard>),
+    List,
+    /// Starts a new round, putting the other seats' cards back in the deck
+    Clear,
+}
 impl FromStr for Command {
     type Err = InvalidCommandErr;
 
@@ -109,13 +820,218 @@ impl FromStr for Command {
         match split.next() {
             Some("help") => Ok(Command::Help),
             Some("exit") => Ok(Command::Exit),
-            Some("list") => Ok(split
+            Some("list") => {
+                // the format can be given after the choice, overriding --format
+                let (mut choice, mut format) = (None, None);
+                let mut filter = EventFilter::default();
+                while let Some(arg) = split.next() {
+                    match arg {
+                        "--format" => {
+                            format = Some(split.next().ok_or(InvalidCommandErr)?.parse()?)
+                        }
+                        "--all" => filter.all = true,
+                        "--sort" => match split.next() {
+                            Some("ev") => filter.by_ev = true,
+                            Some("card") => filter.by_ev = false,
+                            _ => return Err(InvalidCommandErr),
+                        },
+                        "--suit" => match split.next().and_then(parse_target) {
+                            Some(SideBetTarget::Suit(suit)) => filter.suit = Some(suit),
+                            _ => return Err(InvalidCommandErr),
+                        },
+                        "--rank" => {
+                            let ranks = split.next().and_then(EventFilter::parse_ranks);
+                            filter.ranks = Some(ranks.ok_or(InvalidCommandErr)?);
+                        }
+                        choice_name if choice.is_none() => choice = Some(choice_name.to_owned()),
+                        _ => return Err(InvalidCommandErr),
+                    }
+                }
+                Ok(match choice {
+                    Some(choice) => Command::ListEvents(choice, format, filter),
+                    None => Command::ListChoices(format),
+                })
+            }
+            Some("tree") => match split.next() {
+                None => Ok(Command::Tree(1)),
+                Some(depth) => depth
+                    .parse()
+                    .ok()
+                    .filter(|&depth| depth > 0)
+                    .map(Command::Tree)
+                    .ok_or(InvalidCommandErr),
+            },
+            Some("payouts") => Ok(Command::Payouts(
+                split.next().unwrap_or("optimal").to_owned(),
+            )),
+            Some("why") => Ok(Command::Why(split.next().unwrap_or("optimal").to_owned())),
+            Some("stages") => Ok(Command::ListStages),
+            Some("bust") => Ok(Command::Bust),
+            Some("report") => split
+                .next()
+                .map(PathBuf::from)
+                .map(Command::Report)
+                .ok_or(InvalidCommandErr),
+            Some("strategy") => Ok(Command::Strategy(split.next().map(PathBuf::from))),
+            Some("export") => match (split.next(), split.next()) {
+                (Some("stages"), Some(path)) => Ok(Command::ExportStages(PathBuf::from(path))),
+                (Some("charts"), Some(dir)) => Ok(Command::ExportCharts(PathBuf::from(dir))),
+                (Some("csv"), Some(path)) => match split.next() {
+                    None => Ok(Command::ExportCsv(PathBuf::from(path), false)),
+                    Some("--all") => Ok(Command::ExportCsv(PathBuf::from(path), true)),
+                    Some(_) => Err(InvalidCommandErr),
+                },
+                (Some("dot"), Some(path)) => match split.next().map(str::parse) {
+                    None => Ok(Command::ExportDot(PathBuf::from(path), 1)),
+                    Some(Ok(depth)) if depth > 0 => {
+                        Ok(Command::ExportDot(PathBuf::from(path), depth))
+                    }
+                    Some(_) => Err(InvalidCommandErr),
+                },
+                _ => Err(InvalidCommandErr),
+            },
+            Some("compare") if split.clone().next().is_none() => Ok(Command::ComparePolicies),
+            Some("compare") => {
+                let mut strategy = || {
+                    let spec = split.next()?;
+                    parse_strategy(spec).map(|options| (spec.to_owned(), options))
+                };
+                match (strategy(), strategy()) {
+                    (Some(s1), Some(s2)) => Ok(Command::Compare(Box::new([s1, s2]))),
+                    _ => Err(InvalidCommandErr),
+                }
+            }
+            Some("record") => match split.next() {
+                Some("stop") => Ok(Command::Record(None)),
+                Some(path) => Ok(Command::Record(Some(PathBuf::from(path)))),
+                None => Err(InvalidCommandErr),
+            },
+            Some("replay") => split
+                .next()
+                .map(PathBuf::from)
+                .map(Command::Replay)
+                .ok_or(InvalidCommandErr),
+            Some("copy") => Ok(Command::Copy),
+            Some("ruin") => {
+                let mut amount = || {
+                    split
+                        .next()
+                        .and_then(|x| x.parse().ok())
+                        .filter(|&x: &f64| x > 0.0)
+                };
+                let (bankroll, bet) = (amount(), amount());
+                let hands = split.next().and_then(|x| x.parse().ok());
+                match (bankroll, bet, hands) {
+                    (Some(bankroll), Some(bet), Some(hands)) => {
+                        Ok(Command::Ruin(bankroll, bet, hands))
+                    }
+                    _ => Err(InvalidCommandErr),
+                }
+            }
+            Some("autoplay") => split
+                .next()
+                .and_then(|hands| hands.parse().ok())
+                .filter(|&hands| hands > 0)
+                .map(Command::Autoplay)
+                .ok_or(InvalidCommandErr),
+            Some("verify") => match split.next() {
+                None => Ok(Command::Verify(100_000)),
+                Some(hands) => hands
+                    .parse()
+                    .ok()
+                    .filter(|&hands| hands > 1)
+                    .map(Command::Verify)
+                    .ok_or(InvalidCommandErr),
+            },
+            Some("kelly") => split
+                .next()
+                .and_then(|bankroll| bankroll.parse().ok())
+                .filter(|&bankroll: &f64| bankroll > 0.0)
+                .map(Command::Kelly)
+                .ok_or(InvalidCommandErr),
+            Some("bankroll") => match split.next() {
+                None => Ok(Command::Bankroll(None)),
+                Some(balance) => balance
+                    .parse()
+                    .ok()
+                    .filter(|&balance: &f64| balance >= 0.0)
+                    .map(|balance| Command::Bankroll(Some(balance)))
+                    .ok_or(InvalidCommandErr),
+            },
+            Some("deal") => Ok(Command::Deal),
+            Some("pick") => split
                 .next()
                 .map(ToOwned::to_owned)
-                .map(Command::ListEvents)
-                .unwrap_or(Command::ListChoices)),
+                .map(Command::Pick)
+                .ok_or(InvalidCommandErr),
+            Some("game") => match (split.next(), split.next()) {
+                (Some("new"), None) => Ok(Command::Game(GameCommand::New(None))),
+                (Some("new"), Some(bet)) => bet
+                    .parse()
+                    .ok()
+                    .filter(|&bet: &f64| bet > 0.0)
+                    .map(|bet| Command::Game(GameCommand::New(Some(bet))))
+                    .ok_or(InvalidCommandErr),
+                (Some("list"), None) => Ok(Command::Game(GameCommand::List)),
+                (Some("switch"), Some(id)) => id
+                    .parse()
+                    .map(|id| Command::Game(GameCommand::Switch(id)))
+                    .map_err(|_| InvalidCommandErr),
+                _ => Err(InvalidCommandErr),
+            },
+            Some("practice") => match split.next() {
+                None => Ok(Command::Practice(PracticeCommand::Start(None))),
+                Some("stop") => Ok(Command::Practice(PracticeCommand::Stop)),
+                Some(bet) => bet
+                    .parse()
+                    .ok()
+                    .filter(|&bet: &f64| bet > 0.0)
+                    .map(|bet| Command::Practice(PracticeCommand::Start(Some(bet))))
+                    .ok_or(InvalidCommandErr),
+            },
+            Some("bet") => split
+                .next()
+                .and_then(|bet| bet.parse().ok())
+                .filter(|&bet: &f64| bet > 0.0)
+                .map(Command::Bet)
+                .ok_or(InvalidCommandErr),
             Some("reset") => Ok(Command::Reset),
             Some("back") => Ok(Command::Back),
+            Some("forward") => Ok(Command::Forward),
+            Some("history") => Ok(Command::History),
+            Some("stats") => Ok(Command::Stats),
+            Some("career") => Ok(Command::Career),
+            Some("luck") => Ok(Command::Luck),
+            Some("remove") => {
+                let cards = split
+                    .map(PlayingCard::from_str)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| InvalidCommandErr)?;
+                match cards.is_empty() {
+                    true => Err(InvalidCommandErr),
+                    false => Ok(Command::Remove(cards)),
+                }
+            }
+            Some("table") => match split.next() {
+                Some("list") => Ok(Command::Table(TableCommand::List)),
+                Some("clear") => Ok(Command::Table(TableCommand::Clear)),
+                Some(seat) => {
+                    let seat = seat
+                        .parse()
+                        .ok()
+                        .filter(|&seat: &usize| seat >= 2)
+                        .ok_or(InvalidCommandErr)?;
+                    let cards = split
+                        .map(PlayingCard::from_str)
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| InvalidCommandErr)?;
+                    match cards.is_empty() {
+                        true => Err(InvalidCommandErr),
+                        false => Ok(Command::Table(TableCommand::Deal(seat, cards))),
+                    }
+                }
+                None => Err(InvalidCommandErr),
+            },
             Some(cmd) => PlayingCard::from_str(cmd)
                 .map(Command::Card)
                 .map_err(|_| InvalidCommandErr),
@@ -123,72 +1039,759 @@ impl FromStr for Command {
         }
     }
 }
-impl Command {
-    fn from_stdin() -> io::Result<Self> {
+/// How tables like the choices are printed
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+enum Format {
+    #[default]
+    Text,
+    /// GitHub flavored markdown tables
+    Markdown,
+    /// A JSON object on a single line, for scripts
+    Json,
+}
+impl FromStr for Format {
+    type Err = InvalidCommandErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "markdown" => Ok(Format::Markdown),
+            "json" => Ok(Format::Json),
+            _ => Err(InvalidCommandErr),
+        }
+    }
+}
+/// Parses a strategy (how to solve the game) for the compare command
+///
+/// Strategies are an objective, either `ev`, `target={multiplier}`, or
+/// `utility={risk_aversion}` (see [`parse_utility`]), optionally
+/// followed by a tie break, either `,variance` or `,bust`
+fn parse_strategy(spec: &str) -> Option<SolveOptions> {
+    let (objective, tie_break) = match spec.split_once(',') {
+        Some((objective, tie_break)) => (objective, Some(tie_break)),
+        None => (spec, None),
+    };
+    let objective = match objective.split_once('=') {
+        None if objective == "ev" => Objective::ExpectedValue,
+        Some(("target", target)) => Objective::TargetProbability(target.parse().ok()?),
+        Some(("utility", utility)) => Objective::Utility(parse_utility(utility)?),
+        _ => return None,
+    };
+    let tie_break = match tie_break {
+        None => TieBreak::None,
+        Some("variance") => TieBreak::Variance,
+        Some("bust") => TieBreak::BustProbability,
+        Some(_) => return None,
+    };
+    Some(SolveOptions {
+        objective,
+        tie_break,
+        ..SolveOptions::default()
+    })
+}
+/// Parses a utility for the risk averse solver, given as `{risk_aversion}` or
+/// `{risk_aversion}:{bankroll}` where the bankroll is a multiple of the bet (10 by default)
+fn parse_utility(spec: &str) -> Option<Utility> {
+    let (risk_aversion, bankroll) = match spec.split_once(':') {
+        Some((risk_aversion, bankroll)) => (risk_aversion, bankroll.parse().ok()?),
+        None => (spec, 10.0),
+    };
+    let risk_aversion = risk_aversion.parse().ok().filter(|&r: &f64| r >= 0.0)?;
+    (bankroll > 1.0).then_some(Utility {
+        risk_aversion,
+        bankroll,
+    })
+}
+/// Parses the card swap rule, given as `{stage}:{fee}` where the fee is a fraction
+/// of the pot (e.g. `4:0.1`)
+fn parse_card_swap(spec: &str) -> Option<CardSwap> {
+    let (stage, fee) = spec.split_once(':')?;
+    let stage = stage.parse().ok().filter(|&stage| stage >= 1)?;
+    let fee = fee.parse().ok().filter(|fee| (0.0..1.0).contains(fee))?;
+    Some(CardSwap { stage, fee })
+}
+/// Parses a side bet on the card of a stage, given as `{stage}:{card}:{stake}:{payout}`
+/// where the card is a suit or rank and the stake is a multiple of the bet (e.g.
+/// `1:A:0.1:11` for the first card being an ace, paying 10:1)
+///
+/// The stage is left out for the final card (e.g. `H:0.2:3.5`), so it's returned
+/// separately to be set once the number of stages is known
+fn parse_side_bet(spec: &str) -> Option<(Option<usize>, SideBet)> {
+    let (stage, target, stake, payout) = match spec.split(':').collect::<Vec<_>>()[..] {
+        [target, stake, payout] => (None, target, stake, payout),
+        [stage, target, stake, payout] => {
+            let stage = stage.parse().ok().filter(|&stage| stage >= 1)?;
+            (Some(stage), target, stake, payout)
+        }
+        _ => return None,
+    };
+    let side_bet = SideBet {
+        card: 0,
+        target: parse_target(target)?,
+        stake: stake.parse().ok().filter(|&stake: &f64| stake > 0.0)?,
+        payout: payout.parse().ok().filter(|&payout: &f64| payout > 0.0)?,
+    };
+    Some((stage, side_bet))
+}
+/// Parses a jackpot for winning with the final card, given as `{card}:{amount}` where
+/// the card is a suit or rank and the amount is a multiple of the bet (e.g. `A:500`)
+fn parse_jackpot(spec: &str) -> Option<Jackpot> {
+    let (target, amount) = spec.split_once(':')?;
+    Some(Jackpot {
+        target: parse_target(target)?,
+        amount: amount.parse().ok().filter(|&amount: &f64| amount >= 0.0)?,
+    })
+}
+/// Parses the suit or rank a card is wagered to be (e.g. `H` or `A`)
+fn parse_target(target: &str) -> Option<SideBetTarget> {
+    PlayingCard::deck_iter().find_map(|card| {
+        if card.suit_label().eq_ignore_ascii_case(target) {
+            Some(SideBetTarget::Suit(card.suit()))
+        } else if card.rank_label().eq_ignore_ascii_case(target) {
+            Some(SideBetTarget::Rank(card.rank()))
+        } else {
+            None
+        }
+    })
+}
+/// The label of the suit or rank a card is wagered to be (e.g. `H` or `A`)
+fn target_label(target: SideBetTarget) -> &'static str {
+    PlayingCard::deck_iter()
+        .find_map(|card| match target {
+            SideBetTarget::Suit(suit) if card.suit() == suit => Some(card.suit_label()),
+            SideBetTarget::Rank(rank) if card.rank() == rank => Some(card.rank_label()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+/// Reads commands for the interactive loop
+///
+/// Commands come from stdin unless a replay is in progress, and can be
+/// recorded to a session log so they can be replayed later
+///
+/// Also deals cards for the `deal` command
+struct Prompt {
+    /// Reads lines from the terminal, with history and tab completion
+    #[cfg(not(target_os = "wasi"))]
+    editor: Editor<CommandCompleter, DefaultHistory>,
+    replay: VecDeque<String>,
+    /// The log of the session, if it's being recorded
+    session: Option<Session>,
+    /// The bankroll ledger once it's been loaded, see [`Prompt::ledger`]
+    ledger: Option<bankroll::Ledger>,
+    rng: StdRng,
+    /// The deck cards are dealt from, see [`GameConfig::deck`]
+    deck: DeckSpec,
+    /// Leave out the prompts and replayed lines, and don't suggest corrections
+    /// (which would read the next line as the answer), see [`Options::machine`]
+    plain: bool,
+}
+/// Guesses which command was meant by an invalid line, by correcting a typo in
+/// the command's name or parsing a card that was spelled out (see [`fuzzy::parse_card`])
+fn suggest_command(line: &str) -> Option<String> {
+    let line = line.trim();
+    let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+    let corrected = fuzzy::closest(name, COMMAND_NAMES.iter().copied())
+        .map(|name| format!("{} {}", name, args).trim_end().to_owned())
+        .filter(|corrected| Command::from_str(corrected).is_ok());
+    corrected.or_else(|| fuzzy::parse_card(line).map(|card| card.to_string()))
+}
+impl Prompt {
+    fn new(seed: u64, deck: DeckSpec, plain: bool) -> Self {
+        Self {
+            #[cfg(not(target_os = "wasi"))]
+            editor: {
+                let mut editor = Editor::new().expect("line editor");
+                editor.set_helper(Some(CommandCompleter::default()));
+                editor
+            },
+            replay: VecDeque::new(),
+            session: None,
+            ledger: None,
+            rng: StdRng::seed_from_u64(seed),
+            deck,
+            plain,
+        }
+    }
+    /// The bankroll ledger, which is loaded the first time it's needed and then kept
+    /// up to date as hands change it
+    fn ledger(&mut self) -> io::Result<&mut bankroll::Ledger> {
+        let ledger = match self.ledger.take() {
+            Some(ledger) => ledger,
+            None => bankroll::Ledger::load()?,
+        };
+        Ok(self.ledger.insert(ledger))
+    }
+    /// Sets the choice names completed with tab, see [`CommandCompleter`]
+    #[cfg(not(target_os = "wasi"))]
+    fn complete_choices(&mut self, tree: &DiscreteDecisionTree) {
+        if let Some(completer) = self.editor.helper_mut() {
+            completer.choices = tree.iter().map(|c| format!("{:?}", c.choice)).collect();
+        }
+    }
+    /// Reads a command, with the `status` line as the prompt
+    ///
+    /// Lines that aren't valid commands can still be accepted after confirming
+    /// a suggested correction (see [`suggest_command`])
+    fn read_command(&mut self, status: &str) -> io::Result<Command> {
         loop {
-            print!("? ");
-            io::Write::flush(&mut io::stdout())?;
-            let mut line = String::new();
-            io::stdin().read_line(&mut line)?;
+            let line = match status {
+                _ if self.plain => self.read_line(""),
+                "" => self.read_line("? "),
+                status => self.read_line(&format!("{} ? ", status)),
+            };
+            // the input ending (e.g. ctrl-d, or the end of piped input) is the same as exiting
+            let line = match line {
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    println!();
+                    return Ok(Command::Exit);
+                }
+                line => line?,
+            };
 
+            debug!(line = line.trim(), "read command");
+            // record what the command was interpreted as, so replays don't need confirming
+            let line = match Command::from_str(&line) {
+                Ok(_) => line,
+                Err(_) if self.plain => {
+                    warn!(line = line.trim(), "invalid command");
+                    let error = serde_json::json!({ "error": lang::text().invalid_command });
+                    println!("{}", error);
+                    continue;
+                }
+                Err(_) => match suggest_command(&line) {
+                    Some(suggestion)
+                        if self
+                            .confirm(&lang::fill(lang::text().did_you_mean, &[&suggestion]))? =>
+                    {
+                        debug!(line = line.trim(), %suggestion, "corrected command");
+                        suggestion
+                    }
+                    Some(_) => continue,
+                    None => {
+                        warn!(line = line.trim(), "invalid command");
+                        println!("{}", lang::text().invalid_command);
+                        continue;
+                    }
+                },
+            };
             if let Ok(cmd) = Command::from_str(&line) {
+                self.record(&line, &cmd)?;
                 return Ok(cmd);
             }
-            println!("invalid command");
         }
     }
+    /// Reads the next line after the `prompt`, either from the replay or stdin
+    ///
+    /// Fails with [`io::ErrorKind::UnexpectedEof`] once stdin has ended
+    ///
+    /// Lines typed in a terminal can be edited, and earlier lines brought back
+    /// with the arrow keys
+    fn read_line(&mut self, prompt: &str) -> io::Result<String> {
+        match self.replay.pop_front() {
+            Some(line) if self.plain => Ok(line),
+            // echo replayed lines, so the transcript reads as if they were typed
+            Some(line) => {
+                println!("{}{}", prompt, line);
+                Ok(line)
+            }
+            #[cfg(not(target_os = "wasi"))]
+            None if io::stdin().is_terminal() => self.edit_line(prompt),
+            // the editor doesn't show the prompt when input is piped in (and WASI modules
+            // have no editor), so scripts read lines as they come
+            None => {
+                print!("{}", prompt);
+                io::stdout().flush()?;
+                let mut line = String::new();
+                match io::stdin().read_line(&mut line)? {
+                    0 => Err(io::ErrorKind::UnexpectedEof.into()),
+                    _ => Ok(line),
+                }
+            }
+        }
+    }
+    /// Reads a line typed in the terminal with the line editor
+    #[cfg(not(target_os = "wasi"))]
+    fn edit_line(&mut self, prompt: &str) -> io::Result<String> {
+        match self.editor.readline(prompt) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    self.editor
+                        .add_history_entry(line.as_str())
+                        .map_err(io::Error::other)?;
+                }
+                Ok(line)
+            }
+            Err(ReadlineError::Eof) => Err(io::ErrorKind::UnexpectedEof.into()),
+            // ctrl-c quits, like it did before the line was read by the editor
+            Err(ReadlineError::Interrupted) => std::process::exit(130),
+            Err(err) => Err(io::Error::other(err)),
+        }
+    }
+    /// Asks a yes or no question, returning whether it was answered yes (which it
+    /// isn't if the input ended)
+    fn confirm(&mut self, question: &str) -> io::Result<bool> {
+        let text = lang::text();
+        let answer = match self.read_line(&format!("{} {} ", question, text.yes_no)) {
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            answer => answer?.trim().to_lowercase(),
+        };
+        Ok(text.yes.contains(&answer.as_str()))
+    }
+    fn record(&mut self, line: &str, cmd: &Command) -> io::Result<()> {
+        // commands controlling the transcript aren't part of it (replayed commands are
+        // recorded as they're read instead), and deals are recorded as the card dealt
+        if matches!(cmd, Command::Record(_) | Command::Replay(_) | Command::Deal) {
+            return Ok(());
+        }
+        self.log(|session| session.input(line.trim()));
+        Ok(())
+    }
+    /// Writes an event to the session log, if it's being recorded
+    ///
+    /// The session stops being recorded if it can't be written, rather than stopping play
+    fn log(&mut self, write: impl FnOnce(&mut Session) -> io::Result<()>) {
+        if let Some(Err(err)) = self.session.as_mut().map(write) {
+            println!("failed to write the recording, stopped recording: {}", err);
+            self.session = None;
+        }
+    }
+
+    /// Deals a random card from the deck, without the cards that have been `seen`
+    /// (unless the deck is reshuffled)
+    ///
+    /// The card is recorded instead of the `deal` command, so replays deal the same cards
+    fn deal(&mut self, seen: &[PlayingCard]) -> io::Result<Option<PlayingCard>> {
+        let card = self.draw(seen);
+        if let Some(card) = card {
+            debug!(%card, "dealt");
+            println!("dealt {}", render::card(card));
+            self.record(&card.to_string(), &Command::Card(card))?;
+        }
+        Ok(card)
+    }
+    /// Deals the card a choice picked in a practice game is resolved by, like [`Prompt::deal`]
+    ///
+    /// The card is recorded after the pick, and replaying them deals the recorded
+    /// card instead of a new one
+    fn deal_picked(&mut self, seen: &[PlayingCard]) -> io::Result<Option<PlayingCard>> {
+        let replayed = match self.replay.front().map(|line| Command::from_str(line)) {
+            Some(Ok(Command::Card(card))) => Some(card),
+            _ => None,
+        };
+        let card = match replayed {
+            Some(card) => {
+                self.replay.pop_front();
+                Some(card)
+            }
+            None => self.draw(seen),
+        };
+        if let Some(card) = card {
+            debug!(%card, "dealt");
+            self.record(&card.to_string(), &Command::Card(card))?;
+        }
+        Ok(card)
+    }
+    /// Draws a uniformly random card like [`Prompt::deal`], without recording it
+    fn draw(&mut self, seen: &[PlayingCard]) -> Option<PlayingCard> {
+        self.deck
+            .events(seen)
+            .choose_weighted(&mut self.rng, |&(_, weight)| weight)
+            .ok()
+            .map(|&(card, _)| card)
+    }
+    /// Starts recording the session to `path` (see [`Session`]), dealing with `seed`
+    ///
+    /// The recording starts with the decisions taken in the current game, so that
+    /// replaying it starts in the same state
+    fn start_recording(&mut self, path: &Path, seed: u64, taken: &[Taken]) -> io::Result<()> {
+        let mut session = Session::create(path, seed)?;
+        session.input("reset")?;
+        for taken in taken {
+            session.input(&taken.to_string())?;
+        }
+        self.session = Some(session);
+        Ok(())
+    }
+    fn stop_recording(&mut self) {
+        self.session = None;
+    }
+    /// Queues the commands in `path` to be read before any more from stdin
+    ///
+    /// Returns the number of commands queued
+    fn start_replay(&mut self, path: &Path) -> io::Result<usize> {
+        // session logs replay only the lines entered, see [`session::command`]
+        let lines = io::BufReader::new(File::open(path)?)
+            .lines()
+            .filter_map(|line| line.map(|line| session::command(&line)).transpose())
+            .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .collect::<io::Result<Vec<_>>>()?;
+        // replayed commands run right away, even when replayed from a replay
+        for line in lines.iter().rev() {
+            self.replay.push_front(line.clone());
+        }
+        Ok(lines.len())
+    }
 }
 
 fn print_help() {
-    println!("\n[Commands]");
-    println!("help = This command");
-    println!("exit = Quit the program");
-    println!("list = Prints the choices and the expected values");
-    println!("list {{choice_name|'optimal'}} = Prints the random events associated with a choice");
-    println!("reset = Start over (new game)");
-    println!("back = Go back to previous choice (useful if you input the wrong card)");
-    println!("{{card}} = Input a card (your choice can be interpreted)");
-
-    println!("\n[Card Format]");
-    println!(
-        "Card formats are pretty simple. It's the rank (number or letter) of the card, plus the suit, case insensitive"
-    );
-    println!("Examples:");
-    println!("2H  = 2 of hearts");
-    println!("10C = 10 of clubs");
-    println!("QD  = Queen of diamonds");
-    println!("AS  = Ace of spades");
+    let text = lang::text();
+    println!("\n[{}]", text.headings[0]);
+    for (usage, description) in lang::COMMAND_USAGES.iter().zip(text.commands) {
+        println!("{} = {}", usage, description);
+    }
+    for note in text.command_notes {
+        println!("{}", note);
+    }
 
-    println!("\n[Tutorial]");
-    println!(
-        "This tool is intended to be used while playing Ride The Bus (a fictional casino game) in Schedule I"
-    );
-    println!(
-        "1. You are shown a series of Choices and their Expected Values. In Schedule I, choose the option with the highest indicated expected value here"
-    );
-    println!(
-        "2. Once your option is selected, a dealer then places another card in front of you. Input the shown card here using the CLI"
-    );
-    println!(
-        "3. After inputting your card, your choice is automatically interpreted and a new series of choices is shown"
-    );
-    println!("4. Repeat Step 1-3 until you either lose or cashout, then restart with '? reset'")
+    println!("\n[{}]", text.headings[1]);
+    print!("{}", Cli::command().render_help());
+
+    println!("\n[{}]", text.headings[2]);
+    println!("{}", text.strategy_format);
+    println!("{}", text.examples);
+    for (example, description) in lang::STRATEGY_EXAMPLES.iter().zip(text.strategies) {
+        println!("{:<15} = {}", example, description);
+    }
+
+    println!("\n[{}]", text.headings[3]);
+    println!("{}", text.card_format);
+    println!("{}", text.examples);
+    for (example, description) in lang::CARD_EXAMPLES.iter().zip(text.cards) {
+        println!("{:<3} = {}", example, description);
+    }
+
+    println!("\n[{}]", text.headings[4]);
+    for line in text.tutorial {
+        println!("{}", line);
+    }
 }
-fn print_choices(tree: &DiscreteDecisionTree) {
-    println!("[Choices]");
-    println!("# Choice = Expected Value");
-    // get the EV for the optimal choice, used to show an arrow to the best choices (ones equalling this EV)
-    let optimal_ev = tree.optimal().map(|x| x.expected_value).unwrap_or(0.0);
-    for choice in tree.iter() {
-        print!("{:?} = {:.04}", choice.choice, choice.expected_value);
-        if choice.expected_value >= (optimal_ev - 1e-6) {
-            println!(" <----");
-        } else {
-            println!();
+fn print_choices(tree: &DiscreteDecisionTree, options: &Options, format: Format, bet: Option<f64>) {
+    // the column for the objective, unless it's the EV
+    let objective_label = match tree.objective() {
+        Objective::ExpectedValue => None,
+        Objective::TargetProbability(target) => Some(format!("P(pot >= {:.02}x)", target)),
+        Objective::Utility(_) => Some("Certainty Equivalent".to_owned()),
+    };
+    let objective_value = |choice: &ChoiceEval| match tree.objective() {
+        Objective::ExpectedValue => None,
+        Objective::TargetProbability(_) => Some(choice.objective_value),
+        Objective::Utility(utility) => Some(utility.certainty_equivalent(choice.objective_value)),
+    };
+    let render_objective = |choice: &ChoiceEval| match tree.objective() {
+        Objective::TargetProbability(_) => objective_value(choice).map(render::probability),
+        _ => objective_value(choice).map(render::ev),
+    };
+    let side_bet_note = tree.side_bet().map(|side_bet| {
+        format!(
+            "Values include a {:.02}x side bet on the card of stage {} being {} (pays {:.02}x), its return is shown separately",
+            side_bet.stake,
+            side_bet.card + 1,
+            target_label(side_bet.target),
+            side_bet.payout
+        )
+    });
+    // get the EV of the choice with the best EV, used to point it out in case it's not optimal
+    // (and to scale the bars against)
+    let ev_optimal = tree.ev_optimal().map(|x| x.expected_value).unwrap_or(0.0);
+    // listed in the order of the decision, so choices stay in place from one to the next
+    let mut ranked_choices = tree.ranked_choices();
+    ranked_choices.sort_by_key(|ranked| tree.iter().position(|c| std::ptr::eq(c, ranked.choice)));
+    let is_best_ev = |ranked: &RankedChoice| {
+        objective_label.is_some()
+            && !ranked.is_optimal
+            && ranked.choice.expected_value >= (ev_optimal - 1e-6)
+    };
+    let reach = |choice: &ChoiceEval| {
+        choice
+            .reach_probabilities()
+            .iter()
+            .map(|&probability| render::probability(probability))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut notes = Vec::new();
+    if let Some(jackpot) = tree.jackpot() {
+        notes.push(format!(
+            "Values include the {:.02}x jackpot for winning with a final card of {}",
+            jackpot.amount,
+            target_label(jackpot.target)
+        ));
+    }
+    // show when the optimal choice only won on a secondary criterion
+    let decided_by = match tree.optimal_criterion() {
+        Some(Criterion::Variance) => Some("lower variance"),
+        Some(Criterion::BustProbability) => Some("lower bust probability"),
+        Some(Criterion::ExpectedValue) if objective_label.is_some() => {
+            Some("higher expected value")
+        }
+        _ => None,
+    };
+    if let (Some(optimal), Some(decided_by)) = (tree.optimal(), decided_by) {
+        notes.push(format!(
+            "{:?} is a tie, decided by {}",
+            optimal.choice, decided_by
+        ));
+    }
+    // point out whether choices that don't reveal a card (like insurance), or that
+    // redraw the card revealed (like a swap), are worth taking
+    for ranked in ranked_choices.iter().filter(|ranked| {
+        let choice = &ranked.choice.choice;
+        !choice.reveals_card() && !choice.ends_stage()
+            || ["Swap", "Mulligan"].contains(&choice.name())
+    }) {
+        notes.push(match ranked.is_optimal {
+            true => format!("{:?} is worth taking here", ranked.choice.choice),
+            false => format!(
+                "{:?} isn't worth taking here ({} EV short of the best)",
+                ranked.choice.choice,
+                render::value(ranked.ev_gap)
+            ),
+        });
+    }
+    match format {
+        Format::Text => {
+            println!("[Choices]");
+            let side_header = side_bet_note.as_ref().map_or("", |_| " | Side Bet");
+            match &objective_label {
+                None => println!(
+                    "# Choice = Expected Value{} | Regret | Std Dev | P(win) | P(reach next stages)",
+                    side_header
+                ),
+                Some(label) => println!(
+                    "# Choice = Expected Value{} | Regret | Std Dev | {} | P(win) | P(reach next stages)",
+                    side_header, label
+                ),
+            }
+            if let Some(note) = &side_bet_note {
+                println!("# {}", note);
+            }
+            for ranked in &ranked_choices {
+                let choice = ranked.choice;
+                print!(
+                    "{:?} = {}",
+                    choice.choice,
+                    with_amount(choice.expected_value, bet)
+                );
+                if options.bars {
+                    // the bars are relative to the optimal EV, which there's nothing to
+                    // draw relative to when no choice wins anything
+                    let fraction = match ev_optimal > 0.0 {
+                        true => choice.expected_value / ev_optimal,
+                        false => 0.0,
+                    };
+                    print!(" {}", render::bar(fraction));
+                }
+                if side_bet_note.is_some() {
+                    print!(" | side {}", render::value(choice.side_value));
+                }
+                print!(" | regret {}", render::value(choice.regret()));
+                print!(" | sd {}", render::value(choice.std_dev()));
+                if let Some(value) = render_objective(choice) {
+                    print!(" | {}", value);
+                }
+                let win_probability = choice.win_probability();
+                print!(" | win {}", render::probability(win_probability));
+                if options.bars {
+                    print!(" {}", render::bar(win_probability));
+                }
+                if !choice.reach_probabilities().is_empty() {
+                    print!(" | reach {}", reach(choice));
+                }
+                if ranked.is_optimal {
+                    print!(" {}", render::optimal_marker());
+                }
+                if is_best_ev(ranked) {
+                    print!(" (best EV)");
+                }
+                println!();
+            }
+        }
+        Format::Markdown => {
+            if let Some(note) = &side_bet_note {
+                println!("{}\n", note);
+            }
+            let objective_header = objective_label
+                .as_ref()
+                .map(|label| format!(" {} |", label))
+                .unwrap_or_default();
+            let objective_align = if objective_label.is_some() {
+                " ---: |"
+            } else {
+                ""
+            };
+            let (side_header, side_align) = match side_bet_note {
+                Some(_) => (" Side Bet |", " ---: |"),
+                None => ("", ""),
+            };
+            println!(
+                "| Choice | Expected Value |{} Regret | Std Dev |{} P(win) | P(reach next stages) | |",
+                side_header, objective_header
+            );
+            println!(
+                "| --- | ---: |{} ---: | ---: |{} ---: | --- | --- |",
+                side_align, objective_align
+            );
+            for ranked in &ranked_choices {
+                let choice = ranked.choice;
+                print!(
+                    "| {:?} | {} |",
+                    choice.choice,
+                    with_amount(choice.expected_value, bet)
+                );
+                if side_bet_note.is_some() {
+                    print!(" {} |", render::value(choice.side_value));
+                }
+                print!(
+                    " {} | {} |",
+                    render::value(choice.regret()),
+                    render::value(choice.std_dev())
+                );
+                if let Some(value) = render_objective(choice) {
+                    print!(" {} |", value);
+                }
+                let marker = match (ranked.is_optimal, is_best_ev(ranked)) {
+                    (true, _) => "optimal",
+                    (false, true) => "best EV",
+                    (false, false) => "",
+                };
+                println!(
+                    " {} | {} | {} |",
+                    render::probability(choice.win_probability()),
+                    reach(choice),
+                    marker
+                );
+            }
+        }
+        Format::Json => {
+            let choices = ranked_choices
+                .iter()
+                .map(|ranked| {
+                    let choice = ranked.choice;
+                    serde_json::json!({
+                        "choice": format!("{:?}", choice.choice),
+                        "expected_value": choice.expected_value,
+                        "expected_amount": bet.map(|bet| choice.expected_value * bet),
+                        "side_bet_value": side_bet_note.as_ref().map(|_| choice.side_value),
+                        "regret": choice.regret(),
+                        "std_dev": choice.std_dev(),
+                        "objective_value": objective_value(choice),
+                        "win_probability": choice.win_probability(),
+                        "reach_probabilities": choice.reach_probabilities(),
+                        "optimal": ranked.is_optimal,
+                        "best_ev": is_best_ev(ranked),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let json = serde_json::json!({
+                "choices": choices,
+                "side_bet": side_bet_note,
+                "notes": notes,
+            });
+            println!("{}", json);
         }
     }
+
+    for note in notes {
+        match format {
+            Format::Text => println!("# {}", note),
+            Format::Markdown => println!("\n{}", note),
+            Format::Json => {}
+        }
+    }
+    // always the same sentence, so a screen reader user knows where to listen
+    if let (true, Format::Text, Some(optimal)) = (render::accessible(), format, tree.optimal()) {
+        let names = ranked_choices
+            .iter()
+            .filter(|ranked| ranked.is_optimal)
+            .map(|ranked| format!("{:?}", ranked.choice.choice))
+            .collect::<Vec<_>>();
+        let text = lang::text();
+        println!(
+            "{}",
+            lang::fill(
+                text.optimal_sentence,
+                &[
+                    &names.join(&format!(" {} ", text.or)),
+                    &render::ev(optimal.expected_value)
+                ]
+            )
+        );
+    }
+}
+/// Prints the decision as a line of JSON, for programs reading the output (see
+/// [`Options::machine`])
+fn print_decision(
+    tree: &DiscreteDecisionTree,
+    cards: &[PlayingCard],
+    stage: usize,
+    stages: usize,
+    bet: f64,
+) {
+    println!("{}", decision_json(tree, cards, stage, stages, bet));
+}
+/// The decision as JSON, with its stage, cards, pot, and ranked choices
+fn decision_json(
+    tree: &DiscreteDecisionTree,
+    cards: &[PlayingCard],
+    stage: usize,
+    stages: usize,
+    bet: f64,
+) -> serde_json::Value {
+    let choices = tree
+        .ranked_choices()
+        .iter()
+        .map(|ranked| {
+            serde_json::json!({
+                "choice": format!("{:?}", ranked.choice.choice),
+                "expected_value": ranked.choice.expected_value,
+                "win_probability": ranked.choice.win_probability(),
+                "optimal": ranked.is_optimal,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "stage": stage,
+        "stages": stages,
+        "cards": cards.iter().map(|card| card.to_string()).collect::<Vec<_>>(),
+        "pot": tree.pot(),
+        "bet": bet,
+        "optimal": tree.optimal().map(|optimal| format!("{:?}", optimal.choice)),
+        "choices": choices,
+    })
+}
+/// Prints only the optimal choice, for `--quiet`
+fn print_optimal(tree: &DiscreteDecisionTree, format: Format) {
+    let Some(optimal) = tree.optimal() else {
+        return;
+    };
+    match format {
+        Format::Json => println!(
+            "{}",
+            serde_json::json!({
+                "optimal": format!("{:?}", optimal.choice),
+                "expected_value": optimal.expected_value,
+            })
+        ),
+        _ => println!("{:?}", optimal.choice),
+    }
+}
+/// Prints the cards each choice wins on and the payouts of the optimal choice, for `--verbose`
+fn print_breakdown(tree: &DiscreteDecisionTree, format: Format, bet: Option<f64>) {
+    for choice in tree.iter().filter(|c| c.choice.reveals_card()) {
+        let name = format!("{:?}", choice.choice);
+        print_events(tree, &name, format, bet, &EventFilter::default());
+    }
+    print_payouts(tree, "optimal", format, bet);
 }
-fn print_events(tree: &DiscreteDecisionTree, choice_name: &str) {
+fn print_events(
+    tree: &DiscreteDecisionTree,
+    choice_name: &str,
+    format: Format,
+    bet: Option<f64>,
+    filter: &EventFilter,
+) {
     // find an option to the target to enumerate for this command
     let list_target = match choice_name.to_lowercase().as_str() {
         "optimal" => tree.optimal(),
@@ -197,86 +1800,2546 @@ fn print_events(tree: &DiscreteDecisionTree, choice_name: &str) {
             .find(|ec| format!("{:?}", ec.choice).to_lowercase() == name),
     };
     // either print the cards and their expected values, or say its an invalid target
-    if let Some(target) = list_target {
-        println!("[{:?}]", target.choice);
-        println!("# REvent = Expected Value");
-        for outcome in target.iter() {
-            // only print cards that are winners (EV>0)
-            if outcome.value > 1e-6 {
-                println!("{} = {:.04}", outcome.event, outcome.value);
-            }
+    let Some(target) = list_target else {
+        println!("invalid list target");
+        return;
+    };
+    let mut outcomes = target
+        .iter_probabilities()
+        .filter(|(_, outcome)| filter.matches(outcome.event))
+        .collect::<Vec<_>>();
+    if filter.by_ev {
+        // stable, so cards with the same EV stay in the order of the deck
+        outcomes.sort_by(|(_, o1), (_, o2)| f64::total_cmp(&o2.value, &o1.value));
+    }
+    match format {
+        Format::Text => {
+            println!("[{:?}]", target.choice);
+            println!("# REvent = Expected Value");
+        }
+        Format::Json => {
+            // every event, since scripts can filter out the losing ones themselves
+            let events = outcomes
+                .iter()
+                .map(|(probability, outcome)| {
+                    serde_json::json!({
+                        "event": outcome.event.map(|event| event.to_string()),
+                        "value": outcome.value,
+                        "amount": bet.map(|bet| outcome.value * bet),
+                        "probability": probability,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let json = serde_json::json!({
+                "choice": format!("{:?}", target.choice),
+                "events": events,
+            });
+            println!("{}", json);
+            return;
+        }
+        Format::Markdown => {
+            println!("| {:?} | Expected Value |", target.choice);
+            println!("| --- | ---: |");
         }
-    } else {
-        println!("invalid list target")
     }
-}
-fn interactive_prompt(tree: &DiscreteDecisionTree) {
-    let mut history = vec![tree];
-    'outer: loop {
-        // get the current decision tree and print the choices available to the user
-        let tree = history.last().expect("non-empty history");
-        print_choices(tree);
-
-        // find the next card from user input (service the CLI prompt)
-        let next_card = loop {
-            let cmd = Command::from_stdin().expect("stdin command");
-            match cmd {
-                Command::Help => print_help(),
-                Command::Exit => std::process::exit(0),
-                Command::ListChoices => print_choices(tree),
-                Command::ListEvents(choice_name) => print_events(tree, &choice_name),
-                Command::Reset => return, // reset to root tree
-                Command::Back => {
-                    // remove the last taken decision, then restart interaction
-                    history.remove(history.len() - 1);
-                    continue 'outer;
-                }
-                Command::Card(card) => break card, // break out with provided card to enter new tree
-            }
+    for (_, outcome) in outcomes {
+        // only print cards that are winners (EV>0), unless all of them are asked for
+        let event = match outcome.event {
+            Some(event) if outcome.value > 1e-6 || filter.all => render::card_name(event),
+            None => "(no card)".to_owned(),
+            _ => continue,
         };
-
-        // find the choice the user made by finding the max EV
-        // we can do this because the decisions are disjoint (except Cashout, which is always smaller), i.e.
-        // a card can only succeed with one decision
-        let choice = tree.iter().max_by(|c1, c2| {
-            let ev1 = c1.get(next_card).map(|o| o.value).unwrap_or(0.0);
-            let ev2 = c2.get(next_card).map(|o| o.value).unwrap_or(0.0);
-            f64::total_cmp(&ev1, &ev2)
-        });
-
-        // get the next tree from the card provided, or error if it was an invalid card, or reset
-        // if there are no more decisions
-        println!();
-        let find = choice
-            .inspect(|c| println!("??? So you chose {:?} ???", c.choice))
-            .and_then(|c| c.get(next_card));
-        match find.map(|o| o.next_decision()) {
-            Some(Some(next_decision)) => history.push(next_decision),
-            Some(None) => break 'outer, // no next_decision
-            None => println!("!!! INVALID CARD PROVIDED !!!"),
+        match format {
+            Format::Markdown => println!("| {} | {} |", event, with_amount(outcome.value, bet)),
+            _ => println!("{} = {}", event, with_amount(outcome.value, bet)),
         }
     }
-    println!("no more decisions, resetting");
 }
-fn main() {
-    let first_decision = DiscreteDecision::new_with_cashout([PickColor::Red, PickColor::Black]);
-
-    // solve ride the bus
-    // this only takes a about a second, hence why it's fine we do this on every start instead of memoizing
-    println!("solving ride the bus");
-    let start = Instant::now();
-    let tree = DiscreteDecisionTree::solve(first_decision);
-    println!(
-        "analyzed {} games in {:.04?}",
-        tree.outcome_count(),
-        start.elapsed()
+fn print_payouts(tree: &DiscreteDecisionTree, choice_name: &str, format: Format, bet: Option<f64>) {
+    let target = match choice_name.to_lowercase().as_str() {
+        "optimal" => tree.optimal(),
+        name => tree
+            .iter()
+            .find(|ec| format!("{:?}", ec.choice).to_lowercase() == name),
+    };
+    let Some(target) = target else {
+        println!("invalid payouts target");
+        return;
+    };
+    if format == Format::Json {
+        let payouts = target
+            .payout_distribution()
+            .into_iter()
+            .map(|(pot, probability)| {
+                serde_json::json!({
+                    "pot": pot,
+                    "amount": bet.map(|bet| pot * bet),
+                    "probability": probability,
+                })
+            })
+            .collect::<Vec<_>>();
+        let json = serde_json::json!({
+            "choice": format!("{:?}", target.choice),
+            "payouts": payouts,
+        });
+        println!("{}", json);
+        return;
+    }
+    println!("[Payouts of {:?}]", target.choice);
+    println!("# Final pot = Probability");
+    for (pot, probability) in target.payout_distribution() {
+        let line = match bet {
+            Some(bet) => format!(
+                "{}x ({:.02}) = {}",
+                render::value(pot),
+                pot * bet,
+                render::small_probability(probability)
+            ),
+            None => format!(
+                "{}x = {}",
+                render::value(pot),
+                render::small_probability(probability)
+            ),
+        };
+        match pot < 1.0 {
+            true => println!("{}", render::bad(&line)),
+            false => println!("{}", line),
+        }
+    }
+}
+/// Prints how the EV of a choice adds up: the chance of a card it wins on, times the
+/// average value after winning (the pot won plus whatever playing on from there is
+/// worth), plus the chance of losing times what's refunded or returned by a side bet
+///
+/// When solved for another objective, its value is broken down the same way
+fn print_why(tree: &DiscreteDecisionTree, choice_name: &str, format: Format) {
+    let target = match choice_name.to_lowercase().as_str() {
+        "optimal" => tree.optimal(),
+        name => tree.choice(name),
+    };
+    let Some(target) = target else {
+        println!("invalid why target");
+        return;
+    };
+    let objective = tree.objective();
+    // the value of an outcome for the objective, following the optimal choices after it
+    let objective_of = |outcome: &RandomEventOutcome| match outcome
+        .next_decision()
+        .and_then(|next| next.optimal())
+    {
+        Some(next) => next.objective_value,
+        None => objective.terminal_value(outcome.value),
+    };
+    let (mut cards, mut won, mut cashouts, mut ends) = (0, 0, 0, 0);
+    let (mut win_probability, mut value_sum, mut pot_sum) = (0.0, 0.0, 0.0);
+    let (mut lost_sum, mut objective_won_sum, mut objective_lost_sum) = (0.0, 0.0, 0.0);
+    // the pots after winning, which only differ with a jackpot or side bet
+    let mut pots = Vec::<f64>::new();
+    for (probability, outcome) in target.iter_probabilities() {
+        cards += 1;
+        if outcome.is_lost() {
+            lost_sum += probability * outcome.value;
+            objective_lost_sum += probability * objective_of(outcome);
+            continue;
+        }
+        won += 1;
+        win_probability += probability;
+        value_sum += probability * outcome.value;
+        objective_won_sum += probability * objective_of(outcome);
+        let pot = match outcome.next_decision() {
+            Some(next) => {
+                if next.optimal().is_some_and(|o| o.choice.is_cashout()) {
+                    cashouts += 1;
+                }
+                next.pot()
+            }
+            None => {
+                ends += 1;
+                outcome.value
+            }
+        };
+        pot_sum += probability * pot;
+        if !pots.iter().any(|&p| (p - pot).abs() < 1e-9) {
+            pots.push(pot);
+        }
+    }
+    let lose_probability = 1.0 - win_probability;
+    let average = |sum: f64, probability: f64| match probability > 1e-12 {
+        true => sum / probability,
+        false => 0.0,
+    };
+    let (value, pot) = (
+        average(value_sum, win_probability),
+        average(pot_sum, win_probability),
+    );
+    let lost = average(lost_sum, lose_probability);
+    if format == Format::Json {
+        let json = serde_json::json!({
+            "choice": format!("{:?}", target.choice),
+            "pot": tree.pot(),
+            "cards": cards,
+            "winning_cards": won,
+            "win_probability": win_probability,
+            "pots_won": pots,
+            "value_after_winning": value,
+            "value_after_losing": lost,
+            "cashouts_after_winning": cashouts,
+            "expected_value": target.expected_value,
+            "objective_value": target.objective_value,
+        });
+        println!("{}", json);
+        return;
+    }
+    println!("[Why {:?}]", target.choice);
+    if target.choice.is_cashout() {
+        println!(
+            "{:?} ends the game with the pot now, so its EV is the pot = {}",
+            target.choice,
+            render::ev(target.expected_value)
+        );
+        return;
+    }
+    if !target.choice.reveals_card() {
+        println!(
+            "{:?} doesn't reveal a card, so its EV is what the pot is worth after it = {}",
+            target.choice,
+            render::ev(target.expected_value)
+        );
+        return;
+    }
+    println!("pot now = {}x", render::value(tree.pot()));
+    println!(
+        "cards it wins on = {} of {} (P(win) {})",
+        won,
+        cards,
+        render::probability(win_probability)
+    );
+    let pots = pots
+        .iter()
+        .map(|&pot| format!("{}x", render::value(pot)))
+        .collect::<Vec<_>>();
+    println!("pot after winning = {}", pots.join(", "));
+    let after = match ends == won {
+        true => "the game ends after winning".to_owned(),
+        false => format!(
+            "+{}x by playing on, cashing out is optimal after {} of the {} cards",
+            render::value(value - pot),
+            cashouts,
+            won - ends
+        ),
+    };
+    println!(
+        "average value after winning = {}x ({})",
+        render::value(value),
+        after
     );
-    println!("all games considered, done!");
+    // the terms are multiples of the bet, which only add up to the EV as multipliers
+    let mut terms = format!(
+        "{} x {}x",
+        render::probability(win_probability),
+        render::value(value)
+    );
+    if lost.abs() > 1e-9 {
+        println!(
+            "average value after losing = {}x (refunded, or returned by the side bet)",
+            render::value(lost)
+        );
+        terms += &format!(
+            " + {} x {}x",
+            render::probability(lose_probability),
+            render::value(lost)
+        );
+    }
+    println!("EV = {} = {}", terms, render::ev(target.expected_value));
+
+    // the optimal choice is the one with the best value for the objective instead
+    let (objective_won, objective_lost) = (
+        average(objective_won_sum, win_probability),
+        average(objective_lost_sum, lose_probability),
+    );
+    let objective_terms = |render_value: fn(f64) -> String| {
+        format!(
+            "{} x {} + {} x {}",
+            render::probability(win_probability),
+            render_value(objective_won),
+            render::probability(lose_probability),
+            render_value(objective_lost)
+        )
+    };
+    match objective {
+        Objective::ExpectedValue => {}
+        Objective::TargetProbability(target_pot) => println!(
+            "P(pot >= {:.02}x) = {} = {}, which the optimal choice maximizes",
+            target_pot,
+            objective_terms(render::probability),
+            render::probability(target.objective_value)
+        ),
+        Objective::Utility(utility) => println!(
+            "expected utility = {} = {} (certainty equivalent {}), which the optimal choice maximizes",
+            objective_terms(render::value),
+            render::value(target.objective_value),
+            render::ev(utility.certainty_equivalent(target.objective_value))
+        ),
+    }
+}
+/// Formats a multiple of the bet, along with the amount it comes to for a `bet`
+fn with_amount(value: f64, bet: Option<f64>) -> String {
+    match bet {
+        Some(bet) => format!("{} ({:.02})", render::ev(value), value * bet),
+        None => render::ev(value),
+    }
+}
+fn print_stages(tree: &DiscreteDecisionTree, format: Format) {
+    let stages = export::stage_values(tree);
+    if format == Format::Json {
+        let stages = stages
+            .iter()
+            .enumerate()
+            .map(|(i, stage)| {
+                serde_json::json!({
+                    "stage": i + 1,
+                    "reach_probability": stage.reach_probability,
+                    "expected_value": stage.expected_value,
+                    "survived_value": stage.survived_value,
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::json!({ "stages": stages }));
+        return;
+    }
+    println!("[Stages]");
+    println!("# Stage = P(reach) | Expected Value | Expected Value if survived (gain)");
+    for (i, stage) in stages.iter().enumerate() {
+        print!(
+            "{} = {} | {}",
+            i + 1,
+            render::probability(stage.reach_probability),
+            render::ev(stage.expected_value)
+        );
+        match stage.survived_value {
+            Some(survived) => println!(
+                " | {} ({})",
+                render::ev(survived),
+                render::gain(survived - stage.expected_value)
+            ),
+            None => println!(" | - (always cashed out)"),
+        }
+    }
+}
+fn print_bust(tree: &DiscreteDecisionTree, stage: usize, format: Format) {
+    let Some(optimal) = tree.optimal() else {
+        println!("no choices to bust on");
+        return;
+    };
+    let stages = export::stage_values(tree);
+    // the chance of busting at each stage once it's reached, which isn't known for
+    // stages that can't be reached
+    let reached = stages
+        .iter()
+        .map(|value| {
+            (value.reach_probability > 0.0)
+                .then(|| value.bust_probability / value.reach_probability)
+        })
+        .collect::<Vec<_>>();
+    // the chance of having busted by the end of each stage
+    let cumulative = stages
+        .iter()
+        .scan(0.0, |busted, value| {
+            *busted += value.bust_probability;
+            Some(*busted)
+        })
+        .collect::<Vec<_>>();
+    if format == Format::Json {
+        let stages = stages
+            .iter()
+            .zip(&cumulative)
+            .zip(&reached)
+            .enumerate()
+            .map(|(i, ((value, cumulative), reached))| {
+                serde_json::json!({
+                    "stage": stage + i,
+                    "bust_probability": value.bust_probability,
+                    "reached_bust_probability": reached,
+                    "cumulative_bust_probability": cumulative,
+                })
+            })
+            .collect::<Vec<_>>();
+        let json = serde_json::json!({
+            "stages": stages,
+            "bust_probability": optimal.bust_probability,
+        });
+        println!("{}", json);
+        return;
+    }
+    println!("[Bust]");
+    println!("# Stage = P(bust at stage) | P(bust if reached) | P(busted by end of stage)");
+    for (i, ((value, cumulative), reached)) in
+        stages.iter().zip(&cumulative).zip(&reached).enumerate()
+    {
+        print!(
+            "{} = {} | {} | {}",
+            stage + i,
+            render::probability(value.bust_probability),
+            reached.map_or("-".to_owned(), render::probability),
+            render::probability(*cumulative)
+        );
+        match i {
+            0 => println!(" (this stage)"),
+            1 => println!(" (next stage)"),
+            _ => println!(),
+        }
+    }
+    println!(
+        "P(bust by the end) = {}",
+        render::probability(optimal.bust_probability)
+    );
+}
+/// The state of the game that matters to a stage's decision, used to collapse
+/// equivalent card histories when exporting the stage table
+///
+/// * Pick Red/Black: nothing has been seen yet
+/// * Pick Higher/Lower: the rank of the first card
+/// * Pick Inside/Outside: the ranks of the two cards, smaller first
+/// * Pick Suit: the suits seen so far
+fn stage_state(history: &[PlayingCard]) -> String {
+    match history {
+        [] => "-".to_owned(),
+        [c1] => c1.rank_label().to_owned(),
+        [c2, c1] => {
+            let (lo, hi) = if c1.rank() <= c2.rank() {
+                (c1, c2)
+            } else {
+                (c2, c1)
+            };
+            format!("{}-{}", lo.rank_label(), hi.rank_label())
+        }
+        _ => {
+            let mut suits = history.iter().map(|c| c.suit_label()).collect::<Vec<_>>();
+            suits.sort();
+            suits.concat()
+        }
+    }
+}
+/// The state of a decision of a game file, which is every card kept in the order
+/// they were dealt, since its picks can depend on anything about them
+fn seen_state(history: &[PlayingCard]) -> String {
+    match history {
+        [] => "-".to_owned(),
+        _ => {
+            let cards = history.iter().rev().map(|c| c.to_string());
+            cards.collect::<Vec<_>>().join(" ")
+        }
+    }
+}
+/// How decisions are collapsed into states for the strategy of the game, where
+/// only the picks of Ride The Bus are known to depend on less than every card
+fn state_of(options: &Options) -> fn(&[PlayingCard]) -> String {
+    match options.game {
+        Some(_) => seen_state,
+        None => stage_state,
+    }
+}
+fn print_strategy(tree: &DiscreteDecisionTree, options: &Options) {
+    let format = options.format;
+    let table = StrategyTable::of(tree, state_of(options));
+    if format == Format::Json {
+        match serde_json::to_string(&table) {
+            Ok(json) => println!("{}", json),
+            Err(err) => println!("failed to print strategy: {}", err),
+        }
+        return;
+    }
+    println!("[Strategy]");
+    println!("# Stage State = Optimal Choice");
+    for entry in table.entries {
+        println!("{} {} = {}", entry.stage, entry.state, entry.choice);
+    }
+}
+fn export_strategy(tree: &DiscreteDecisionTree, path: &Path, options: &Options) {
+    let table = StrategyTable::of(tree, state_of(options));
+    let written = File::create(path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        match path.extension().is_some_and(|ext| ext == "json") {
+            true => serde_json::to_writer_pretty(&mut writer, &table).map_err(io::Error::other)?,
+            false => table.write_csv(&mut writer)?,
+        }
+        writer.flush()
+    });
+    match written {
+        Ok(()) => println!(
+            "exported {} stage states to {}",
+            table.entries.len(),
+            path.display()
+        ),
+        Err(err) => println!("failed to export strategy: {}", err),
+    }
+}
+fn export_report(tree: &DiscreteDecisionTree, path: &Path, options: &Options) {
+    match report::write_report(tree, path, state_of(options)) {
+        Ok(()) => println!("exported report to {}", path.display()),
+        Err(err) => println!("failed to export report: {}", err),
+    }
+}
+fn export_stages(tree: &DiscreteDecisionTree, path: &Path, options: &Options) {
+    match export::write_stage_table(tree, path, state_of(options)) {
+        Ok(rows) => println!("exported {} stage states to {}", rows, path.display()),
+        Err(err) => println!("failed to export stages: {}", err),
+    }
+}
+/// A one line summary of where the user is in the game
+fn status_line(
+    tree: &DiscreteDecisionTree,
+    cards: &[PlayingCard],
+    stage: usize,
+    stages: usize,
+    bet: Option<f64>,
+) -> String {
+    let [stage_label, pot, ev, no_cashout] = lang::text().status;
+    let mut parts = vec![
+        format!("{} {}/{}", stage_label, stage, stages),
+        match bet {
+            Some(bet) => format!("{} {:.02}x ({:.02})", pot, tree.pot(), tree.pot() * bet),
+            None => format!("{} {:.02}x", pot, tree.pot()),
+        },
+    ];
+    // what the pot is worth from here with optimal play, unlike the pot (what cashing
+    // out gives)
+    if let Some(optimal) = tree.optimal() {
+        parts.push(format!("{} {}", ev, render::ev(optimal.expected_value)));
+    }
+    if !cards.is_empty() {
+        parts.push(render::cards(cards));
+    }
+    // cashing out (for the pot) isn't allowed after doubling down, or at some stages of game files
+    if !tree.iter().any(|c| c.choice.is_cashout()) {
+        parts.push(no_cashout.to_owned());
+    }
+    format!("[{}]", parts.join(" | "))
+}
+fn export_charts(tree: &DiscreteDecisionTree, dir: &Path) {
+    match chart::write_charts(tree, dir) {
+        Ok(paths) => {
+            for path in paths {
+                println!("exported chart to {}", path.display());
+            }
+        }
+        Err(err) => println!("failed to export charts: {}", err),
+    }
+}
+fn export_outcomes(tree: &DiscreteDecisionTree, path: &Path, seen: &[PlayingCard], all: bool) {
+    match export::write_outcome_table(tree, path, seen, all) {
+        Ok(rows) => println!("exported {} outcomes to {}", rows, path.display()),
+        Err(err) => println!("failed to export outcomes: {}", err),
+    }
+}
+fn export_dot(tree: &DiscreteDecisionTree, path: &Path, depth: usize) {
+    match export::write_dot(tree, path, depth) {
+        Ok(nodes) => println!("exported {} nodes to {}", nodes, path.display()),
+        Err(err) => println!("failed to export graph: {}", err),
+    }
+}
+fn print_comparison(strategies: &[(String, SolveOptions); 2], options: &Options) {
+    println!("solving for {} and {}", strategies[0].0, strategies[1].0);
+    let [tree1, tree2] = strategies
+        .each_ref()
+        .map(|(_, solve_options)| solve(*solve_options, options));
+    let (m1, m2) = (compare::Metrics::of(&tree1), compare::Metrics::of(&tree2));
+
+    println!("[Comparison]");
+    println!("# Metric = {} | {}", strategies[0].0, strategies[1].0);
+    let print_row = |metric: &str, v1: String, v2: String| println!("{metric} = {v1} | {v2}");
+    print_row(
+        "Expected Value",
+        render::ev(m1.expected_value),
+        render::ev(m2.expected_value),
+    );
+    print_row(
+        "Bust Probability",
+        render::probability(m1.bust_probability),
+        render::probability(m2.bust_probability),
+    );
+    print_row(
+        &format!("P({:.02}x)", m1.max_payout),
+        render::probability(m1.max_payout_probability),
+        render::probability(m2.max_payout_probability),
+    );
+    let stages = usize::max(
+        m1.cashout_probabilities.len(),
+        m2.cashout_probabilities.len(),
+    );
+    for stage in 0..stages {
+        let cashout = |m: &compare::Metrics| {
+            render::probability(m.cashout_probabilities.get(stage).copied().unwrap_or(0.0))
+        };
+        print_row(
+            &format!("P(Cashout at stage {})", stage + 1),
+            cashout(&m1),
+            cashout(&m2),
+        );
+    }
+
+    let differences = compare::differences(&tree1, &tree2, state_of(options));
+    println!("\n[Differences]");
+    println!("# Stage State = {} | {}", strategies[0].0, strategies[1].0);
+    for difference in &differences {
+        println!(
+            "{} {} = {} | {}",
+            difference.stage, difference.state, difference.choices.0, difference.choices.1
+        );
+    }
+    println!("{} states recommend different choices", differences.len());
+}
+/// Picks a choice for a decision, see [`compare::policy_payouts`]
+type Policy =
+    dyn for<'a> Fn(&'a DiscreteDecisionTree, usize, &[PlayingCard]) -> Option<&'a ChoiceEval>;
+fn policy<F>(f: F) -> Box<Policy>
+where
+    F: for<'a> Fn(&'a DiscreteDecisionTree, usize, &[PlayingCard]) -> Option<&'a ChoiceEval>
+        + 'static,
+{
+    Box::new(f)
+}
+/// The choice with the best EV that keeps playing, i.e. doesn't cash out
+fn play_on(tree: &DiscreteDecisionTree) -> Option<&ChoiceEval> {
+    tree.iter()
+        .filter(|c| !c.choice.is_cashout())
+        .max_by(|c1, c2| f64::total_cmp(&c1.expected_value, &c2.expected_value))
+}
+/// The choice a naive player makes, who never cashes out:
+/// * Red
+/// * Higher if the first card is 8 or lower, otherwise Lower
+/// * Inside if the two cards are at least 7 ranks apart, otherwise Outside
+/// * Hearts
+fn naive_choice<'a>(
+    tree: &'a DiscreteDecisionTree,
+    history: &[PlayingCard],
+) -> Option<&'a ChoiceEval> {
+    let name = match history {
+        [] => "Red",
+        [c1] if c1.rank() <= 8 => "Higher",
+        [_] => "Lower",
+        [c2, c1] if c1.rank().abs_diff(c2.rank()) >= 7 => "Inside",
+        [_, _] => "Outside",
+        _ => "Hearts",
+    };
+    tree.choice(name).or_else(|| play_on(tree))
+}
+fn print_policies(tree: &DiscreteDecisionTree) {
+    let mut policies = vec![
+        ("optimal".to_owned(), policy(|tree, _, _| tree.optimal())),
+        (
+            "never cashout".to_owned(),
+            policy(|tree, _, _| play_on(tree)),
+        ),
+    ];
+    for k in 1..tree.depth() {
+        policies.push((
+            format!("cashout after stage {}", k),
+            policy(move |tree, stage, _| match stage > k {
+                // stages of a game file may not allow cashing out
+                true => tree
+                    .iter()
+                    .find(|c| c.choice.is_cashout())
+                    .or_else(|| play_on(tree)),
+                false => play_on(tree),
+            }),
+        ));
+    }
+    policies.push((
+        "naive".to_owned(),
+        policy(|tree, _, history| naive_choice(tree, history)),
+    ));
+    let payouts = policies
+        .iter()
+        .map(|(_, policy)| compare::policy_payouts(tree, policy))
+        .collect::<Vec<_>>();
 
-    // print the tutorial, then start the interactive loop
-    print_help();
+    let expected_values = payouts
+        .iter()
+        .map(|payouts| {
+            payouts
+                .distribution
+                .iter()
+                .fold(0.0, |sum, (pot, probability)| sum + pot * probability)
+        })
+        .collect::<Vec<_>>();
+    println!("[Policies]");
+    println!("# Policy = Expected Value | Bust Probability | EV lost vs optimal");
+    for (i, (name, _)) in policies.iter().enumerate() {
+        println!(
+            "{} = {} | {} | {}",
+            name,
+            render::ev(expected_values[i]),
+            render::probability(payouts[i].bust_probability),
+            render::value(expected_values[0] - expected_values[i])
+        );
+    }
+
+    // every final pot any policy can end with, in a row each
+    let mut pots = payouts
+        .iter()
+        .flat_map(|payouts| &payouts.distribution)
+        .map(|&(pot, _)| pot)
+        .collect::<Vec<_>>();
+    pots.sort_by(f64::total_cmp);
+    pots.dedup_by(|p1, p2| (*p1 - *p2).abs() < 1e-6);
+    let names = policies
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    println!("\n[Payout Distributions]");
+    println!("# Final pot = {}", names.join(" | "));
+    for pot in pots {
+        let probabilities = payouts
+            .iter()
+            .map(|payouts| {
+                let probability = payouts
+                    .distribution
+                    .iter()
+                    .find(|(p, _)| (p - pot).abs() < 1e-6)
+                    .map_or(0.0, |&(_, probability)| probability);
+                render::small_probability(probability)
+            })
+            .collect::<Vec<_>>();
+        println!("{:.02}x = {}", pot, probabilities.join(" | "));
+    }
+}
+fn record(prompt: &mut Prompt, path: Option<PathBuf>, seed: u64, taken: &[Taken]) {
+    match path {
+        Some(path) => match prompt.start_recording(&path, seed, taken) {
+            Ok(()) => println!("recording the session to {}", path.display()),
+            Err(err) => println!("failed to start recording: {}", err),
+        },
+        None => {
+            prompt.stop_recording();
+            println!("stopped recording");
+        }
+    }
+}
+fn replay(prompt: &mut Prompt, path: &Path) {
+    match prompt.start_replay(path) {
+        Ok(count) => println!("replaying {} commands from {}", count, path.display()),
+        Err(err) => println!("failed to replay: {}", err),
+    }
+}
+fn copy_recommendation(tree: &DiscreteDecisionTree) {
+    let Some(optimal) = tree.optimal() else {
+        println!("no choices to copy");
+        return;
+    };
+    let text = format!(
+        "{:?} (EV {}, win {})",
+        optimal.choice,
+        render::ev(optimal.expected_value),
+        render::probability(optimal.win_probability())
+    );
+    match clipboard::copy(&text) {
+        Ok(()) => println!("copied '{}'", text),
+        Err(err) => println!("failed to copy: {}", err),
+    }
+}
+fn print_kelly(tree: &DiscreteDecisionTree, bankroll: f64) {
+    // the bet is placed before the hand, so it's sized for playing the whole hand optimally
+    let Some(optimal) = tree.optimal() else {
+        println!("no choices to bet on");
+        return;
+    };
+    let fraction = kelly::fraction(&optimal.payout_distribution());
+    if fraction <= 0.0 {
+        println!(
+            "the game isn't favorable (EV {}), don't bet",
+            render::ev(optimal.expected_value)
+        );
+        return;
+    }
+    println!(
+        "bet {:.02}% of your bankroll, {:.02} of {:.02} (EV {})",
+        fraction * 100.0,
+        fraction * bankroll,
+        bankroll,
+        render::ev(optimal.expected_value)
+    );
+}
+/// Prints the balance of the bankroll `ledger` (after setting it, if a `balance` is
+/// given) and the bets the Kelly criterion suggests for it
+fn print_bankroll(
+    ledger: &mut bankroll::Ledger,
+    tree: &DiscreteDecisionTree,
+    balance: Option<f64>,
+) {
+    if let Some(balance) = balance
+        && let Err(err) = ledger.set(balance)
+    {
+        println!("failed to set the bankroll: {}", err);
+        return;
+    }
+    let Some(balance) = ledger.balance() else {
+        println!("no bankroll yet, set it with 'bankroll {{amount}}'");
+        return;
+    };
+    // the hands since the balance was last set
+    let hands = ledger
+        .entries
+        .iter()
+        .rev()
+        .take_while(|entry| entry.kind != bankroll::Kind::Set)
+        .collect::<Vec<_>>();
+    let net = hands.iter().fold(0.0, |net, entry| net + entry.amount);
+    println!("[Bankroll]");
+    println!(
+        "balance = {:.02} ({:+.02} over {} hands since it was set)",
+        balance,
+        net,
+        hands.len()
+    );
+
+    // the bet is placed before the hand, so it's sized for playing the whole hand optimally
+    let Some(optimal) = tree.optimal() else {
+        return;
+    };
+    let fraction = kelly::fraction(&optimal.payout_distribution());
+    if fraction <= 0.0 {
+        println!(
+            "the game isn't favorable (EV {}), don't bet",
+            render::ev(optimal.expected_value)
+        );
+        return;
+    }
+    // smaller shares of the Kelly bet give up some growth for much smaller swings
+    println!("# Bet = Amount | Share of bankroll");
+    for (name, share) in [("kelly", 1.0), ("half kelly", 0.5), ("quarter kelly", 0.25)] {
+        println!(
+            "{} = {:.02} | {:.02}%",
+            name,
+            balance * fraction * share,
+            fraction * share * 100.0
+        );
+    }
+}
+fn print_ruin(tree: &DiscreteDecisionTree, bankroll: f64, bet: f64, hands: usize, seed: u64) {
+    const TRIALS: usize = 10_000;
+    // every hand is played optimally from the start
+    let Some(optimal) = tree.optimal() else {
+        println!("no choices to bet on");
+        return;
+    };
+    // a fresh generator from the seed, so the same command gives the same estimate
+    let mut rng = StdRng::seed_from_u64(seed);
+    let ruin = ruin::simulate(
+        &optimal.payout_distribution(),
+        bankroll,
+        bet,
+        hands,
+        TRIALS,
+        &mut rng,
+    );
+    println!("[Risk of Ruin]");
+    println!(
+        "# simulated {} sessions betting {:.02} of {:.02} (seed {})",
+        TRIALS, bet, bankroll, seed
+    );
+    println!(
+        "P(broke within {} hands) = {}",
+        hands,
+        render::probability(ruin.broke_within)
+    );
+    println!(
+        "P(broke before doubling to {:.02}) = {}",
+        bankroll * 2.0,
+        render::probability(ruin.broke_before_doubling)
+    );
+}
+fn print_autoplay(tree: &DiscreteDecisionTree, hands: usize, seed: u64, format: Format) {
+    // a fresh generator from the seed, so the same command plays the same hands
+    let mut rng = StdRng::seed_from_u64(seed);
+    let expected_value = tree.optimal().map_or(1.0, |optimal| optimal.expected_value);
+    if format != Format::Json {
+        println!("[Autoplay]");
+        println!("# playing {} hands optimally (seed {})", hands, seed);
+        println!("# Hand = Choices and cards | Final pot");
+    }
+    let mut total = 0.0;
+    for hand in 1..=hands {
+        let path = autoplay::play_optimally(tree, &mut rng);
+        total += path.payout;
+        if format == Format::Json {
+            let steps = path
+                .steps
+                .iter()
+                .map(|(choice, card)| {
+                    serde_json::json!({
+                        "choice": format!("{:?}", choice.choice),
+                        "card": card.map(|card| card.to_string()),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let json = serde_json::json!({ "hand": hand, "steps": steps, "pot": path.payout });
+            println!("{}", json);
+            continue;
+        }
+        let steps = path
+            .steps
+            .iter()
+            .map(|(choice, card)| match card {
+                Some(card) => format!("{:?} {}", choice.choice, card),
+                None => format!("{:?}", choice.choice),
+            })
+            .collect::<Vec<_>>();
+        println!("{} = {} | {:.02}x", hand, steps.join(", "), path.payout);
+    }
+
+    let mean = total / hands as f64;
+    match format {
+        Format::Json => println!(
+            "{}",
+            serde_json::json!({ "hands": hands, "mean_pot": mean, "expected_value": expected_value })
+        ),
+        _ => println!(
+            "# mean pot {}x over {} hands, expected {}x",
+            render::value(mean),
+            hands,
+            render::value(expected_value)
+        ),
+    }
+}
+/// Checks the solved EV against hands dealt from a shuffled deck and scored by the
+/// rules of the game, see [`autoplay::play_from_deck`]
+fn print_verify(tree: &DiscreteDecisionTree, options: &Options, hands: usize, seed: u64) {
+    // the z score of a 99% confidence interval
+    const Z: f64 = 2.576;
+    let Some(optimal) = tree.optimal() else {
+        println!("no choices to verify");
+        return;
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut total, mut total_squares) = (0.0, 0.0);
+    let deck = options.deck();
+    for _ in 0..hands {
+        let first_decision = first_decision(options.config, options.game.as_ref());
+        let Some(pot) = autoplay::play_from_deck(tree, first_decision, &deck, &mut rng) else {
+            println!("the deck ran out of cards before a hand ended");
+            return;
+        };
+        total += pot;
+        total_squares += pot * pot;
+    }
+    let n = hands as f64;
+    let mean = total / n;
+    // the sample variance, corrected for estimating the mean from the same hands
+    let variance = ((total_squares - n * mean * mean) / (n - 1.0)).max(0.0);
+    let margin = Z * (variance / n).sqrt();
+
+    println!("[Verify]");
+    println!(
+        "# dealt {} hands from a shuffled deck playing optimally (seed {})",
+        hands, seed
+    );
+    println!("solved EV = {}", render::ev(optimal.expected_value));
+    println!(
+        "simulated EV = {} +/- {} (99% confidence)",
+        render::ev(mean),
+        render::margin(margin)
+    );
+    match (optimal.expected_value - mean).abs() <= margin {
+        true => println!("ok, the solved EV is within the confidence interval"),
+        false => println!(
+            "{}",
+            render::banner(
+                "!!!",
+                "MISMATCH, the solved EV is outside the confidence interval"
+            )
+        ),
+    }
+}
+/// The choice a card was dealt for at the decision, if it can be dealt there
+fn dealt_for(tree: &DiscreteDecisionTree, card: PlayingCard) -> Option<&ChoiceEval> {
+    // the decisions are disjoint (except Cashout, which is always smaller), so the
+    // choice with the most EV for the card is the one it was dealt for
+    // (cashing out has an outcome for any card, but isn't dealt one)
+    tree.iter()
+        .filter(|c| c.choice.reveals_card() && c.get(card).is_some())
+        .max_by(|c1, c2| {
+            let ev1 = c1.get(card).map_or(0.0, |o| o.value);
+            let ev2 = c2.get(card).map_or(0.0, |o| o.value);
+            f64::total_cmp(&ev1, &ev2)
+        })
+}
+/// Deals the card at the cursor's decision, taking the choice it was dealt for (see
+/// [`dealt_for`]) after following the optimal choices that don't reveal a card (e.g.
+/// buying insurance), returning the outcome if the card can be dealt there
+///
+/// The outcome is the one that ended the hand if it ends before the card is dealt
+fn deal_card<'a>(cursor: &mut GameCursor<'a>, card: PlayingCard) -> Option<&'a RandomEventOutcome> {
+    while let Some(optimal) = cursor.tree().optimal().filter(|c| !c.choice.reveals_card()) {
+        let outcome = cursor.descend(&format!("{:?}", optimal.choice), None)?;
+        if outcome.next_decision().is_none() {
+            return Some(outcome);
+        }
+    }
+    let choice = dealt_for(cursor.tree(), card)?;
+    cursor.descend(&format!("{:?}", choice.choice), Some(card))
+}
+/// Prints the EV of every choice at the decision after the `cards`, exiting if
+/// the hand can't get there
+fn print_evs(tree: &DiscreteDecisionTree, cards: &[PlayingCard], format: Format) {
+    let mut cursor = GameCursor::new(tree);
+    for &card in cards {
+        let outcome = deal_card(&mut cursor, card);
+        match outcome.map(|o| o.next_decision()) {
+            Some(Some(_)) => {}
+            Some(None) => Options::usage(&format!("the hand ended at {}", card)),
+            None => Options::usage(&format!("{} can't be dealt here", card)),
+        }
+    }
+    let tree = cursor.tree();
+    if format == Format::Json {
+        let choices = tree
+            .iter()
+            .map(|c| {
+                serde_json::json!({
+                    "choice": format!("{:?}", c.choice),
+                    "expected_value": c.expected_value,
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::json!({ "choices": choices }));
+        return;
+    }
+    for choice in tree.iter() {
+        println!(
+            "{:?} = {}",
+            choice.choice,
+            render::ev(choice.expected_value)
+        );
+    }
+}
+/// Reads the cards of a hand from stdin, one per line, exiting if one isn't a card
+fn read_cards() -> Vec<PlayingCard> {
+    io::stdin()
+        .lock()
+        .lines()
+        .map(|line| line.expect("stdin cards"))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| match PlayingCard::from_str(line.trim()) {
+            Ok(card) => card,
+            Err(_) => Options::usage(&format!("'{}' isn't a card", line.trim())),
+        })
+        .collect()
+}
+/// Prints the optimal choice at each decision of a hand dealt the `cards`, taking
+/// the choice each card was dealt for (like inputting it in the prompt), until the
+/// cards run out or the hand ends
+fn print_batch(tree: &DiscreteDecisionTree, cards: &[PlayingCard], format: Format) {
+    if format != Format::Json {
+        println!("[Batch]");
+        println!("# Stage = Cards seen | Optimal choice | Expected Value");
+    }
+    let mut cursor = GameCursor::new(tree);
+    let print_step = |tree: &DiscreteDecisionTree, seen: &[PlayingCard], stage: usize| {
+        let Some(optimal) = tree.optimal() else {
+            return;
+        };
+        let seen = seen.iter().map(|card| card.to_string()).collect::<Vec<_>>();
+        match format {
+            Format::Json => println!(
+                "{}",
+                serde_json::json!({
+                    "stage": stage,
+                    "seen": seen,
+                    "optimal": format!("{:?}", optimal.choice),
+                    "expected_value": optimal.expected_value,
+                })
+            ),
+            _ => println!(
+                "{} = {} | {:?} | {}",
+                stage,
+                match seen.is_empty() {
+                    true => "none".to_owned(),
+                    false => seen.join(" "),
+                },
+                optimal.choice,
+                render::ev(optimal.expected_value)
+            ),
+        }
+    };
+    print_step(tree, &[], 1);
+    for (i, &card) in cards.iter().enumerate() {
+        let outcome = deal_card(&mut cursor, card);
+        match outcome.map(|o| (o, o.next_decision())) {
+            Some((_, Some(next))) => print_step(next, &cards[..=i], cursor.stage()),
+            Some((outcome, None)) => {
+                if i + 1 < cards.len() {
+                    Options::usage(&format!(
+                        "the hand ended at {}, before the rest of the cards",
+                        card
+                    ));
+                }
+                match format {
+                    Format::Json => println!("{}", serde_json::json!({ "pot": outcome.value })),
+                    _ => println!("# the hand ended with a {:.02}x pot", outcome.value),
+                }
+            }
+            None => Options::usage(&format!("{} can't be dealt here", card)),
+        }
+    }
+}
+/// A seed from the clock, for when no seed is given
+fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+/// A decision taken in the interactive loop, either by inputting the card that
+/// was revealed or by picking a choice that doesn't reveal one
+///
+/// Displays as the command that takes it
+enum Taken {
+    Card(PlayingCard),
+    Pick(String),
+    /// A card dealt for a choice picked before it (see [`Game::picked`])
+    PickedCard(String, PlayingCard),
+}
+impl Display for Taken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Card(card) => write!(f, "{}", card),
+            Self::Pick(choice_name) => write!(f, "pick {}", choice_name),
+            Self::PickedCard(choice_name, card) => write!(f, "pick {}\n{}", choice_name, card),
+        }
+    }
+}
+/// A game tracked by the interactive loop, with its own hand in progress, bet, and stats
+struct Game<'a> {
+    /// Where the hand in progress is in the game
+    cursor: GameCursor<'a>,
+    taken: Vec<Taken>,
+    /// The steps taken back with `back`, latest last, which `forward` takes again
+    ///
+    /// Each is the choice taken and the card that resolved it
+    undone: Vec<(String, Option<PlayingCard>, Taken)>,
+    /// The choice picked with `pick` for the next card, which is otherwise taken to
+    /// be the choice the card wins
+    picked: Option<String>,
+    /// The amount bet on each hand if one was set, which is shown along with multiples
+    /// of the bet, otherwise hands are counted in bets
+    bet: Option<f64>,
+    /// The number of hands finished
+    hands: usize,
+    /// The amount won over every finished hand (negative if lost)
+    net: f64,
+    /// How much more was won over every finished hand than expected at each decision,
+    /// as an amount like the net (see [`LuckStep`])
+    luck: f64,
+    /// How the choices picked compare to the optimal ones, if this is a practice game
+    practice: Option<Practice>,
+}
+/// What a [`Game`] keeps when the game is solved again (e.g. after cards are removed
+/// from the deck), where the hand in progress starts over
+struct GameStats {
+    bet: Option<f64>,
+    hands: usize,
+    net: f64,
+    luck: f64,
+    practice: Option<Practice>,
+}
+/// The mistakes made in a practice game
+#[derive(Default)]
+struct Practice {
+    /// The number of choices picked that weren't optimal
+    mistakes: usize,
+    /// The EV given up by those choices, as a multiple of the bet
+    ev_lost: f64,
+}
+impl<'a> Game<'a> {
+    fn new(tree: &'a DiscreteDecisionTree, bet: Option<f64>) -> Self {
+        Self {
+            cursor: GameCursor::new(tree),
+            taken: Vec::new(),
+            undone: Vec::new(),
+            picked: None,
+            bet,
+            hands: 0,
+            net: 0.0,
+            luck: 0.0,
+            practice: None,
+        }
+    }
+    /// Creates a practice game, see [`Practice`]
+    fn practice(tree: &'a DiscreteDecisionTree, bet: Option<f64>) -> Self {
+        Self {
+            practice: Some(Practice::default()),
+            ..Self::new(tree, bet)
+        }
+    }
+    /// Continues a game in the game solved again, with a new hand
+    fn resume(tree: &'a DiscreteDecisionTree, stats: GameStats) -> Self {
+        Self {
+            hands: stats.hands,
+            net: stats.net,
+            luck: stats.luck,
+            practice: stats.practice,
+            ..Self::new(tree, stats.bet)
+        }
+    }
+    /// Ends the game in the tree it was playing, keeping what carries over to the game
+    /// solved again, see [`Game::resume`]
+    fn into_stats(self) -> GameStats {
+        GameStats {
+            bet: self.bet,
+            hands: self.hands,
+            net: self.net,
+            luck: self.luck,
+            practice: self.practice,
+        }
+    }
+    /// Compares a choice picked in a practice game against the optimal choice of
+    /// the decision, pointing out the optimal one if it wasn't picked (unless `quiet`)
+    fn judge(&mut self, tree: &DiscreteDecisionTree, choice: &ChoiceEval, quiet: bool) {
+        let Some(practice) = &mut self.practice else {
+            return;
+        };
+        let ranked_choices = tree.ranked_choices();
+        let Some(picked) = ranked_choices
+            .iter()
+            .find(|ranked| std::ptr::eq(ranked.choice, choice))
+        else {
+            return;
+        };
+        if !picked.is_optimal {
+            // only count EV given up, since the optimal choice may have less EV for other objectives
+            practice.mistakes += 1;
+            practice.ev_lost += picked.ev_gap.max(0.0);
+            let text = format!(
+                "{:?} isn't optimal, {:?} was ({} EV short)",
+                choice.choice,
+                ranked_choices[0].choice.choice,
+                render::value(picked.ev_gap)
+            );
+            if !quiet {
+                println!("{}", render::banner("!!!", &text));
+            }
+        }
+    }
+    /// The amount bet on each hand, which is 1 (a bet) unless one was set
+    fn bet(&self) -> f64 {
+        self.bet.unwrap_or(1.0)
+    }
+    /// The bet to show amounts for along with multiples of the bet, if one was set
+    fn amounts(&self) -> Option<f64> {
+        self.bet
+    }
+    /// The decision the hand in progress is at
+    fn tree(&self) -> &'a DiscreteDecisionTree {
+        self.cursor.tree()
+    }
+    /// The cards seen in the hand in progress
+    fn cards(&self) -> Vec<PlayingCard> {
+        self.cursor.events().collect()
+    }
+    /// Records a step taken in the hand, which can't be taken forward again after
+    /// going back anymore
+    fn take(&mut self, taken: Taken) {
+        self.taken.push(taken);
+        self.undone.clear();
+        self.picked = None;
+    }
+    /// Takes back the last step of the hand, returning `false` if there wasn't one
+    fn back(&mut self) -> bool {
+        self.picked = None;
+        let last = self.cursor.last();
+        if !self.cursor.back() {
+            return false;
+        }
+        let taken = self.taken.pop();
+        if let (Some((choice, event)), Some(taken)) = (last, taken) {
+            self.undone
+                .push((format!("{:?}", choice.choice), event, taken));
+        }
+        true
+    }
+    /// Takes the last step taken back again, returning `false` if there wasn't one
+    fn forward(&mut self) -> bool {
+        let Some((choice, event, taken)) = self.undone.pop() else {
+            return false;
+        };
+        self.picked = None;
+        self.cursor.descend(&choice, event);
+        self.taken.push(taken);
+        true
+    }
+    /// Starts a new hand, abandoning the one in progress
+    fn reset(&mut self) {
+        self.cursor.reset();
+        self.taken.clear();
+        self.undone.clear();
+        self.picked = None;
+    }
+    /// Finishes the hand in progress with the final `pot` (a multiple of the bet, with
+    /// what the side bet won less its stake), then starts a new hand
+    ///
+    /// How the hand went is printed, unless `quiet`
+    fn finish(&mut self, pot: f64, quiet: bool) {
+        self.hands += 1;
+        // the side bet's stake is spent along with the bet, so the net is the pot less
+        // the bet (the stake is already taken off), and it's paid back with what was won
+        let stake = self
+            .cursor
+            .root()
+            .side_bet()
+            .map_or(0.0, |side_bet| side_bet.stake);
+        self.net += (pot - 1.0) * self.bet();
+        let line = format!(
+            "hand finished with a {:.02}x pot, won {:.02} (net {:+.02} over {} hands)",
+            pot,
+            (pot + stake) * self.bet(),
+            self.net,
+            self.hands
+        );
+        // anything less than the bet back means a pick lost
+        match pot < 1.0 {
+            _ if quiet => {}
+            true => println!("{}", render::bad(&line)),
+            false => println!("{}", line),
+        }
+        if self.practice.is_some() && !quiet {
+            print_practice(self);
+        }
+        self.reset();
+    }
+}
+/// Prints the results of a practice game against what playing optimally is expected to win
+fn print_practice(game: &Game) {
+    let Some(practice) = &game.practice else {
+        return;
+    };
+    let optimal_ev = game
+        .cursor
+        .root()
+        .optimal()
+        .map_or(1.0, |optimal| optimal.expected_value);
+    println!(
+        "practice: net {:+.02} over {} hands, {:+.02} expected playing optimally, {} mistakes gave up {}x the bet in EV",
+        game.net,
+        game.hands,
+        (optimal_ev - 1.0) * game.bet() * game.hands as f64,
+        practice.mistakes,
+        render::value(practice.ev_lost)
+    );
+}
+fn print_games(games: &[Game], current: usize, stages: usize) {
+    println!("[Games]");
+    println!("# Game = Bet | Hands | Net | Hand in progress");
+    for (i, game) in games.iter().enumerate() {
+        print!(
+            "{} = {:.02} | {} | {:+.02} | {}",
+            i + 1,
+            game.bet(),
+            game.hands,
+            game.net,
+            status_line(
+                game.tree(),
+                &game.cards(),
+                game.cursor.stage(),
+                stages,
+                game.amounts()
+            )
+        );
+        if game.practice.is_some() {
+            print!(" (practice)");
+        }
+        if i == current {
+            print!(" (current)");
+        }
+        println!();
+    }
+}
+/// Prints the size of the solved tree, and of the subtree of the current decision
+fn print_stats(
+    root: &DiscreteDecisionTree,
+    tree: &DiscreteDecisionTree,
+    solve_time: Duration,
+    format: Format,
+) {
+    let trees = [("Root", root), ("Current", tree)];
+    if format == Format::Json {
+        let stats = trees
+            .iter()
+            .map(|(name, tree)| {
+                serde_json::json!({
+                    "tree": name.to_lowercase(),
+                    "decisions": tree.decision_count(),
+                    "outcomes": tree.outcome_count(),
+                    "depth": tree.depth(),
+                    "memory_bytes": tree.memory_size(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let json = serde_json::json!({
+            "trees": stats,
+            "solve_seconds": solve_time.as_secs_f64(),
+        });
+        println!("{}", json);
+        return;
+    }
+    println!("[Stats]");
+    println!("# Tree = Decisions | Outcomes | Depth | Memory");
+    for (name, tree) in trees {
+        println!(
+            "{} = {} | {} | {} | {:.02} MiB",
+            name,
+            tree.decision_count(),
+            tree.outcome_count(),
+            tree.depth(),
+            tree.memory_size() as f64 / (1024.0 * 1024.0)
+        );
+    }
+    println!("solved (or loaded from the cache) in {:.04?}", solve_time);
+}
+/// Prints the totals of every hand kept in the career file, see [`career::Career`]
+fn print_career(path: Option<&Path>, format: Format) {
+    let Some(path) = path else {
+        println!("no career file, keep one with --career {{file}}");
+        return;
+    };
+    let hands = match career::load(path) {
+        Ok(hands) => hands,
+        Err(err) => {
+            println!("failed to load the career file: {}", err);
+            return;
+        }
+    };
+    if hands.is_empty() {
+        println!("no hands finished yet");
+        return;
+    }
+    let career = career::Career::of(&hands);
+    if format == Format::Json {
+        let json = serde_json::json!({
+            "hands": career.hands,
+            "staked": career.staked,
+            "returned": career.returned,
+            "expected": career.expected,
+            "realized_return": career.realized_return(),
+            "expected_return": career.expected_return(),
+            "biggest_win": career.biggest_win.map(|(pot, won)| serde_json::json!({ "pot": pot, "won": won })),
+            "longest_losing_streak": career.longest_losing_streak,
+        });
+        println!("{}", json);
+        return;
+    }
+    let percent = |ret: f64| format!("{:+.02}%", ret * 100.0);
+    println!("[Career]");
+    println!("hands = {}", career.hands);
+    println!("staked = {:.02}", career.staked);
+    println!(
+        "returned = {:.02} ({})",
+        career.returned,
+        percent(career.realized_return())
+    );
+    println!(
+        "expected = {:.02} ({})",
+        career.expected,
+        percent(career.expected_return())
+    );
+    let luck = career.returned - career.expected;
+    let line = format!("{:+.02} against the expected return", luck);
+    match luck < 0.0 {
+        true => println!("{}", render::bad(&line)),
+        false => println!("{}", line),
+    }
+    match career.biggest_win {
+        Some((pot, won)) => println!(
+            "biggest win = {:.02}x pot, won {:.02} above the bet",
+            pot, won
+        ),
+        None => println!("biggest win = none"),
+    }
+    println!(
+        "longest losing streak = {} hands",
+        career.longest_losing_streak
+    );
+}
+/// Prints the cards and choices taken in the hand in progress, with the pot after each
+fn print_history(cursor: &GameCursor, format: Format) {
+    let history = cursor.history();
+    if format == Format::Json {
+        let steps = history
+            .iter()
+            .map(|(choice, card, next)| {
+                serde_json::json!({
+                    "card": card.map(|card| card.to_string()),
+                    "choice": format!("{:?}", choice.choice),
+                    "pot": next.pot(),
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::json!({ "history": steps }));
+        return;
+    }
+    if history.is_empty() {
+        println!("nothing has been taken this hand");
+        return;
+    }
+    println!("[History]");
+    println!("# Step = Card | Choice | Pot");
+    for (i, (choice, card, next)) in history.iter().enumerate() {
+        let card = card.map_or("-".to_owned(), render::card);
+        println!(
+            "{} = {} | {:?} | {:.02}x",
+            i + 1,
+            card,
+            choice.choice,
+            next.pot()
+        );
+    }
+}
+/// A choice taken in a hand, with its EV and what the card dealt made it worth
+struct LuckStep {
+    choice: String,
+    card: Option<PlayingCard>,
+    /// The EV of the choice when it was taken
+    expected: f64,
+    /// The EV of the decision the card led to, or the final pot if the hand ended
+    realized: f64,
+}
+impl LuckStep {
+    /// How much more the card made the choice worth than expected, as a multiple of the bet
+    fn luck(&self) -> f64 {
+        self.realized - self.expected
+    }
+}
+/// The choices taken so far in the hand of the `cursor`, with their luck
+fn luck_steps(cursor: &GameCursor) -> Vec<LuckStep> {
+    cursor
+        .history()
+        .iter()
+        .map(|(choice, card, _)| LuckStep {
+            choice: format!("{:?}", choice.choice),
+            card: *card,
+            expected: choice.expected_value,
+            realized: choice.outcome(*card).map_or(0.0, |outcome| outcome.value),
+        })
+        .collect()
+}
+/// The choices taken in a hand of the `cursor` that ended with the `pot`, after the
+/// last `choice` was resolved by the `card`, with their luck
+fn hand_luck_steps(
+    cursor: &GameCursor,
+    choice: &ChoiceEval,
+    card: Option<PlayingCard>,
+    pot: f64,
+) -> Vec<LuckStep> {
+    // the cursor stays at the last decision when the hand ends, so the last step isn't
+    // part of its history
+    let mut steps = luck_steps(cursor);
+    steps.push(LuckStep {
+        choice: format!("{:?}", choice.choice),
+        card,
+        expected: choice.expected_value,
+        realized: pot,
+    });
+    steps
+}
+/// Prints the luck of each step of a hand and of the hand as a whole, along with the
+/// running luck of every hand finished in the `game`
+fn print_luck(steps: &[LuckStep], game: &Game, format: Format) {
+    let hand = steps.iter().fold(0.0, |luck, step| luck + step.luck());
+    if format == Format::Json {
+        let steps = steps
+            .iter()
+            .map(|step| {
+                serde_json::json!({
+                    "choice": step.choice,
+                    "card": step.card.map(|card| card.to_string()),
+                    "expected_value": step.expected,
+                    "realized_value": step.realized,
+                    "luck": step.luck(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let json = serde_json::json!({
+            "steps": steps,
+            "hand_luck": hand,
+            "bet": game.bet(),
+            "running_luck": game.luck,
+            "hands": game.hands,
+        });
+        println!("{}", json);
+        return;
+    }
+    println!("[Luck]");
+    if !steps.is_empty() {
+        println!("# Step = Choice | EV | Realized | Luck (multiples of the bet)");
+        for (i, step) in steps.iter().enumerate() {
+            let card = match step.card {
+                Some(card) => format!(" on {}", render::card(card)),
+                None => String::new(),
+            };
+            println!(
+                "{} = {}{} | {} | {} | {}",
+                i + 1,
+                step.choice,
+                card,
+                render::ev(step.expected),
+                render::ev(step.realized),
+                render::gain(step.luck())
+            );
+        }
+        match game.amounts() {
+            Some(bet) => println!("hand luck = {} ({:+.02})", render::gain(hand), hand * bet),
+            None => println!("hand luck = {}", render::gain(hand)),
+        }
+    }
+    // lucky and unlucky runs even out, so this drifts around zero over many hands
+    // the bet can change between hands, so once it's set the running luck is an amount
+    let line = match game.amounts() {
+        Some(_) => format!(
+            "running luck = {:+.02} over {} hands, in the amounts bet",
+            game.luck, game.hands
+        ),
+        None => format!(
+            "running luck = {} over {} hands",
+            render::gain(game.luck),
+            game.hands
+        ),
+    };
+    match game.luck < 0.0 {
+        true => println!("{}", render::bad(&line)),
+        false => println!("{}", line),
+    }
+}
+/// Prints the cards dealt to the other seats at the table this round
+fn print_table(table: &[(usize, PlayingCard)]) {
+    if table.is_empty() {
+        println!("no cards dealt to other seats, add them with 'table {{seat}} {{cards}}'");
+        return;
+    }
+    let mut seats = table.iter().map(|&(seat, _)| seat).collect::<Vec<_>>();
+    seats.sort_unstable();
+    seats.dedup();
+    println!("[Table]");
+    println!("# Seat = Cards");
+    for seat in seats {
+        let cards = table
+            .iter()
+            .filter(|&&(dealt_to, _)| dealt_to == seat)
+            .map(|&(_, card)| card)
+            .collect::<Vec<_>>();
+        println!("{} = {}", seat, render::cards(&cards));
+    }
+    println!(
+        "# {} cards are out of the deck for the rest of the round",
+        table.len()
+    );
+}
+/// The copies of a card left in the deck, after the cards of your hand and the
+/// cards dealt to the other seats at the table
+fn copies_left(
+    deck: &DeckSpec,
+    card: PlayingCard,
+    hand: &[PlayingCard],
+    table: &[(usize, PlayingCard)],
+) -> usize {
+    let dealt = hand
+        .iter()
+        .chain(table.iter().map(|(_, dealt)| dealt))
+        .filter(|&&dealt| dealt == card)
+        .count();
+    (deck.copies(card) as usize).saturating_sub(dealt)
+}
+/// Runs the interactive loop with the `kept` games (or a new one if there are none)
+/// until cards are removed from the deck, returning them so the game can be solved
+/// again without them, along with the games and the index of the current one
+fn interactive_prompt(
+    tree: &DiscreteDecisionTree,
+    options: &Options,
+    prompt: &mut Prompt,
+    solve_time: Duration,
+    kept: Vec<GameStats>,
+    mut current: usize,
+) -> (Vec<PlayingCard>, Vec<GameStats>, usize) {
+    // a mulligan reveals another card without passing a stage, so the stages can't be
+    // counted from the tree
+    let stages = options.stages();
+    let mut games = kept
+        .into_iter()
+        .map(|stats| Game::resume(tree, stats))
+        .collect::<Vec<_>>();
+    if games.is_empty() {
+        games.push(Game::new(tree, None));
+        current = 0;
+    }
+    // the cards dealt to the other seats this round, which every hand is dealt around
+    let mut table: Vec<(usize, PlayingCard)> = Vec::new();
+    // the decision evaluated around the cards at the table, with the path to it, which
+    // is solved again when the table changes (see [`TableCommand`])
+    let mut resolved = None;
+    'outer: loop {
+        // get the current decision tree and print the choices available to the user
+        let cards = games[current].cards();
+        // a card discarded with a mulligan stays in the cards, so the stage is counted
+        // from the choices that ended one
+        let stage = games[current].cursor.stage();
+        // cards at the table change the odds of the rest of the hand, so it's evaluated
+        // again against the deck without them, once for each decision reached
+        let history = games[current].cursor.history();
+        let path = history
+            .iter()
+            .map(|(choice, event, _)| (format!("{:?}", choice.choice), *event))
+            .collect::<Vec<_>>();
+        let stale = !matches!(&resolved, Some((at, _)) if *at == path);
+        if !table.is_empty() && stale {
+            let deck = table
+                .iter()
+                .fold(options.deck(), |deck, &(_, card)| deck.without_card(card));
+            let steps = path
+                .iter()
+                .map(|(name, event)| (name.as_str(), *event))
+                .collect::<Vec<_>>();
+            debug!(
+                cards = table.len(),
+                "resolving around the cards at the table"
+            );
+            // solved again from the rules of the game, since a cached tree only has the
+            // names of its choices
+            resolved = DiscreteDecisionTree::solve_at(
+                first_decision(options.config, options.game.as_ref()),
+                &steps,
+                &deck,
+                games[current].tree().options(),
+            )
+            .map(|tree| (path, tree));
+        }
+        let tree = match &resolved {
+            Some((_, tree)) => tree,
+            None => games[current].tree(),
+        };
+        let seen = cards
+            .iter()
+            .copied()
+            .chain(table.iter().map(|&(_, card)| card))
+            .collect::<Vec<_>>();
+        let quiet = options.verbosity == Verbosity::Quiet || options.machine;
+        if options.card_faces && !cards.is_empty() && !quiet {
+            println!("{}", render::card_faces(&cards));
+        }
+        // practice games hide the EVs, so the choices have to be picked from memory
+        match games[current].practice {
+            Some(_) => {
+                let names = tree
+                    .iter()
+                    .map(|choice| format!("{:?}", choice.choice))
+                    .collect::<Vec<_>>();
+                println!("[Practice] pick {}", names.join(", "));
+            }
+            None if options.machine => {
+                print_decision(tree, &cards, stage, stages, games[current].bet())
+            }
+            None if quiet => print_optimal(tree, options.format),
+            None => print_choices(tree, options, options.format, games[current].amounts()),
+        }
+        let bet = games[current].bet();
+        prompt.log(|session| session.decision(decision_json(tree, &cards, stage, stages, bet)));
+        if options.verbosity == Verbosity::Verbose
+            && games[current].practice.is_none()
+            && !options.machine
+        {
+            print_breakdown(tree, options.format, games[current].amounts());
+        }
+
+        // find the next card from user input (service the CLI prompt)
+        let mut status = match quiet {
+            true => String::new(),
+            false => status_line(tree, &cards, stage, stages, games[current].amounts()),
+        };
+        if games.len() > 1 {
+            status = format!("[game {}] {}", current + 1, status);
+        }
+        if games[current].practice.is_some() {
+            status = format!("[practice] {}", status);
+        }
+        if !table.is_empty() {
+            status = format!("[table: {} cards out] {}", table.len(), status);
+        }
+        if let Some(picked) = &games[current].picked {
+            status = format!("{} [picked {}]", status, picked);
+        }
+        #[cfg(not(target_os = "wasi"))]
+        prompt.complete_choices(tree);
+        let next_card = loop {
+            let game = &mut games[current];
+            let cmd = prompt.read_command(&status).expect("stdin command");
+            match cmd {
+                Command::Help => print_help(),
+                Command::Exit => std::process::exit(0),
+                Command::ListChoices(format) => print_choices(
+                    tree,
+                    options,
+                    format.unwrap_or(options.format),
+                    game.amounts(),
+                ),
+                Command::ListEvents(choice_name, format, filter) => print_events(
+                    tree,
+                    &choice_name,
+                    format.unwrap_or(options.format),
+                    game.amounts(),
+                    &filter,
+                ),
+                Command::Tree(depth) => print!("{}", tree.fmt_tree(depth)),
+                Command::Payouts(choice_name) => {
+                    print_payouts(tree, &choice_name, options.format, game.amounts())
+                }
+                Command::Why(choice_name) => print_why(tree, &choice_name, options.format),
+                Command::ListStages => print_stages(game.cursor.root(), options.format),
+                Command::Bust => print_bust(tree, stage, options.format),
+                Command::Strategy(None) => print_strategy(game.cursor.root(), options),
+                Command::Report(path) => export_report(game.cursor.root(), &path, options),
+                Command::Strategy(Some(path)) => {
+                    export_strategy(game.cursor.root(), &path, options)
+                }
+                Command::ExportStages(path) => export_stages(game.cursor.root(), &path, options),
+                Command::ExportCharts(dir) => export_charts(game.cursor.root(), &dir),
+                Command::ExportDot(path, depth) => export_dot(tree, &path, depth),
+                Command::ExportCsv(path, all) => export_outcomes(tree, &path, &cards, all),
+                Command::Compare(strategies) => print_comparison(&strategies, options),
+                Command::ComparePolicies => print_policies(game.cursor.root()),
+                Command::Record(path) => {
+                    let seed = options.seed.expect("seed picked at startup");
+                    record(prompt, path, seed, &game.taken);
+                    // the decision being made was shown before the recording started
+                    let decision = decision_json(tree, &cards, stage, stages, game.bet());
+                    prompt.log(|session| session.decision(decision));
+                }
+                Command::Replay(path) => replay(prompt, &path),
+                Command::Copy => copy_recommendation(tree),
+                Command::Kelly(bankroll) => print_kelly(game.cursor.root(), bankroll),
+                Command::Bankroll(balance) => match prompt.ledger() {
+                    Ok(ledger) => print_bankroll(ledger, game.cursor.root(), balance),
+                    Err(err) => println!("failed to load the bankroll: {}", err),
+                },
+                Command::Ruin(bankroll, bet, hands) => {
+                    let seed = options.seed.expect("seed picked at startup");
+                    print_ruin(game.cursor.root(), bankroll, bet, hands, seed)
+                }
+                Command::Autoplay(hands) => {
+                    let seed = options.seed.expect("seed picked at startup");
+                    print_autoplay(game.cursor.root(), hands, seed, options.format)
+                }
+                Command::Verify(hands) => {
+                    let seed = options.seed.expect("seed picked at startup");
+                    print_verify(game.cursor.root(), options, hands, seed)
+                }
+                Command::Game(GameCommand::New(bet)) => {
+                    games.push(Game::new(games[0].cursor.root(), bet));
+                    current = games.len() - 1;
+                    println!("\nstarted game {}", current + 1);
+                    continue 'outer;
+                }
+                Command::Game(GameCommand::List) => print_games(&games, current, stages),
+                Command::Practice(PracticeCommand::Start(bet)) => {
+                    games.push(Game::practice(games[0].cursor.root(), bet));
+                    current = games.len() - 1;
+                    println!("\nstarted practice game {}", current + 1);
+                    continue 'outer;
+                }
+                Command::Practice(PracticeCommand::Stop) => match game.practice {
+                    Some(_) => {
+                        print_practice(game);
+                        // the first game is never a practice game, so there's always one left
+                        games.remove(current);
+                        current = 0;
+                        println!("\nstopped practicing, switched to game 1");
+                        continue 'outer;
+                    }
+                    None => print_error(options, "not practicing, start with 'practice'"),
+                },
+                Command::Game(GameCommand::Switch(id)) => match id.checked_sub(1) {
+                    Some(idx) if idx < games.len() => {
+                        current = idx;
+                        println!("\nswitched to game {}", id);
+                        continue 'outer;
+                    }
+                    _ => print_error(options, "invalid game"),
+                },
+                // the hand in progress is valued (and its luck counted) in the bet it started with
+                Command::Bet(_) if !game.cursor.is_empty() => {
+                    print_error(options, "the bet can only be changed between hands")
+                }
+                Command::Bet(bet) => {
+                    game.bet = Some(bet);
+                    println!("betting {:.02} on each hand", bet);
+                    continue 'outer;
+                }
+                Command::Reset => {
+                    // start a new hand from the root tree
+                    game.reset();
+                    if !quiet {
+                        println!();
+                    }
+                    continue 'outer;
+                }
+                Command::Back => {
+                    // remove the last taken decision, then restart interaction
+                    game.back();
+                    continue 'outer;
+                }
+                Command::Forward => match game.forward() {
+                    true => continue 'outer,
+                    false => print_error(options, "nothing to go forward to"),
+                },
+                Command::History => print_history(&game.cursor, options.format),
+                Command::Stats => print_stats(game.cursor.root(), tree, solve_time, options.format),
+                Command::Career => print_career(options.career.as_deref(), options.format),
+                Command::Luck => print_luck(&luck_steps(&game.cursor), game, options.format),
+                Command::Remove(cards) => match options.unremovable(&cards) {
+                    Some(card) => print_error(
+                        options,
+                        &format!("{} isn't left in the deck to remove", card),
+                    ),
+                    None if options.undealable(&cards) => {
+                        print_error(options, "the deck needs a card for every stage of the game")
+                    }
+                    None => {
+                        let games = games.into_iter().map(Game::into_stats).collect();
+                        return (cards, games, current);
+                    }
+                },
+                Command::Table(TableCommand::Deal(seat, dealt)) => {
+                    // each card has to be left after the ones before it are dealt
+                    let mut after = table.clone();
+                    let taken = dealt.into_iter().find(|&card| {
+                        let left = copies_left(&prompt.deck, card, &cards, &after);
+                        after.push((seat, card));
+                        left == 0
+                    });
+                    match taken {
+                        Some(card) => {
+                            print_error(options, &format!("{} isn't left in the deck", card))
+                        }
+                        None => {
+                            table = after;
+                            resolved = None;
+                            println!();
+                            print_table(&table);
+                            println!();
+                            continue 'outer;
+                        }
+                    }
+                }
+                Command::Table(TableCommand::List) => print_table(&table),
+                Command::Table(TableCommand::Clear) => {
+                    table.clear();
+                    resolved = None;
+                    println!("\nstarted a new round, the other seats' cards are back in the deck");
+                    continue 'outer;
+                }
+                Command::Pick(choice_name) => {
+                    let choice = tree
+                        .iter()
+                        .find(|ec| format!("{:?}", ec.choice).eq_ignore_ascii_case(&choice_name));
+                    match choice {
+                        Some(choice) if choice.choice.is_cashout() => {
+                            // cashing out doesn't depend on the card, so the hand ends here
+                            if !quiet {
+                                println!();
+                                print_chosen(choice);
+                            }
+                            game.judge(tree, choice, quiet);
+                            prompt.log(|session| session.choice(tree, choice, None));
+                            if !quiet {
+                                println!("no more decisions, resetting");
+                            }
+                            finish_hand(
+                                game,
+                                prompt,
+                                options,
+                                choice,
+                                None,
+                                choice.expected_value,
+                                false,
+                            );
+                            if !quiet {
+                                println!();
+                            }
+                            continue 'outer;
+                        }
+                        Some(choice) if !choice.choice.reveals_card() => {
+                            if !quiet {
+                                println!();
+                                print_chosen(choice);
+                            }
+                            game.judge(tree, choice, quiet);
+                            prompt.log(|session| session.choice(tree, choice, None));
+                            // choices that don't reveal a card have a single outcome
+                            let name = format!("{:?}", choice.choice);
+                            let Some(outcome) = game.cursor.descend(&name, None) else {
+                                continue;
+                            };
+                            match outcome.next_decision() {
+                                Some(_) => game.take(Taken::Pick(name)),
+                                None => {
+                                    if !quiet {
+                                        println!("no more decisions, resetting");
+                                    }
+                                    let (pot, lost) = (outcome.value, outcome.is_lost());
+                                    finish_hand(game, prompt, options, choice, None, pot, lost);
+                                    if !quiet {
+                                        println!();
+                                    }
+                                }
+                            }
+                            continue 'outer;
+                        }
+                        Some(choice) if game.practice.is_some() => {
+                            // lock in the choice, then deal the card it's resolved by
+                            if !quiet {
+                                println!();
+                                print_chosen(choice);
+                            }
+                            game.judge(tree, choice, quiet);
+                            let Some(card) = prompt.deal_picked(&seen).expect("record dealt card")
+                            else {
+                                print_error(options, "no cards left to deal");
+                                continue;
+                            };
+                            if !quiet {
+                                println!("dealt {}", render::card(card));
+                            }
+                            prompt.log(|session| session.choice(tree, choice, Some(card)));
+                            let name = format!("{:?}", choice.choice);
+                            let Some(outcome) = game.cursor.descend(&name, Some(card)) else {
+                                continue;
+                            };
+                            match outcome.next_decision() {
+                                // the card only continues the hand for the choice picked, so
+                                // inputting it picks the same choice again
+                                Some(_) => game.take(Taken::Card(card)),
+                                None => {
+                                    if !quiet {
+                                        println!("no more decisions, resetting");
+                                    }
+                                    let (pot, lost) = (outcome.value, outcome.is_lost());
+                                    let card = Some(card);
+                                    finish_hand(game, prompt, options, choice, card, pot, lost);
+                                    if !quiet {
+                                        println!();
+                                    }
+                                }
+                            }
+                            continue 'outer;
+                        }
+                        Some(choice) => {
+                            // the card input next is dealt for this choice, even if it wins another
+                            game.picked = Some(format!("{:?}", choice.choice));
+                            if !quiet {
+                                println!("picked {:?}, input the card dealt for it", choice.choice);
+                            }
+                            continue 'outer;
+                        }
+                        None => print_error(options, "invalid choice"),
+                    }
+                }
+                Command::Deal => match prompt.deal(&seen).expect("record dealt card") {
+                    Some(card) => break card,
+                    None => print_error(options, "no cards left to deal"),
+                },
+                Command::Card(card) if prompt.deck.copies(card) == 0 => {
+                    print_error(options, &format!("{} isn't in the deck", card))
+                }
+                Command::Card(card)
+                    if table.iter().any(|&(_, dealt)| dealt == card)
+                        && copies_left(&prompt.deck, card, &cards, &table) == 0 =>
+                {
+                    print_error(options, &format!("{} was dealt to another seat", card))
+                }
+                Command::Card(card) => break card, // break out with provided card to enter new tree
+            }
+        };
+
+        // the card is dealt for the choice picked, if one was
+        let picked = games[current].picked.take();
+        let choice = match &picked {
+            Some(name) => tree.choice(name),
+            // otherwise find the choice the user made by finding the max EV
+            // we can do this because the decisions are disjoint (except Cashout, which is always smaller), i.e.
+            // a card can only succeed with one decision
+            None => tree.iter().max_by(|c1, c2| {
+                let ev1 = c1.get(next_card).map(|o| o.value).unwrap_or(0.0);
+                let ev2 = c2.get(next_card).map(|o| o.value).unwrap_or(0.0);
+                f64::total_cmp(&ev1, &ev2)
+            }),
+        };
+
+        // get the next tree from the card provided, or error if it was an invalid card, or reset
+        // if there are no more decisions
+        if !quiet {
+            println!();
+        }
+        let game = &mut games[current];
+        let find = choice
+            .inspect(|c| match picked {
+                _ if quiet => {}
+                Some(_) => println!(
+                    "{} was dealt for {:?}",
+                    render::card_name(next_card),
+                    c.choice
+                ),
+                None => print_chosen(c),
+            })
+            .and_then(|c| {
+                game.cursor
+                    .descend(&format!("{:?}", c.choice), Some(next_card))
+            });
+        if let (Some(choice), Some(_)) = (choice, find) {
+            prompt.log(|session| session.choice(tree, choice, Some(next_card)));
+        }
+        match choice.zip(find).map(|(c, o)| (c, o, o.next_decision())) {
+            Some((_, _, Some(_))) => game.take(match picked {
+                Some(name) => Taken::PickedCard(name, next_card),
+                None => Taken::Card(next_card),
+            }),
+            Some((choice, outcome, None)) => {
+                // no next_decision
+                if !quiet {
+                    println!("no more decisions, resetting");
+                }
+                let (pot, lost) = (outcome.value, outcome.is_lost());
+                finish_hand(game, prompt, options, choice, Some(next_card), pot, lost);
+                if !quiet {
+                    println!();
+                }
+            }
+            None if options.machine => print_error(options, lang::text().invalid_card),
+            None => println!("{}", render::banner("!!!", lang::text().invalid_card)),
+        }
+    }
+}
+/// Prints the choice the player took, for when it isn't obvious from the card
+fn print_chosen(choice: &ChoiceEval) {
+    let text = lang::fill(lang::text().you_chose, &[&format!("{:?}", choice.choice)]);
+    println!("{}", render::banner("???", &text));
+}
+/// Prints an error with a command, which is a line of JSON for programs reading the
+/// output (see [`Options::machine`])
+fn print_error(options: &Options, message: &str) {
+    match options.machine {
+        true => println!("{}", serde_json::json!({ "error": message })),
+        false => println!("{}", message),
+    }
+}
+/// Finishes the hand of the `game` with the final `pot`, after the last `choice` was
+/// resolved by the `card` (if it reveals one), which `lost` the pot if it did
+///
+/// The hand is logged to the session, added to the career file and bankroll (unless
+/// it's a practice hand), and its luck is added to the game's and printed
+fn finish_hand(
+    game: &mut Game,
+    prompt: &mut Prompt,
+    options: &Options,
+    choice: &ChoiceEval,
+    card: Option<PlayingCard>,
+    pot: f64,
+    lost: bool,
+) {
+    let bet = game.bet();
+    let quiet = options.verbosity == Verbosity::Quiet || options.machine;
+    prompt.log(|session| session.finish(pot, bet));
+    if let (Some(path), None) = (&options.career, &game.practice) {
+        let expected = game
+            .cursor
+            .root()
+            .optimal()
+            .map_or(1.0, |optimal| optimal.expected_value);
+        if let Err(err) = career::append(path, &career::Hand::new(bet, pot, expected)) {
+            let message = format!("failed to add the hand to the career file: {}", err);
+            print_error(options, &message);
+        }
+    }
+    if game.practice.is_none() {
+        // a hand that lost the pot can still get some back (e.g. from insurance), and
+        // one that won can end with less than the bet (e.g. after paying for a mulligan)
+        let kind = match (choice.choice.is_cashout(), lost) {
+            (true, _) => bankroll::Kind::Cashout,
+            (false, true) => bankroll::Kind::Lose,
+            (false, false) => bankroll::Kind::Win,
+        };
+        match prompt.ledger().and_then(|ledger| {
+            let entry = ledger.record(kind, (pot - 1.0) * bet)?;
+            Ok(entry.map(|entry| (entry.amount, entry.balance)))
+        }) {
+            Ok(Some((amount, balance))) if !quiet => {
+                println!("bankroll {:.02} ({:+.02})", balance, amount)
+            }
+            Ok(_) => {}
+            Err(err) => print_error(options, &format!("failed to update the bankroll: {}", err)),
+        }
+    }
+    let steps = hand_luck_steps(&game.cursor, choice, card, pot);
+    game.luck += steps.iter().fold(0.0, |luck, step| luck + step.luck()) * bet;
+    game.finish(pot, quiet);
+    if !quiet {
+        print_luck(&steps, game, options.format);
+    }
+}
+/// Solves Ride The Bus, or the game of the game file, under the rules and deck of the options
+fn solve(solve_options: SolveOptions, options: &Options) -> DiscreteDecisionTree {
+    // the rules of the game (side bets, the jackpot, and variant and house rules) are
+    // the same whatever the strategy, which only picks the objective and tie break
+    let solve_options = SolveOptions {
+        side_bet: options.solve.side_bet,
+        jackpot: options.solve.jackpot,
+        card_swap: options.solve.card_swap,
+        double_down: options.config.double_down(),
+        insurance: options.insurance(),
+        mulligan: options.config.mulligan(),
+        ..solve_options
+    };
+    let first_decision = first_decision(options.config, options.game.as_ref());
+    DiscreteDecisionTree::solve_with(first_decision, &options.deck(), solve_options)
+}
+/// The first decision of Ride The Bus, or of the game file, which every other
+/// decision follows from
+fn first_decision(config: GameConfig, game: Option<&GameFile>) -> DiscreteDecision {
+    match game {
+        Some(game) => game.first_decision(config),
+        None => stage_decision([PickColor::Red, PickColor::Black], config),
+    }
+}
+fn main() {
+    // rust ignores SIGPIPE, so printing after the reader of piped output has gone
+    // would panic instead of exiting quietly like other command line tools
+    #[cfg(unix)]
+    // SAFETY: this runs first thing in main, before any other thread is started or
+    // handler installed, and SIG_DFL is a valid disposition for SIGPIPE
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+    let mut options = Options::from_args();
+    render::set_number_format(options.numbers);
+    render::set_accessible(options.accessible);
+    lang::set_lang(options.lang);
+    if let Err(err) = logging::init(options.log_level, options.log_file.as_deref()) {
+        println!("failed to open log file: {}", err);
+    }
+    // the session log has the EVs shown while playing, so nothing has to be solved
+    if let CliCommand::Review { path } = &options.command {
+        if let Err(err) = review::run(path) {
+            println!("failed to review session: {}", err);
+        }
+        return;
+    }
+    let (mut tree, mut solve_time) = load_or_solve(&options);
+
+    // without a seed, pick one from the clock (and show it, so the deals can be repeated)
+    let seed = *options.seed.get_or_insert_with(clock_seed);
+    match &options.command {
+        CliCommand::Interactive if options.verbosity == Verbosity::Quiet || options.machine => {}
+        CliCommand::Interactive => println!("dealing with seed {}", seed),
+        CliCommand::Solve => {
+            print_choices(&tree, &options, options.format, None);
+            print_stages(&tree, options.format);
+            return;
+        }
+        CliCommand::Batch { hand } => {
+            let hand = match hand.is_empty() {
+                true => read_cards(),
+                false => hand.clone(),
+            };
+            print_batch(&tree, &hand, options.format);
+            return;
+        }
+        CliCommand::Ev { hand } => {
+            print_evs(&tree, hand, options.format);
+            return;
+        }
+        &CliCommand::Simulate { hands } => {
+            print_autoplay(&tree, hands, seed, options.format);
+            return;
+        }
+        CliCommand::Review { .. } => unreachable!("sessions are reviewed before solving"),
+        CliCommand::Export(export) => {
+            match export {
+                ExportCommand::Report { path } => export_report(&tree, path, &options),
+                ExportCommand::Strategy { path } => export_strategy(&tree, path, &options),
+                ExportCommand::Stages { path } => export_stages(&tree, path, &options),
+                ExportCommand::Charts { dir } => export_charts(&tree, dir),
+                ExportCommand::Csv { path, all } => export_outcomes(&tree, path, &[], *all),
+                ExportCommand::Dot { path, depth } => export_dot(&tree, path, *depth),
+            }
+            return;
+        }
+    }
+    if options.tui {
+        #[cfg(not(target_os = "wasi"))]
+        if let Err(err) = tui::run(&tree, options.deck(), options.stages(), seed) {
+            println!("failed to run the full screen UI: {}", err);
+        }
+        // WASI modules have no terminal to take over, so the UI isn't available
+        #[cfg(target_os = "wasi")]
+        println!("the full screen UI isn't available when running as a WASI module");
+        return;
+    }
+    let mut prompt = Prompt::new(seed, options.deck(), options.machine);
+    // the same as starting with the 'record' command, before anything is entered
+    if let Some(path) = &options.record
+        && let Err(err) = prompt.start_recording(path, seed, &[])
+    {
+        println!("failed to start recording: {}", err);
+    }
+
+    // print the tutorial (unless a script is reading the output), then start the interactive loop
+    if options.format != Format::Json && options.verbosity != Verbosity::Quiet && !options.machine {
+        print_help();
+        println!();
+    }
+    // the games carry over when the game is solved again
+    let mut games = Vec::new();
+    let mut current = 0;
     loop {
+        let removed;
+        (removed, games, current) =
+            interactive_prompt(&tree, &options, &mut prompt, solve_time, games, current);
+        // the cards removed change every EV, so the game is solved again without them
+        options.removed.extend(removed);
+        prompt.deck = options.deck();
         println!();
-        interactive_prompt(&tree);
+        (tree, solve_time) = load_or_solve(&options);
+        println!();
+    }
+}
+/// Loads the solved game from the cache, or solves it, along with how long that took
+fn load_or_solve(options: &Options) -> (DiscreteDecisionTree, Duration) {
+    let start = Instant::now();
+    // lookups only print what was looked up, so they can be read by scripts
+    let quiet = match options.command {
+        CliCommand::Ev { .. } | CliCommand::Batch { .. } => true,
+        CliCommand::Interactive => options.verbosity == Verbosity::Quiet || options.machine,
+        _ => options.verbosity == Verbosity::Quiet,
+    };
+    let progress = |message: String| {
+        if !quiet {
+            println!("{}", message);
+        }
+    };
+    let cached = options.cache.as_deref().and_then(|path| {
+        match cache::load(
+            path,
+            options.solve,
+            options.config,
+            options.game.as_ref(),
+            &options.removed,
+        ) {
+            Ok(tree) => tree,
+            Err(err) => {
+                warn!(%err, "failed to load cache");
+                progress(format!("failed to load cache: {}", err));
+                None
+            }
+        }
+    });
+    let tree = match cached {
+        Some(tree) => {
+            info!(outcomes = tree.outcome_count(), elapsed = ?start.elapsed(), "loaded cache");
+            progress(format!(
+                "loaded {} games from cache in {:.04?}",
+                tree.outcome_count(),
+                start.elapsed()
+            ));
+            tree
+        }
+        None => {
+            // solve ride the bus
+            // this only takes a about a second, hence why it's fine we do this on every start
+            // unless it's cached
+            match options.removed.as_slice() {
+                [] => progress("solving ride the bus".to_owned()),
+                removed => progress(format!(
+                    "solving ride the bus without {}",
+                    removed
+                        .iter()
+                        .map(|card| card.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                )),
+            }
+            let tree = solve(options.solve, options);
+            progress(format!(
+                "analyzed {} games in {:.04?}",
+                tree.outcome_count(),
+                start.elapsed()
+            ));
+            if let Some(path) = &options.cache {
+                match cache::save(
+                    path,
+                    &tree,
+                    options.solve,
+                    options.config,
+                    options.game.as_ref(),
+                    &options.removed,
+                ) {
+                    Ok(()) => progress(format!("cached solved game to {}", path.display())),
+                    Err(err) => {
+                        warn!(%err, "failed to cache solved game");
+                        println!("failed to cache solved game: {}", err);
+                    }
+                }
+            }
+            tree
+        }
+    };
+    progress("all games considered, done!".to_owned());
+    (tree, start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ride_the_bus::decision::{builder::GameBuilder, event::Deck};
+
+    #[test]
+    fn replays_the_cards_dealt_in_practice() {
+        let path =
+            std::env::temp_dir().join(format!("ride-the-bus-{}.practice", std::process::id()));
+        let deck = GameConfig::default().deck();
+        // a red and a higher pick, each dealt a card
+        let picks = |prompt: &mut Prompt| {
+            let mut seen = Vec::new();
+            for _ in 0..2 {
+                assert!(matches!(prompt.read_command("").unwrap(), Command::Pick(_)));
+                seen.extend(prompt.deal_picked(&seen).unwrap());
+            }
+            seen
+        };
+
+        let mut recorded = Prompt::new(1, deck.clone(), true);
+        recorded
+            .replay
+            .extend(["pick red".to_owned(), "pick higher".to_owned()]);
+        recorded.start_recording(&path, 1, &[]).unwrap();
+        let dealt = picks(&mut recorded);
+        recorded.stop_recording();
+
+        // a different seed would deal different cards if they weren't replayed
+        let mut replayed = Prompt::new(2, deck, true);
+        replayed.start_replay(&path).unwrap();
+        assert!(matches!(replayed.read_command("").unwrap(), Command::Reset));
+        let redealt = picks(&mut replayed);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(dealt.len(), 2);
+        assert_eq!(redealt, dealt);
+        assert!(replayed.replay.is_empty());
+    }
+
+    #[test]
+    fn luck_of_an_optimal_hand_sums_to_the_final_pot_less_the_starting_ev() {
+        let game = GameBuilder::new()
+            .choice("Hearts", 5.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+            .with_cashout()
+            .stage()
+            .choice("Spades", 5.0, |cards: &[PlayingCard]| cards[0].suit() == 2)
+            .with_cashout()
+            .build();
+        let tree = DiscreteDecisionTree::solve_with(game, &Deck, SolveOptions::default());
+        let expected = tree.optimal().unwrap().expected_value;
+        // a hand that wins every stage, and one that loses the last
+        for hand in [["AH", "KS"], ["AH", "KH"]] {
+            let mut cursor = GameCursor::new(&tree);
+            let mut cards = hand
+                .iter()
+                .map(|card| card.parse::<PlayingCard>().ok().unwrap());
+            let (choice, card, pot) = loop {
+                let choice = cursor.tree().optimal().unwrap();
+                let card = cards.next();
+                let outcome = cursor.descend(choice.choice.name(), card).unwrap();
+                if outcome.next_decision().is_none() {
+                    break (choice, card, outcome.value);
+                }
+            };
+            let steps = hand_luck_steps(&cursor, choice, card, pot);
+            assert_eq!(steps.len(), 2);
+            let luck = steps.iter().map(LuckStep::luck).sum::<f64>();
+            assert!(
+                (luck - (pot - expected)).abs() < 1e-9,
+                "{luck} != {pot} - {expected}"
+            );
+        }
+    }
+
+    /// Solves the game dealt from a small deck of `cards`
+    fn solve_small(game: DiscreteDecision, cards: &[&str]) -> DiscreteDecisionTree {
+        let cards = cards
+            .iter()
+            .map(|card| card.parse::<PlayingCard>().ok().unwrap())
+            .collect::<Vec<_>>();
+        let deck = move |seen: &[PlayingCard]| {
+            cards
+                .iter()
+                .filter(|card| !seen.contains(card))
+                .map(|&card| (card, 1.0))
+                .collect()
+        };
+        DiscreteDecisionTree::solve_with(game, &deck, SolveOptions::default())
+    }
+    /// A single stage that pays 4x for a heart, dealt from the ace of hearts and
+    /// king of spades
+    fn two_card_hearts() -> DiscreteDecisionTree {
+        let game = GameBuilder::new()
+            .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+            .build();
+        solve_small(game, &["AH", "KS"])
+    }
+
+    #[test]
+    fn dot_export_writes_a_node_for_each_decision_choice_and_final_value() {
+        let path = std::env::temp_dir().join(format!("ride-the-bus-{}.dot", std::process::id()));
+        let tree = two_card_hearts();
+        // the decision, its choice, and the final value of each card
+        assert_eq!(export::write_dot(&tree, &path, 1).unwrap(), 4);
+        let dot = std::fs::read_to_string(&path).unwrap();
+        assert!(dot.starts_with("digraph ride_the_bus {"));
+        assert!(dot.contains("n0 -> n1 [style=bold];"));
+        assert!(dot.contains("n2 [shape=plaintext, label=\"4.00x\"];"));
+        assert!(dot.contains("n1 -> n3 [label=\"KS = "));
+        // only the decision is written without any depth
+        assert_eq!(export::write_dot(&tree, &path, 0).unwrap(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn csv_export_writes_a_row_for_each_outcome() {
+        let path = std::env::temp_dir().join(format!("ride-the-bus-{}.csv", std::process::id()));
+        let queen = "QC".parse::<PlayingCard>().ok().unwrap();
+        let rows = export::write_outcome_table(&two_card_hearts(), &path, &[queen], false);
+        assert_eq!(rows.unwrap(), 2);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            csv.lines().collect::<Vec<_>>(),
+            [
+                "stage,history,choice,card,value,probability",
+                "2,QC,Hearts,AH,4.000000,0.500000",
+                "2,QC,Hearts,KS,0.000000,0.500000",
+            ]
+        );
+
+        // any card, then a heart from the 2 cards left, which the whole tree adds rows
+        // for after each of the first cards
+        let game = GameBuilder::new()
+            .choice("Any", 2.0, |_: &[PlayingCard]| true)
+            .stage()
+            .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+            .build();
+        let tree = solve_small(game, &["AH", "KS", "QC"]);
+        assert_eq!(
+            export::write_outcome_table(&tree, &path, &[], false).unwrap(),
+            3
+        );
+        assert_eq!(
+            export::write_outcome_table(&tree, &path, &[], true).unwrap(),
+            3 + 3 * 2
+        );
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.contains("\n2,KS,Hearts,AH,8.000000,0.500000\n"));
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -0,0 +1,214 @@
+use crate::PlayingCard;
+use std::io::{self, IsTerminal};
+use std::sync::OnceLock;
+
+/// The symbols of the suits, in the order of [`PlayingCard::suit`]
+pub const SUIT_SYMBOLS: &[&str] = &["♥", "♦", "♠", "♣"];
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether output is colored, which it isn't when `NO_COLOR` is set or stdout
+/// isn't a terminal (e.g. piped into a file)
+pub fn color_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        !no_color && io::stdout().is_terminal()
+    })
+}
+/// Wraps `text` in the ANSI `color`, if output is colored
+fn paint(color: &str, text: &str) -> String {
+    match color_enabled() {
+        true => format!("{color}{text}{RESET}"),
+        false => text.to_owned(),
+    }
+}
+static ACCESSIBLE: OnceLock<bool> = OnceLock::new();
+/// Sets whether output is made for screen readers, which can only be done once
+pub fn set_accessible(accessible: bool) {
+    let _ = ACCESSIBLE.set(accessible);
+}
+/// Whether output is made for screen readers, with cards spelled out and no
+/// arrows or other symbols that are read out one by one
+pub fn accessible() -> bool {
+    ACCESSIBLE.get().copied().unwrap_or(false)
+}
+/// The card's code (e.g. `QD`), or its name (e.g. `queen of diamonds`) for
+/// screen readers
+pub fn card_name(card: PlayingCard) -> String {
+    match (accessible(), card.is_joker()) {
+        (false, _) => card.to_string(),
+        (true, true) => "joker".to_owned(),
+        (true, false) => format!("{} of {}", card.rank_name(), card.suit_name()),
+    }
+}
+/// Renders the card, with red suits in red and black suits in the terminal's
+/// own foreground (white or black depending on the theme)
+pub fn card(card: PlayingCard) -> String {
+    // a joker has no suit, so it isn't red even though its color bit is
+    match card.color() {
+        0 if !card.is_joker() => paint(RED, &card_name(card)),
+        _ => card_name(card),
+    }
+}
+/// Renders the cards (see [`card`]) separated by spaces, or by commas once
+/// they're spelled out
+pub fn cards(cards: &[PlayingCard]) -> String {
+    let sep = if accessible() { ", " } else { " " };
+    cards.iter().map(|&c| card(c)).collect::<Vec<_>>().join(sep)
+}
+/// Points out the optimal choice, with an arrow or in words for screen readers
+pub fn optimal_marker() -> String {
+    match accessible() {
+        true => "(optimal)".to_owned(),
+        false => good("<----"),
+    }
+}
+/// Surrounds something the user should notice with `marks` (e.g. `???`), or
+/// makes it a sentence for screen readers
+pub fn banner(marks: &str, text: &str) -> String {
+    match accessible() {
+        true => format!("{}.", text),
+        false => format!("{} {} {}", marks, text, marks),
+    }
+}
+/// Highlights the optimal choice (or anything else that went well) in green
+pub fn good(text: &str) -> String {
+    paint(GREEN, text)
+}
+/// Highlights a bust (or anything else that went badly) in red
+pub fn bad(text: &str) -> String {
+    paint(RED, text)
+}
+
+/// How EVs and probabilities are printed, set once at startup with [`set_number_format`]
+#[derive(Clone, Copy)]
+pub struct NumberFormat {
+    /// The decimal places of values and probabilities (4 by default)
+    pub precision: usize,
+    /// Print probabilities as percentages, e.g. `50.00%` instead of `0.5000`
+    pub percent: bool,
+    /// Print EVs as the return on the bet, e.g. `+22.50%` instead of `1.2250`
+    pub ev_return: bool,
+}
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            precision: 4,
+            percent: false,
+            ev_return: false,
+        }
+    }
+}
+static NUMBER_FORMAT: OnceLock<NumberFormat> = OnceLock::new();
+/// Sets how numbers are printed, which can only be done once
+pub fn set_number_format(format: NumberFormat) {
+    let _ = NUMBER_FORMAT.set(format);
+}
+fn number_format() -> NumberFormat {
+    NUMBER_FORMAT.get().copied().unwrap_or_default()
+}
+/// Renders a multiple of the bet (like a pot, regret, or std dev)
+pub fn value(value: f64) -> String {
+    format!("{:.*}", number_format().precision, value)
+}
+/// Renders a change in EV always with its sign, in percentage points of the bet
+/// if EVs are printed as returns
+pub fn gain(gain: f64) -> String {
+    let format = number_format();
+    match format.ev_return {
+        true => format!("{:+.*}%", format.precision.saturating_sub(2), gain * 100.0),
+        false => format!("{:+.*}", format.precision, gain),
+    }
+}
+/// Renders the margin of error of an EV (without a sign), in percentage points of
+/// the bet if EVs are printed as returns
+pub fn margin(margin: f64) -> String {
+    let format = number_format();
+    match format.ev_return {
+        true => format!("{:.*}%", format.precision.saturating_sub(2), margin * 100.0),
+        false => value(margin),
+    }
+}
+/// Renders an EV, as a return on the bet if set to
+///
+/// Percentages have 2 fewer decimal places, so they're as precise as the values
+pub fn ev(ev: f64) -> String {
+    let format = number_format();
+    match format.ev_return {
+        true => format!(
+            "{:+.*}%",
+            format.precision.saturating_sub(2),
+            (ev - 1.0) * 100.0
+        ),
+        false => value(ev),
+    }
+}
+/// Renders a probability, as a percentage if set to
+pub fn probability(probability: f64) -> String {
+    probability_places(probability, number_format().precision)
+}
+/// Renders a probability that can be very small (like a payout) with 2 more
+/// decimal places than other probabilities
+pub fn small_probability(probability: f64) -> String {
+    probability_places(probability, number_format().precision + 2)
+}
+fn probability_places(probability: f64, places: usize) -> String {
+    match number_format().percent {
+        true => format!("{:.*}%", places.saturating_sub(2), probability * 100.0),
+        false => format!("{:.*}", places, probability),
+    }
+}
+
+/// Renders the cards as small card faces placed side by side
+///
+/// Red suits (hearts and diamonds) are colored using ANSI escape codes, if output
+/// is colored (see [`color_enabled`])
+///
+/// ```text
+/// ┌─────┐ ┌─────┐
+/// │7    │ │10   │
+/// │  ♥  │ │  ♠  │
+/// │    7│ │   10│
+/// └─────┘ └─────┘
+/// ```
+pub fn card_faces(cards: &[PlayingCard]) -> String {
+    let mut lines: [String; 5] = Default::default();
+    for (i, &card) in cards.iter().enumerate() {
+        let (color, reset) = match card.color() == 0 && !card.is_joker() && color_enabled() {
+            true => (RED, RESET),
+            _ => ("", ""),
+        };
+        let rank = card.rank_label();
+        let suit = match card.is_joker() {
+            true => "★",
+            false => SUIT_SYMBOLS[card.suit() as usize],
+        };
+
+        // cards are separated by a single space
+        let sep = if i == 0 { "" } else { " " };
+        lines[0] += &format!("{sep}┌─────┐");
+        lines[1] += &format!("{sep}│{color}{rank:<5}{reset}│");
+        lines[2] += &format!("{sep}│  {color}{suit}{reset}  │");
+        lines[3] += &format!("{sep}│{color}{rank:>5}{reset}│");
+        lines[4] += &format!("{sep}└─────┘");
+    }
+    lines.join("\n")
+}
+
+const BAR_WIDTH: usize = 20;
+const PARTIAL_BLOCKS: &[char] = &[' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Renders a horizontal bar filled proportionally to `fraction` (0.0-1.0)
+///
+/// The bar is always the same width, using eighth blocks for the partially
+/// filled cell so that small differences are still visible
+pub fn bar(fraction: f64) -> String {
+    let eighths = (fraction.clamp(0.0, 1.0) * (BAR_WIDTH * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(PARTIAL_BLOCKS[eighths % 8]);
+    }
+    format!("{:<width$}", bar, width = BAR_WIDTH)
+}
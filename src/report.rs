@@ -0,0 +1,104 @@
+use crate::PlayingCard;
+use crate::chart;
+use crate::decision::solver::DiscreteDecisionTree;
+use crate::export;
+use crate::render;
+use std::{error::Error, fmt::Write as _, fs, path::Path};
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 960px; margin: 2em auto; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 4px 10px; text-align: right; }
+th:first-child, td:first-child { text-align: left; }";
+
+/// Writes a standalone HTML report of the solved game to `path`: the value of each
+/// stage, charts, the payout distribution, and the optimal choice for every stage
+/// and state (see [`export::stage_strategy`])
+pub fn write_report(
+    tree: &DiscreteDecisionTree,
+    path: &Path,
+    state_of: impl Fn(&[PlayingCard]) -> String,
+) -> Result<(), Box<dyn Error>> {
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, "<html><head><meta charset=\"utf-8\">")?;
+    writeln!(html, "<title>Ride The Bus Strategy</title>")?;
+    writeln!(html, "<style>{}</style></head><body>", STYLE)?;
+    writeln!(html, "<h1>Ride The Bus Strategy</h1>")?;
+    if let Some(optimal) = tree.optimal() {
+        writeln!(
+            html,
+            "<p>Playing optimally, a hand has an expected value of {} and a bust probability of {}.</p>",
+            render::ev(optimal.expected_value),
+            render::probability(optimal.bust_probability)
+        )?;
+    }
+
+    writeln!(html, "<h2>Stages</h2>")?;
+    writeln!(
+        html,
+        "<table><tr><th>Stage</th><th>P(reach)</th><th>Expected Value</th><th>Expected Value if survived</th></tr>"
+    )?;
+    for (i, stage) in export::stage_values(tree).iter().enumerate() {
+        let survived = stage.survived_value.map_or("-".to_owned(), render::ev);
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            i + 1,
+            render::probability(stage.reach_probability),
+            render::ev(stage.expected_value),
+            survived
+        )?;
+    }
+    writeln!(html, "</table>")?;
+    for svg in chart::render_charts(tree)? {
+        writeln!(html, "<div>{}</div>", svg)?;
+    }
+
+    writeln!(html, "<h2>Payout Distribution</h2>")?;
+    writeln!(
+        html,
+        "<table><tr><th>Final Pot</th><th>Probability</th></tr>"
+    )?;
+    let distribution = tree
+        .optimal()
+        .map(|c| c.payout_distribution())
+        .unwrap_or_default();
+    for (pot, probability) in distribution {
+        writeln!(
+            html,
+            "<tr><td>{:.02}x</td><td>{}</td></tr>",
+            pot,
+            render::small_probability(probability)
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    writeln!(html, "<h2>Optimal Choices</h2>")?;
+    writeln!(
+        html,
+        "<table><tr><th>Stage</th><th>State</th><th>Choice</th><th>P(win)</th><th>Expected Value</th></tr>"
+    )?;
+    for ((stage, state), row) in export::stage_strategy(tree, state_of) {
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            stage,
+            escape(&state),
+            escape(&row.choice),
+            render::probability(row.win_probability),
+            render::ev(row.expected_value)
+        )?;
+    }
+    writeln!(html, "</table>")?;
+    writeln!(html, "</body></html>")?;
+
+    fs::write(path, html)?;
+    Ok(())
+}
+
+/// Escapes text to be shown in HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,200 @@
+use crate::render;
+use ride_the_bus::PlayingCard;
+use serde::Deserialize;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, IsTerminal, Write},
+    path::Path,
+};
+
+/// An event of a session log written with `--record` (see [`crate::session::Session`])
+#[derive(Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum Event {
+    Decision(Decision),
+    Choice(Taken),
+    Finish {
+        pot: f64,
+        won: f64,
+    },
+    /// The start of the session and the lines entered, which are only needed to
+    /// play the session again
+    #[serde(other)]
+    Other,
+}
+#[derive(Deserialize)]
+struct Decision {
+    time: u64,
+    stage: usize,
+    stages: usize,
+    cards: Vec<String>,
+    pot: f64,
+    choices: Vec<Shown>,
+}
+/// A choice shown at a decision
+#[derive(Deserialize)]
+struct Shown {
+    choice: String,
+    expected_value: f64,
+    optimal: bool,
+}
+/// The choice taken at a decision
+#[derive(Deserialize)]
+struct Taken {
+    choice: String,
+    card: Option<String>,
+    optimal: bool,
+    regret: f64,
+}
+
+/// A decision of the session, with the choice taken and how the hand finished (if it did)
+struct Step {
+    decision: Decision,
+    taken: Taken,
+    finished: Option<(f64, f64)>,
+}
+
+/// Steps through the session logged at `path`, printing the choices shown at each
+/// decision, the optimal ones, and the choice taken
+///
+/// Each step waits for enter (or 'b' to go back, 'q' to quit) when stdin is a
+/// terminal, otherwise every step is printed at once
+pub fn run(path: &Path) -> io::Result<()> {
+    let steps = read_steps(path)?;
+    let start = steps.first().map_or(0, |step| step.decision.time);
+    println!("{} decisions taken in {}", steps.len(), path.display());
+    let interactive = io::stdin().is_terminal();
+    let mut idx = 0;
+    while let Some(step) = steps.get(idx) {
+        println!();
+        print_step(step, idx, steps.len(), start);
+        if !interactive {
+            idx += 1;
+            continue;
+        }
+        print!("[enter] next | b back | q quit ? ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            println!();
+            break;
+        }
+        match line.trim() {
+            "q" | "quit" | "exit" => break,
+            "b" | "back" => idx = idx.saturating_sub(1),
+            _ => idx += 1,
+        }
+    }
+    Ok(())
+}
+
+/// Reads the decisions of the session where a choice was taken, in order
+///
+/// Decisions shown again without a choice (e.g. after listing the events of one)
+/// are left out
+fn read_steps(path: &Path) -> io::Result<Vec<Step>> {
+    let mut steps = Vec::new();
+    let mut shown: Option<Decision> = None;
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str::<Event>(&line)
+            .map_err(|err| io::Error::other(format!("not a session log: {}", err)))?;
+        match event {
+            Event::Decision(decision) => shown = Some(decision),
+            Event::Choice(taken) => {
+                if let Some(decision) = shown.take() {
+                    steps.push(Step {
+                        decision,
+                        taken,
+                        finished: None,
+                    });
+                }
+            }
+            Event::Finish { pot, won } => {
+                if let Some(step) = steps.last_mut() {
+                    step.finished = Some((pot, won));
+                }
+            }
+            Event::Other => {}
+        }
+    }
+    Ok(steps)
+}
+
+fn print_step(step: &Step, idx: usize, steps: usize, start: u64) {
+    let decision = &step.decision;
+    let elapsed = decision.time.saturating_sub(start) as f64 / 1000.0;
+    let mut status = vec![
+        format!("stage {}/{}", decision.stage, decision.stages),
+        format!("pot {:.01}x", decision.pot),
+    ];
+    if !decision.cards.is_empty() {
+        status.push(cards(&decision.cards));
+    }
+    println!(
+        "[step {}/{} | +{:.01}s] [{}]",
+        idx + 1,
+        steps,
+        elapsed,
+        status.join(" | ")
+    );
+    for shown in &decision.choices {
+        let marker = match shown.optimal {
+            true => format!(" {}", render::optimal_marker()),
+            false => String::new(),
+        };
+        println!(
+            "{} = {}{}",
+            shown.choice,
+            render::ev(shown.expected_value),
+            marker
+        );
+    }
+
+    let taken = &step.taken;
+    let on = match &taken.card {
+        // without its color, since the line may be colored as a whole
+        Some(card) => match card.parse::<PlayingCard>() {
+            Ok(card) => format!(" on {}", render::card_name(card)),
+            Err(_) => format!(" on {}", card),
+        },
+        None => String::new(),
+    };
+    match taken.optimal {
+        true => println!("took {}{}, an optimal choice", taken.choice, on),
+        false => {
+            let optimal = decision
+                .choices
+                .iter()
+                .filter(|shown| shown.optimal)
+                .map(|shown| shown.choice.as_str())
+                .collect::<Vec<_>>();
+            let line = format!(
+                "took {}{}, regret {} (optimal was {})",
+                taken.choice,
+                on,
+                render::value(taken.regret),
+                optimal.join(" or ")
+            );
+            println!("{}", render::bad(&line));
+        }
+    }
+    if let Some((pot, won)) = step.finished {
+        println!("hand finished with a {:.02}x pot, won {:.02}", pot, won);
+    }
+}
+
+/// Renders the logged cards like the prompt does, or as logged if they can't be parsed
+fn cards(logged: &[String]) -> String {
+    match logged
+        .iter()
+        .map(|card| card.parse::<PlayingCard>().ok())
+        .collect::<Option<Vec<_>>>()
+    {
+        Some(cards) => render::cards(&cards),
+        None => logged.join(" "),
+    }
+}
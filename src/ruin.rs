@@ -0,0 +1,65 @@
+use rand::Rng;
+
+/// The chance of going broke betting the same amount on every hand, estimated by
+/// simulating many sessions
+pub struct Ruin {
+    /// The probability of going broke within the number of hands
+    pub broke_within: f64,
+    /// The probability of going broke before doubling the bankroll, however many
+    /// hands it takes
+    pub broke_before_doubling: f64,
+}
+
+/// The most hands a session is played for when waiting to go broke or double up,
+/// which is only reached if the pot hardly ever changes (e.g. always cashing out)
+const MAX_HANDS: usize = 100_000;
+
+/// Simulates `trials` sessions of betting `bet` on every hand, starting with
+/// `bankroll`, where the final pot of each hand (a multiple of the bet) is drawn
+/// from the `(pot, probability)` distribution
+///
+/// A session is broke once the bankroll can't cover the bet
+pub fn simulate(
+    distribution: &[(f64, f64)],
+    bankroll: f64,
+    bet: f64,
+    hands: usize,
+    trials: usize,
+    rng: &mut impl Rng,
+) -> Ruin {
+    let mut draw_pot = || {
+        let mut roll = rng.random::<f64>();
+        for &(pot, probability) in distribution {
+            if roll < probability {
+                return pot;
+            }
+            roll -= probability;
+        }
+        // the probabilities may sum to slightly less than 1
+        distribution.last().map_or(1.0, |&(pot, _)| pot)
+    };
+
+    let (mut broke_within, mut broke_before_doubling) = (0, 0);
+    for _ in 0..trials {
+        // play until broke, or until both doubled up and past the number of hands
+        let mut balance = bankroll;
+        let (mut broke_after, mut doubled) = (None, false);
+        for hand in 0..=MAX_HANDS.max(hands) {
+            if balance < bet {
+                broke_after = Some(hand);
+                break;
+            }
+            doubled |= balance >= bankroll * 2.0;
+            if doubled && hand >= hands {
+                break;
+            }
+            balance += (draw_pot() - 1.0) * bet;
+        }
+        broke_within += usize::from(broke_after.is_some_and(|hand| hand <= hands));
+        broke_before_doubling += usize::from(broke_after.is_some() && !doubled);
+    }
+    Ruin {
+        broke_within: broke_within as f64 / trials as f64,
+        broke_before_doubling: broke_before_doubling as f64 / trials as f64,
+    }
+}
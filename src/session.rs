@@ -0,0 +1,88 @@
+use ride_the_bus::{DiscreteDecisionTree, PlayingCard, decision::solver::ChoiceEval};
+use serde_json::{Value, json};
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A log of an interactive session recorded with 'record' (or `--record`), written as
+/// it's played with one line of JSON per event (each with the time in milliseconds
+/// since the unix epoch)
+///
+/// The lines entered are logged too, so the session can be played again with
+/// 'replay' (see [`command`])
+pub struct Session {
+    file: File,
+}
+impl Session {
+    /// Starts a session log at `path`, dealing with `seed`
+    pub fn create(path: &Path, seed: u64) -> io::Result<Self> {
+        let mut session = Self {
+            file: File::create(path)?,
+        };
+        session.write("start", json!({ "seed": seed }))?;
+        Ok(session)
+    }
+    /// Logs a line entered at the prompt
+    pub fn input(&mut self, line: &str) -> io::Result<()> {
+        self.write("input", json!({ "input": line }))
+    }
+    /// Logs the choices shown at a decision, as printed for programs reading the output
+    pub fn decision(&mut self, decision: Value) -> io::Result<()> {
+        self.write("decision", decision)
+    }
+    /// Logs the choice taken at the decision of the `tree`, and the card it was resolved
+    /// by (if it reveals one)
+    pub fn choice(
+        &mut self,
+        tree: &DiscreteDecisionTree,
+        choice: &ChoiceEval,
+        card: Option<PlayingCard>,
+    ) -> io::Result<()> {
+        // choices tied with the optimal one are optimal too
+        let ranked_choices = tree.ranked_choices();
+        let ranked = ranked_choices
+            .iter()
+            .find(|ranked| std::ptr::eq(ranked.choice, choice));
+        self.write(
+            "choice",
+            json!({
+                "choice": format!("{:?}", choice.choice),
+                "card": card.map(|card| card.to_string()),
+                "expected_value": choice.expected_value,
+                "optimal": ranked.is_some_and(|ranked| ranked.is_optimal),
+                "optimal_expected_value": tree.optimal().map(|optimal| optimal.expected_value),
+                "regret": choice.regret(),
+            }),
+        )
+    }
+    /// Logs the end of a hand, with the final `pot` and the `bet` it paid out on
+    pub fn finish(&mut self, pot: f64, bet: f64) -> io::Result<()> {
+        self.write(
+            "finish",
+            json!({ "pot": pot, "bet": bet, "won": pot * bet }),
+        )
+    }
+
+    fn write(&mut self, event: &str, mut fields: Value) -> io::Result<()> {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        fields["event"] = json!(event);
+        fields["time"] = json!(time);
+        writeln!(self.file, "{}", fields)
+    }
+}
+
+/// The command to replay from a line of a session log or a recording
+///
+/// Lines of the session log that aren't lines entered have nothing to replay
+pub fn command(line: &str) -> Option<String> {
+    if !line.trim_start().starts_with('{') {
+        return Some(line.to_owned());
+    }
+    let event = serde_json::from_str::<Value>(line).ok()?;
+    event["input"].as_str().map(|input| input.to_owned())
+}
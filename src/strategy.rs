@@ -0,0 +1,177 @@
+use crate::decision::event::RandomEvent;
+use crate::decision::solver::{ChoiceEval, DiscreteDecisionTree};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::BTreeMap, io};
+
+/// Collapses every decision reached by playing optimally into its stage and
+/// "relevant state" using `state_of`, which is given the cards kept so far
+/// (backwards, like [`crate::Choice::score`]), and finds the optimal choice for each
+///
+/// Only the optimal choice of each decision is followed, and the decisions made again
+/// after a rule like doubling down or insurance aren't stages of their own, so the
+/// choice of such a decision is the rule itself. Decisions that share a stage and
+/// state are considered equivalent, so only the first one found is kept
+pub fn optimal_choices<E: RandomEvent>(
+    tree: &DiscreteDecisionTree<E>,
+    state_of: impl Fn(&[E]) -> String,
+) -> BTreeMap<(usize, String), &ChoiceEval<E>> {
+    let mut choices = BTreeMap::new();
+    collect_optimal_choices(tree, &mut Vec::new(), None, &state_of, &mut choices);
+    choices
+}
+
+/// `revealed` is the card revealed by the last choice if it can still be discarded
+/// with a mulligan, in which case the decision is whether to keep it
+fn collect_optimal_choices<'a, E: RandomEvent>(
+    tree: &'a DiscreteDecisionTree<E>,
+    kept: &mut Vec<E>,
+    revealed: Option<E>,
+    state_of: &impl Fn(&[E]) -> String,
+    choices: &mut BTreeMap<(usize, String), &'a ChoiceEval<E>>,
+) {
+    let Some(optimal) = tree.optimal() else {
+        return;
+    };
+    // stages are numbered from 1, and there is one card kept per stage passed
+    if revealed.is_none() {
+        let key = (kept.len() + 1, state_of(kept));
+        choices.entry(key).or_insert(optimal);
+    }
+    // rules that make the decision again (e.g. doubling down) end up in the same stage
+    // and state with other choices, so they're left at the rule
+    if !optimal.choice.reveals_card() && !optimal.choice.ends_stage() {
+        return;
+    }
+
+    // descend into every decision the optimal choice can lead to, keeping the cards
+    // backwards
+    for outcome in optimal.iter() {
+        let Some(next) = outcome.next_decision() else {
+            continue;
+        };
+        // the stage ends with the card revealed now, or the one revealed before if
+        // it was kept
+        match (optimal.choice.ends_stage(), outcome.event.or(revealed)) {
+            (true, Some(card)) => {
+                kept.insert(0, card);
+                collect_optimal_choices(next, kept, None, state_of, choices);
+                kept.remove(0);
+            }
+            (true, None) => collect_optimal_choices(next, kept, None, state_of, choices),
+            (false, revealed) => collect_optimal_choices(next, kept, revealed, state_of, choices),
+        }
+    }
+}
+
+/// Quotes a field of a CSV row if it has a comma, quote, or line break in it (e.g. the
+/// name of a choice from a game file), doubling any quotes
+pub fn csv_field(field: &str) -> Cow<'_, str> {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => Cow::Owned(format!("\"{}\"", field.replace('"', "\"\""))),
+        false => Cow::Borrowed(field),
+    }
+}
+
+/// The optimal choice for each stage and state of a game (see [`optimal_choices`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyTable {
+    pub entries: Vec<StrategyEntry>,
+}
+/// The optimal choice for a stage in a given state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyEntry {
+    /// The stage, where `1` is the first decision
+    pub stage: usize,
+    pub state: String,
+    pub choice: String,
+}
+impl StrategyTable {
+    /// Creates the table for a solved tree, sorted by stage and state
+    pub fn of<E: RandomEvent>(
+        tree: &DiscreteDecisionTree<E>,
+        state_of: impl Fn(&[E]) -> String,
+    ) -> Self {
+        let entries = optimal_choices(tree, state_of)
+            .into_iter()
+            .map(|((stage, state), optimal)| StrategyEntry {
+                stage,
+                state,
+                choice: format!("{:?}", optimal.choice),
+            })
+            .collect();
+        Self { entries }
+    }
+    /// Looks up the optimal choice for a stage in a given state
+    pub fn choice(&self, stage: usize, state: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.stage == stage && entry.state == state)
+            .map(|entry| entry.choice.as_str())
+    }
+    /// Writes the table as CSV, with a header row
+    pub fn write_csv(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(writer, "stage,state,choice")?;
+        for entry in &self.entries {
+            writeln!(
+                writer,
+                "{},{},{}",
+                entry.stage,
+                csv_field(&entry.state),
+                csv_field(&entry.choice)
+            )?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlayingCard;
+    use crate::decision::builder::GameBuilder;
+    use crate::decision::solver::{DoubleDown, SolveOptions};
+
+    #[test]
+    fn follows_only_the_optimal_choices() {
+        let first_decision = GameBuilder::new()
+            .choice("Red", 2.0, |cards: &[PlayingCard]| cards[0].color() == 0)
+            .choice("Black", 2.0, |cards: &[PlayingCard]| cards[0].color() == 1)
+            .stage()
+            .choice("Hearts", 4.0, |cards: &[PlayingCard]| cards[0].suit() == 0)
+            .with_cashout()
+            .build();
+        let cards = ["AH", "AS", "KS"].map(|card| card.parse::<PlayingCard>().ok().unwrap());
+        let deck = move |history: &[PlayingCard]| {
+            cards
+                .iter()
+                .filter(|card| !history.contains(card))
+                .map(|&card| (card, 1.0))
+                .collect()
+        };
+        let options = SolveOptions {
+            double_down: Some(DoubleDown {
+                stages: 1 << 2,
+                multiplier: 2.0,
+            }),
+            ..Default::default()
+        };
+        let tree = DiscreteDecisionTree::solve_with(first_decision, &deck, options);
+
+        let choices = optimal_choices(&tree, |cards| cards.iter().map(|c| c.to_string()).collect())
+            .into_iter()
+            .map(|((stage, state), optimal)| (stage, state, format!("{:?}", optimal.choice)))
+            .collect::<Vec<_>>();
+        // red is never picked, so the heart is never kept, and doubling down is left at
+        // the rule rather than the decision made again after it
+        let expected = [(1, "", "Black"), (2, "AS", "Double"), (2, "KS", "Double")]
+            .map(|(stage, state, choice)| (stage, state.to_owned(), choice.to_owned()));
+        assert_eq!(choices, expected);
+    }
+
+    #[test]
+    fn quotes_csv_fields_that_need_it() {
+        assert_eq!(csv_field("Red"), "Red");
+        assert_eq!(csv_field("Red, or not"), "\"Red, or not\"");
+        assert_eq!(csv_field("The \"best\" pick"), "\"The \"\"best\"\" pick\"");
+    }
+}
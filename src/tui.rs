@@ -0,0 +1,379 @@
+use crate::render::{self, SUIT_SYMBOLS};
+use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Cell, List, ListItem, Paragraph, Row, Table, TableState},
+};
+use ride_the_bus::{
+    DiscreteDecisionTree, PlayingCard,
+    card::RANK_LABELS,
+    decision::cursor::GameCursor,
+    decision::event::{DeckSpec, EventSource},
+};
+use std::io;
+
+/// The most entries kept in the history log, older ones are dropped
+const MAX_LOG: usize = 500;
+
+/// A full screen session of playing hands against the solved game, showing the
+/// choices, the cards of the hand, what's left in the deck, and a log of what happened
+struct Session<'a> {
+    cursor: GameCursor<'a>,
+    deck: DeckSpec,
+    stages: usize,
+    rng: StdRng,
+    /// The row selected in the choices panel
+    selected: TableState,
+    /// A card being typed in, to play it instead of dealing one
+    input: String,
+    /// What happened so far, newest last
+    log: Vec<Line<'static>>,
+    /// The number of hands finished
+    hands: usize,
+    /// The amount won over every finished hand, as a multiple of the bet
+    net: f64,
+}
+
+/// Plays hands of the solved game in a full screen terminal UI until quit, dealing
+/// cards from `deck` with the `seed`
+///
+/// Choices are picked with the arrow keys and taken with Enter, which deals a card
+/// for them. Typing a card (e.g. `7H`) and pressing Enter plays that card instead,
+/// for following a game played with real cards
+pub fn run(
+    tree: &DiscreteDecisionTree,
+    deck: DeckSpec,
+    stages: usize,
+    seed: u64,
+) -> io::Result<()> {
+    let mut session = Session {
+        cursor: GameCursor::new(tree),
+        deck,
+        stages,
+        rng: StdRng::seed_from_u64(seed),
+        selected: TableState::default().with_selected(Some(0)),
+        input: String::new(),
+        log: vec![Line::raw(format!("dealing with seed {}", seed))],
+        hands: 0,
+        net: 0.0,
+    };
+    let mut terminal = ratatui::init();
+    let result = session.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl<'a> Session<'a> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+                KeyCode::Esc if self.input.is_empty() => return Ok(()),
+                KeyCode::Esc => self.input.clear(),
+                KeyCode::Up => self.select(-1),
+                KeyCode::Down => self.select(1),
+                KeyCode::Enter => self.take(),
+                // with nothing typed, backspace takes back the last step of the hand
+                KeyCode::Backspace if self.input.is_empty() && self.cursor.back() => {
+                    self.selected.select(Some(0));
+                    self.push_log(Line::raw("took back the last step"));
+                }
+                KeyCode::Backspace if self.input.is_empty() => {}
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Char(c) if c.is_ascii_alphanumeric() && self.input.len() < 3 => {
+                    self.input.push(c.to_ascii_uppercase())
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves the selection in the choices panel by `offset`, wrapping around
+    fn select(&mut self, offset: isize) {
+        let len = self.cursor.tree().iter().count() as isize;
+        if len == 0 {
+            return;
+        }
+        let selected = self.selected.selected().unwrap_or(0) as isize;
+        self.selected
+            .select(Some((selected + offset).rem_euclid(len) as usize));
+    }
+    /// The cards seen in the hand in progress
+    fn cards(&self) -> Vec<PlayingCard> {
+        self.cursor.events().collect()
+    }
+    /// Takes the selected choice, or plays the card typed in
+    fn take(&mut self) {
+        let tree = self.cursor.tree();
+        let ranked_choices = tree.ranked_choices();
+        if !self.input.is_empty() {
+            let input = std::mem::take(&mut self.input);
+            match input.parse::<PlayingCard>() {
+                Ok(card) => self.play_card(card),
+                Err(_) => self.push_log(Line::styled(
+                    format!("'{}' isn't a card", input),
+                    Style::new().fg(Color::Red),
+                )),
+            }
+            return;
+        }
+        let Some(ranked) = self.selected.selected().and_then(|i| ranked_choices.get(i)) else {
+            return;
+        };
+        let choice = ranked.choice;
+        let name = format!("{:?}", choice.choice);
+        if choice.choice.is_cashout() {
+            // cashing out doesn't depend on the card, so the hand ends here
+            self.push_log(Line::raw(format!("chose {}", name)));
+            self.finish(choice.expected_value);
+            return;
+        }
+        if !choice.choice.reveals_card() {
+            // choices that don't reveal a card have a single outcome
+            self.push_log(Line::raw(format!("chose {}", name)));
+            self.descend(&name, None);
+            return;
+        }
+        let seen = self.cards();
+        let card = self
+            .deck
+            .events(&seen)
+            .choose_weighted(&mut self.rng, |&(_, weight)| weight)
+            .ok()
+            .map(|&(card, _)| card);
+        match card {
+            Some(card) => {
+                self.push_log(Line::from(vec![
+                    Span::raw(format!("chose {}, dealt ", name)),
+                    card_span(card),
+                ]));
+                self.descend(&name, Some(card));
+            }
+            None => self.push_log(Line::raw("no cards left to deal")),
+        }
+    }
+    /// Plays a card dealt outside of the session, taking the choice it continues
+    fn play_card(&mut self, card: PlayingCard) {
+        if self.deck.copies(card) == 0 {
+            self.push_log(Line::raw(format!("{} isn't in the deck", card)));
+            return;
+        }
+        // the decisions are disjoint (except Cashout, which is always smaller), so the
+        // choice with the most EV for the card is the one it was dealt for
+        let choice = self.cursor.tree().iter().max_by(|c1, c2| {
+            let ev1 = c1.get(card).map(|o| o.value).unwrap_or(0.0);
+            let ev2 = c2.get(card).map(|o| o.value).unwrap_or(0.0);
+            f64::total_cmp(&ev1, &ev2)
+        });
+        let Some(choice) = choice.filter(|c| c.choice.reveals_card()) else {
+            self.push_log(Line::raw("no choice reveals a card here"));
+            return;
+        };
+        let name = format!("{:?}", choice.choice);
+        self.push_log(Line::from(vec![
+            card_span(card),
+            Span::raw(format!(", so you chose {}", name)),
+        ]));
+        self.descend(&name, Some(card));
+    }
+    /// Takes the choice resolved by the event, finishing the hand if it ends there
+    fn descend(&mut self, name: &str, event: Option<PlayingCard>) {
+        match self.cursor.descend(name, event) {
+            Some(outcome) if outcome.next_decision().is_none() => self.finish(outcome.value),
+            Some(_) => self.selected.select(Some(0)),
+            None => self.push_log(Line::styled(
+                "that card can't be dealt here",
+                Style::new().fg(Color::Red),
+            )),
+        }
+    }
+    /// Finishes the hand in progress with the final `pot`, then starts a new hand
+    fn finish(&mut self, pot: f64) {
+        self.hands += 1;
+        self.net += pot - 1.0;
+        let line = format!(
+            "hand {} finished with a {:.02}x pot (net {:+.02})",
+            self.hands, pot, self.net
+        );
+        // anything less than the bet back means a pick lost
+        let style = match pot < 1.0 {
+            true => Style::new().fg(Color::Red),
+            false => Style::new().fg(Color::Green),
+        };
+        self.push_log(Line::styled(line, style));
+        self.cursor.reset();
+        self.selected.select(Some(0));
+    }
+    fn push_log(&mut self, line: Line<'static>) {
+        self.log.push(line);
+        if self.log.len() > MAX_LOG {
+            self.log.remove(0);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [status, body, log, input] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(10),
+            Constraint::Length(10),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [choices, side] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(body);
+        let [cards, deck] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(side);
+
+        let tree = self.cursor.tree();
+        let hand = self.cards();
+        frame.render_widget(
+            Paragraph::new(format!(
+                "stage {}/{} | pot {:.02}x | {} hands | net {:+.02}",
+                self.cursor.stage(),
+                self.stages,
+                tree.pot(),
+                self.hands,
+                self.net
+            ))
+            .style(Style::new().add_modifier(Modifier::REVERSED)),
+            status,
+        );
+
+        let rows = tree.ranked_choices().into_iter().map(|ranked| {
+            let choice = ranked.choice;
+            let marker = match ranked.is_optimal {
+                true => Span::styled("<----", Style::new().fg(Color::Green)),
+                false => Span::raw(""),
+            };
+            Row::new(vec![
+                Cell::from(format!("{:?}", choice.choice)),
+                Cell::from(render::ev(choice.expected_value)),
+                Cell::from(render::probability(choice.win_probability())),
+                Cell::from(render::value(choice.std_dev())),
+                Cell::from(marker),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(10),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(5),
+            ],
+        )
+        .header(Row::new(["Choice", "EV", "P(win)", "Std Dev", ""]).style(Style::new().bold()))
+        .block(Block::bordered().title(" Choices "))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, choices, &mut self.selected);
+
+        let hand_line = match hand.is_empty() {
+            true => Line::raw("none"),
+            false => Line::from(
+                hand.iter()
+                    .flat_map(|&card| [card_span(card), Span::raw(" ")])
+                    .collect::<Vec<_>>(),
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(hand_line).block(Block::bordered().title(" Cards ")),
+            cards,
+        );
+
+        frame.render_widget(
+            Paragraph::new(self.deck_lines(&hand)).block(Block::bordered().title(" Deck ")),
+            deck,
+        );
+
+        // show the newest entries that fit
+        let height = log.height.saturating_sub(2) as usize;
+        let items = self
+            .log
+            .iter()
+            .skip(self.log.len().saturating_sub(height))
+            .cloned()
+            .map(ListItem::new)
+            .collect::<Vec<_>>();
+        frame.render_widget(
+            List::new(items).block(Block::bordered().title(" History ")),
+            log,
+        );
+
+        frame.render_widget(
+            Paragraph::new(format!(
+                "card: {:<3} | ↑↓ select, Enter take (or play the typed card), Backspace undo, Esc quit",
+                self.input
+            )),
+            input,
+        );
+    }
+    /// The cards left in the deck after the `hand`, counted by rank and by suit
+    fn deck_lines(&self, hand: &[PlayingCard]) -> Vec<Line<'static>> {
+        let events = self.deck.events(hand);
+        let (mut ranks, mut suits, mut jokers) = ([0.0; 13], [0.0; 4], 0.0);
+        for (card, weight) in events {
+            match card.is_joker() {
+                true => jokers += weight,
+                false => {
+                    ranks[(card.rank() - 2) as usize] += weight;
+                    suits[card.suit() as usize] += weight;
+                }
+            }
+        }
+        let total = ranks.iter().sum::<f64>() + jokers;
+        let mut lines = vec![Line::raw(format!("{} cards left", total))];
+        lines.push(Line::from(
+            suits
+                .iter()
+                .enumerate()
+                .flat_map(|(suit, count)| {
+                    let style = match suit < 2 {
+                        true => Style::new().fg(Color::Red),
+                        false => Style::new(),
+                    };
+                    [
+                        Span::styled(SUIT_SYMBOLS[suit], style),
+                        Span::raw(format!(" {:<3}", count)),
+                    ]
+                })
+                .collect::<Vec<_>>(),
+        ));
+        // ranks are listed high to low, a few to a line
+        let ranks = RANK_LABELS
+            .iter()
+            .zip(ranks)
+            .rev()
+            .map(|(label, count)| format!("{:>2} {:<3}", label, count))
+            .collect::<Vec<_>>();
+        lines.extend(ranks.chunks(5).map(|chunk| Line::raw(chunk.join(" "))));
+        if jokers > 0.0 {
+            lines.push(Line::raw(format!("JK {}", jokers)));
+        }
+        lines
+    }
+}
+
+/// The card as a span, with red suits in red
+fn card_span(card: PlayingCard) -> Span<'static> {
+    match card.color() == 0 && !card.is_joker() {
+        true => Span::styled(card.to_string(), Style::new().fg(Color::Red)),
+        false => Span::raw(card.to_string()),
+    }
+}